@@ -0,0 +1,15 @@
+; an in-house manifest: {"components": [{"vendor": "...", "artifact": "...", "revision": "..."}]}
+(object
+  (pair
+    key: (string (string_content) @vendor_key)
+    value: (string (string_content) @group))?
+  (pair
+    key: (string (string_content) @artifact_key)
+    value: (string (string_content) @name))
+  (pair
+    key: (string (string_content) @revision_key)
+    value: (string (string_content) @version))
+  (#eq? @vendor_key "vendor")
+  (#eq? @artifact_key "artifact")
+  (#eq? @revision_key "revision")
+) @block
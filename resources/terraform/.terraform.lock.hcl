@@ -0,0 +1,19 @@
+# This file is maintained automatically by "terraform init".
+# Manual edits may be lost in future updates.
+
+provider "registry.terraform.io/hashicorp/aws" {
+  version     = "5.31.0"
+  constraints = "~> 5.0"
+  hashes = [
+    "h1:ltxyuBWIy9cq0kIKDJH1jeWJy/y7XJLjS4QrsQK4plA=",
+    "zh:0cdb9c2083bf0902442384f7309367791e4640581652dda456f2d6d7abf0de8d",
+    "zh:2c3d2e5a8b6f2c7d5a8f5f1b0a4f3f2d1d0e9c8b7a6f5e4d3c2b1a0f9e8d7c6b",
+  ]
+}
+
+provider "registry.opentofu.org/integrations/github" {
+  version = "6.0.0"
+  hashes = [
+    "h1:QeeU8ld5Sm2s4zPjv3w3HGL2AK9VWl5zr5r5AaJ0GnE=",
+  ]
+}
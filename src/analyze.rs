@@ -1,2 +1,8 @@
 pub mod producers;
+pub mod sbom_compare;
+pub mod sbom_diff;
+pub mod sbom_effective_pom;
+pub mod sbom_explain;
 pub mod sbom_generate;
+#[cfg(target_os = "linux")]
+pub mod sbom_watch;
@@ -1,2 +1,17 @@
+pub mod binary;
+pub mod cocoapods;
+pub mod custom;
+pub mod dart;
+pub mod docker;
+pub mod github_actions;
+pub mod helm;
+pub mod ivy;
 pub mod maven;
+pub mod npm;
+pub mod plugin;
 pub mod producer;
+pub mod python;
+pub mod registry;
+pub mod sbt;
+pub mod swift;
+pub mod terraform;
@@ -0,0 +1,3 @@
+pub mod binary_producer;
+pub mod go_buildinfo;
+pub mod rust_auditable;
@@ -0,0 +1,225 @@
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::binary::go_buildinfo::GoBuildInfo;
+use crate::analyze::producers::binary::rust_auditable::AuditableInfo;
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{
+    Dependency, DependencyBuilder, DependencyEvidence, DependencyLocation, DependencyType,
+};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::location::Location;
+use crate::model::purl::get_purl;
+use crate::sbom::generate::PROPERTY_LOCAL_REPLACEMENT;
+use crate::utils::vfs::Vfs;
+use derive_builder::Builder;
+
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+
+/// Version of the main module when a Go binary is built from a local checkout.
+const GO_DEVEL_VERSION: &str = "(devel)";
+
+/// Producer for static Go and Rust binaries. It reads the module information the Go linker
+/// embeds in every binary and the dependency list added by `cargo auditable`, so that a
+/// binary can be analyzed without its sources.
+#[derive(Clone, Builder)]
+pub struct BinaryProducer {}
+
+fn is_elf_file(path: &Path, vfs: &dyn Vfs) -> bool {
+    match vfs.read_prefix(path, ELF_MAGIC.len()) {
+        Ok(magic) => magic.as_slice() == ELF_MAGIC,
+        Err(_) => false,
+    }
+}
+
+/// Binaries do not have lines, the location only references the file.
+fn get_binary_location(path: &Path) -> DependencyLocation {
+    let location = Location {
+        file: path.display().to_string(),
+        ..Default::default()
+    };
+    DependencyLocation {
+        block: location.clone(),
+        name: location,
+        version: None,
+    }
+}
+
+/// Get the purl of a Go module: the last element of the path is the name, the rest
+/// is the namespace (e.g. `pkg:golang/github.com/google/uuid@v1.6.0`).
+fn get_go_purl(path: &str, version: &str) -> String {
+    match path.rsplit_once('/') {
+        Some((namespace, name)) => get_purl("golang", Some(namespace), name, Some(version)),
+        None => get_purl("golang", None, path, Some(version)),
+    }
+}
+
+fn get_go_dependencies(path: &Path, build_info: &GoBuildInfo) -> anyhow::Result<Vec<Dependency>> {
+    let mut result = vec![];
+    let modules = build_info
+        .main
+        .iter()
+        .filter(|m| m.version != GO_DEVEL_VERSION)
+        .chain(build_info.dependencies.iter());
+
+    for module in modules {
+        // the code of a module replaced by a local directory is not the one of its version
+        let properties = match &module.local_replacement {
+            Some(directory) => vec![(PROPERTY_LOCAL_REPLACEMENT.to_string(), directory.clone())],
+            None => vec![],
+        };
+        result.push(
+            DependencyBuilder::default()
+                .name(module.path.clone())
+                .version(Some(module.version.clone()))
+                .location(Some(get_binary_location(path)))
+                .r#type(DependencyType::Library)
+                .purl(get_go_purl(module.path.as_str(), module.version.as_str()))
+                .evidence(DependencyEvidence::Compiled)
+                .properties(properties)
+                .build()?,
+        );
+    }
+    Ok(result)
+}
+
+fn get_rust_dependencies(
+    path: &Path,
+    auditable_info: &AuditableInfo,
+) -> anyhow::Result<Vec<Dependency>> {
+    let mut result = vec![];
+    for package in auditable_info.get_compiled_packages() {
+        result.push(
+            DependencyBuilder::default()
+                .name(package.name.clone())
+                .version(Some(package.version.clone()))
+                .location(Some(get_binary_location(path)))
+                .r#type(DependencyType::Library)
+                .purl(get_purl(
+                    "cargo",
+                    None,
+                    package.name.as_str(),
+                    Some(package.version.as_str()),
+                ))
+                .evidence(DependencyEvidence::Compiled)
+                .build()?,
+        );
+    }
+    Ok(result)
+}
+
+impl SbomProducer for BinaryProducer {
+    fn name(&self) -> &str {
+        "binary"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "binary"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec!["**/*"]
+    }
+
+    fn use_file(&self, path: &Path, configuration: &SbomProducerConfiguration) -> bool {
+        is_elf_file(path, configuration.vfs.as_ref())
+    }
+
+    fn is_per_file(&self) -> bool {
+        true
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+
+        for path in paths {
+            let file = match configuration.vfs.read(path) {
+                Ok(data) => data,
+                Err(e) => {
+                    result.diagnostics.push(Diagnostic::error(
+                        DiagnosticKind::ParseError,
+                        path,
+                        format!("cannot read binary: {}", e),
+                    ));
+                    continue;
+                }
+            };
+            let file = match object::File::parse(file.as_slice()) {
+                Ok(f) => f,
+                Err(e) => {
+                    result.diagnostics.push(Diagnostic::error(
+                        DiagnosticKind::ParseError,
+                        path,
+                        format!("cannot parse binary: {}", e),
+                    ));
+                    continue;
+                }
+            };
+
+            let mut dependencies = vec![];
+            if let Ok(build_info) = GoBuildInfo::from_object(&file) {
+                dependencies.push(get_go_dependencies(path, &build_info));
+            }
+            if let Ok(auditable_info) = AuditableInfo::from_object(&file) {
+                dependencies.push(get_rust_dependencies(path, &auditable_info));
+            }
+
+            for d in dependencies {
+                match d {
+                    Ok(d) => result.dependencies.extend(d),
+                    Err(e) => result.diagnostics.push(Diagnostic::error(
+                        DiagnosticKind::ParseError,
+                        path,
+                        format!("cannot get dependencies: {}", e),
+                    )),
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_utils::get_files;
+
+    #[test]
+    fn test_find_binary_dependencies() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/binary");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let producer = BinaryProducerBuilder::default().build().unwrap();
+        let mut files: Vec<PathBuf> = get_files(d.display().to_string().as_str())
+            .unwrap()
+            .into_iter()
+            .filter(|f| producer.use_file(f, &configuration))
+            .collect();
+        files.sort();
+        assert_eq!(files.len(), 2);
+
+        let dependencies = producer
+            .find_dependencies(files.as_slice(), &configuration)
+            .unwrap()
+            .dependencies;
+        let purls: Vec<&str> = dependencies.iter().map(|d| d.purl.as_str()).collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:golang/github.com/google/uuid@v1.6.0",
+                "pkg:golang/golang.org/x/sys@v0.22.0",
+                "pkg:cargo/anyhow@1.0.89",
+                "pkg:cargo/serde@1.0.210",
+            ]
+        );
+        assert!(dependencies
+            .iter()
+            .all(|d| d.evidence == DependencyEvidence::Compiled));
+    }
+}
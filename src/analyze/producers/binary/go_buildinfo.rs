@@ -0,0 +1,260 @@
+use anyhow::anyhow;
+use object::{Object, ObjectSection};
+
+pub const GO_BUILDINFO_SECTION: &str = ".go.buildinfo";
+
+const BUILDINFO_MAGIC: &[u8] = b"\xff Go buildinf:";
+const BUILDINFO_HEADER_SIZE: usize = 32;
+/// Flag set since Go 1.18: the version and module information are stored inline after the
+/// header instead of being pointers to strings somewhere else in the binary.
+const FLAG_INLINE_STRINGS: u8 = 0x2;
+/// The module information is surrounded by two 16 bytes sentinels.
+const MODINFO_SENTINEL_SIZE: usize = 16;
+
+/// A Go module compiled in a binary.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GoModule {
+    pub path: String,
+    pub version: String,
+    /// The local directory the module is replaced with (`replace x => ../x`), the path
+    /// and the version are then the ones of the replaced module
+    pub local_replacement: Option<String>,
+}
+
+/// The build information embedded by the Go linker, see `debug/buildinfo` in the Go
+/// standard library.
+#[derive(Clone, Debug, Default)]
+pub struct GoBuildInfo {
+    pub go_version: String,
+    pub main: Option<GoModule>,
+    pub dependencies: Vec<GoModule>,
+}
+
+/// Read a varint-prefixed string and return it with the rest of the buffer.
+fn decode_string(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let mut length: u64 = 0;
+    let mut shift = 0;
+    for (i, b) in data.iter().enumerate() {
+        if shift > 63 {
+            return None;
+        }
+        length |= ((b & 0x7f) as u64) << shift;
+        if b & 0x80 == 0 {
+            let start = i + 1;
+            let end = start.checked_add(usize::try_from(length).ok()?)?;
+            return Some((data.get(start..end)?, data.get(end..)?));
+        }
+        shift += 7;
+    }
+    None
+}
+
+fn read_pointer(data: &[u8], pointer_size: usize, big_endian: bool) -> Option<u64> {
+    let bytes = data.get(0..pointer_size)?;
+    match (pointer_size, big_endian) {
+        (4, false) => Some(u32::from_le_bytes(bytes.try_into().ok()?) as u64),
+        (4, true) => Some(u32::from_be_bytes(bytes.try_into().ok()?) as u64),
+        (8, false) => Some(u64::from_le_bytes(bytes.try_into().ok()?)),
+        (8, true) => Some(u64::from_be_bytes(bytes.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+/// Read `size` bytes at the virtual address `address` of the binary.
+fn read_at_address<'a>(file: &object::File<'a>, address: u64, size: u64) -> Option<&'a [u8]> {
+    file.sections()
+        .find_map(|s| s.data_range(address, size).ok().flatten())
+}
+
+/// Before Go 1.18, the header contains pointers to Go strings, a Go string being a
+/// pointer to the data followed by the length.
+fn read_go_string<'a>(
+    file: &object::File<'a>,
+    address: u64,
+    pointer_size: usize,
+    big_endian: bool,
+) -> Option<&'a [u8]> {
+    let header = read_at_address(file, address, (pointer_size * 2) as u64)?;
+    let data_address = read_pointer(header, pointer_size, big_endian)?;
+    let length = read_pointer(&header[pointer_size..], pointer_size, big_endian)?;
+    read_at_address(file, data_address, length)
+}
+
+/// Parse the module information, a list of tab-separated lines such as
+/// ```text
+/// path    github.com/org/app
+/// mod     github.com/org/app    (devel)
+/// dep     golang.org/x/sys    v0.1.0    h1:...
+/// =>      ../sys
+/// ```
+/// A replacement by a module keeps the path and the version of the new module, a
+/// replacement by a local directory has no version: the replaced module is kept.
+fn parse_modinfo(modinfo: &str, build_info: &mut GoBuildInfo) {
+    for line in modinfo.lines() {
+        let parts: Vec<&str> = line.split('\t').collect();
+        match parts.as_slice() {
+            ["mod", path, version, ..] => {
+                build_info.main = Some(GoModule {
+                    path: path.to_string(),
+                    version: version.to_string(),
+                    local_replacement: None,
+                })
+            }
+            ["dep", path, version, ..] => build_info.dependencies.push(GoModule {
+                path: path.to_string(),
+                version: version.to_string(),
+                local_replacement: None,
+            }),
+            // a replacement applies to the previous dependency
+            ["=>", path, rest @ ..] => {
+                let Some(last) = build_info.dependencies.last_mut() else {
+                    continue;
+                };
+                let version = rest.first().copied().unwrap_or_default();
+                if version.is_empty() || path.starts_with('.') || path.starts_with('/') {
+                    last.local_replacement = Some(path.to_string());
+                } else {
+                    last.path = path.to_string();
+                    last.version = version.to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl GoBuildInfo {
+    /// Read the build information of a Go binary, return an error if the binary was not
+    /// produced by Go.
+    pub fn from_object(file: &object::File<'_>) -> anyhow::Result<Self> {
+        let section = file
+            .section_by_name(GO_BUILDINFO_SECTION)
+            .ok_or(anyhow!("no {} section", GO_BUILDINFO_SECTION))?;
+        let data = section.data()?;
+
+        // the header is aligned on 16 bytes in the section
+        let header_start = (0..data.len())
+            .step_by(16)
+            .find(|i| data[*i..].starts_with(BUILDINFO_MAGIC))
+            .ok_or(anyhow!("no build information magic"))?;
+        let data = &data[header_start..];
+        if data.len() < BUILDINFO_HEADER_SIZE {
+            anyhow::bail!("build information header is truncated");
+        }
+
+        let pointer_size = data[14] as usize;
+        let flags = data[15];
+
+        let (version, modinfo) = if flags & FLAG_INLINE_STRINGS != 0 {
+            let (version, rest) = decode_string(&data[BUILDINFO_HEADER_SIZE..])
+                .ok_or(anyhow!("cannot read version"))?;
+            let (modinfo, _) = decode_string(rest).ok_or(anyhow!("cannot read modinfo"))?;
+            (version, modinfo)
+        } else {
+            let big_endian = flags != 0;
+            let version_address = read_pointer(&data[16..], pointer_size, big_endian)
+                .ok_or(anyhow!("cannot read version address"))?;
+            let modinfo_address = data
+                .get(16 + pointer_size..)
+                .and_then(|d| read_pointer(d, pointer_size, big_endian))
+                .ok_or(anyhow!("cannot read modinfo address"))?;
+            (
+                read_go_string(file, version_address, pointer_size, big_endian)
+                    .ok_or(anyhow!("cannot read version"))?,
+                read_go_string(file, modinfo_address, pointer_size, big_endian).unwrap_or_default(),
+            )
+        };
+
+        let mut build_info = GoBuildInfo {
+            go_version: String::from_utf8_lossy(version).to_string(),
+            ..Default::default()
+        };
+
+        // binaries built without module support do not have the sentinels
+        if modinfo.len() > 2 * MODINFO_SENTINEL_SIZE
+            && modinfo[modinfo.len() - MODINFO_SENTINEL_SIZE - 1] == b'\n'
+        {
+            let content = String::from_utf8_lossy(
+                &modinfo[MODINFO_SENTINEL_SIZE..modinfo.len() - MODINFO_SENTINEL_SIZE],
+            );
+            parse_modinfo(content.as_ref(), &mut build_info);
+        }
+
+        Ok(build_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_modinfo() {
+        let mut build_info = GoBuildInfo::default();
+        parse_modinfo(
+            "path\tgithub.com/org/app\nmod\tgithub.com/org/app\t(devel)\t\ndep\tgithub.com/google/uuid\tv1.6.0\th1:abc=\ndep\tgolang.org/x/sys\tv0.1.0\th1:def=\n=>\tgithub.com/fork/sys\tv0.2.0\th1:ghi=\nbuild\t-compiler=gc\n",
+            &mut build_info,
+        );
+
+        assert_eq!(build_info.main.unwrap().version, "(devel)");
+        assert_eq!(build_info.dependencies.len(), 2);
+        assert_eq!(build_info.dependencies[0].path, "github.com/google/uuid");
+        assert_eq!(build_info.dependencies[1].path, "github.com/fork/sys");
+        assert_eq!(build_info.dependencies[1].version, "v0.2.0");
+        assert_eq!(build_info.dependencies[1].local_replacement, None);
+    }
+
+    #[test]
+    fn test_parse_modinfo_local_replacement() {
+        let mut build_info = GoBuildInfo::default();
+        parse_modinfo(
+            "path\tgithub.com/org/app\nmod\tgithub.com/org/app\t(devel)\t\ndep\tgolang.org/x/sys\tv0.1.0\th1:def=\n=>\t../sys\t\t\ndep\tgolang.org/x/text\tv0.3.0\n=>\t/src/text\n",
+            &mut build_info,
+        );
+
+        assert_eq!(
+            build_info.dependencies,
+            vec![
+                GoModule {
+                    path: "golang.org/x/sys".to_string(),
+                    version: "v0.1.0".to_string(),
+                    local_replacement: Some("../sys".to_string()),
+                },
+                GoModule {
+                    path: "golang.org/x/text".to_string(),
+                    version: "v0.3.0".to_string(),
+                    local_replacement: Some("/src/text".to_string()),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_go_binary() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/binary/go-app");
+        let data = fs::read(d).unwrap();
+        let file = object::File::parse(data.as_slice()).unwrap();
+        let build_info = GoBuildInfo::from_object(&file).expect("build info is read");
+
+        assert_eq!(build_info.go_version, "go1.22.5");
+        assert_eq!(build_info.main.unwrap().path, "github.com/example/go-app");
+        assert_eq!(
+            build_info.dependencies,
+            vec![
+                GoModule {
+                    path: "github.com/google/uuid".to_string(),
+                    version: "v1.6.0".to_string(),
+                    local_replacement: None,
+                },
+                GoModule {
+                    path: "golang.org/x/sys".to_string(),
+                    version: "v0.22.0".to_string(),
+                    local_replacement: None,
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::anyhow;
+use flate2::read::ZlibDecoder;
+use object::{Object, ObjectSection};
+use serde::Deserialize;
+use std::io::Read;
+
+/// Section added by `cargo auditable`, it contains the dependency tree as zlib-compressed JSON.
+pub const CARGO_AUDITABLE_SECTION: &str = ".dep-v0";
+
+/// Do not decompress more than this, the section is attacker-controlled.
+const MAX_DECOMPRESSED_SIZE: u64 = 8 * 1024 * 1024;
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditablePackageKind {
+    Build,
+    #[default]
+    Runtime,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AuditablePackage {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub kind: AuditablePackageKind,
+    /// The root package is the binary itself.
+    #[serde(default)]
+    pub root: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AuditableInfo {
+    pub packages: Vec<AuditablePackage>,
+}
+
+impl AuditableInfo {
+    /// Read the dependency information embedded by `cargo auditable`.
+    pub fn from_object(file: &object::File<'_>) -> anyhow::Result<Self> {
+        let section = file
+            .section_by_name(CARGO_AUDITABLE_SECTION)
+            .ok_or(anyhow!("no {} section", CARGO_AUDITABLE_SECTION))?;
+        let data = section.data()?;
+
+        let mut json = String::new();
+        ZlibDecoder::new(data)
+            .take(MAX_DECOMPRESSED_SIZE)
+            .read_to_string(&mut json)?;
+
+        Ok(serde_json::from_str(json.as_str())?)
+    }
+
+    /// All the packages compiled in the binary: not the binary itself and not the
+    /// dependencies only used at build time (e.g. proc-macros and build scripts).
+    pub fn get_compiled_packages(&self) -> Vec<&AuditablePackage> {
+        self.packages
+            .iter()
+            .filter(|p| !p.root && p.kind == AuditablePackageKind::Runtime)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_read_auditable_binary() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/binary/rust-app");
+        let data = fs::read(d).unwrap();
+        let file = object::File::parse(data.as_slice()).unwrap();
+        let info = AuditableInfo::from_object(&file).expect("auditable info is read");
+
+        assert_eq!(info.packages.len(), 4);
+        let compiled = info.get_compiled_packages();
+        assert_eq!(compiled.len(), 2);
+        assert_eq!(compiled[0].name, "anyhow");
+        assert_eq!(compiled[0].version, "1.0.89");
+        assert_eq!(compiled[1].name, "serde");
+    }
+}
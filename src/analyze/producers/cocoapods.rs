@@ -0,0 +1 @@
+pub mod podfile_lock_producer;
@@ -0,0 +1,221 @@
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{Dependency, DependencyBuilder, DependencyLocation, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::position::LineIndex;
+use crate::model::purl::{add_purl_subpath, get_purl};
+use crate::utils::tree_sitter::language::get_tree_sitter_yaml;
+use crate::utils::tree_sitter::tree::{get_captured_node, get_node_location, get_tree, TreeCache};
+use crate::utils::tree_sitter::yaml::{get_mapping_pairs, get_scalar_value};
+use derive_builder::Builder;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    /// A pod in the `PODS` section, e.g. `Firebase/Core (10.0.0)`
+    static ref REGEX_POD: Regex = Regex::new(r"^(\S+) \((.+)\)$").unwrap();
+}
+
+const TREE_SITTER_QUERY_PODS: &str = r###"
+(block_mapping_pair
+  key: (flow_node) @pods
+  value: (block_node
+    (block_sequence
+      (block_sequence_item) @item
+    )
+  )
+  (#eq? @pods "PODS")
+)
+"###;
+
+/// Producer for the `Podfile.lock` file of CocoaPods.
+#[derive(Clone, Builder)]
+pub struct PodfileLockProducer {}
+
+/// A pod is either a scalar (`- Alamofire (5.8.1)`) or a mapping when it has dependencies
+/// (`- Firebase/Core (10.0.0):` followed by the list of dependencies). Returns the node
+/// with the pod name and version.
+fn get_pod_node<'tree>(
+    content: &str,
+    item: &tree_sitter::Node<'tree>,
+) -> Option<(String, tree_sitter::Node<'tree>)> {
+    let mut cursor = item.walk();
+    let value = item
+        .named_children(&mut cursor)
+        .find(|n| n.kind() != "comment")?;
+
+    match value.kind() {
+        "flow_node" => get_scalar_value(content, &value).map(|v| (v, value)),
+        _ => get_mapping_pairs(&value)
+            .into_iter()
+            .next()
+            .and_then(|(k, _)| get_scalar_value(content, &k).map(|v| (v, k))),
+    }
+}
+
+fn get_dependencies_from_content(
+    path: &Path,
+    content: &str,
+    language: &tree_sitter::Language,
+    query: &tree_sitter::Query,
+    trees: Option<&TreeCache>,
+) -> anyhow::Result<Vec<Dependency>> {
+    let mut result = vec![];
+    let path_string = path.display().to_string();
+    let tree =
+        get_tree(path, content, language, trees).ok_or(anyhow::anyhow!("cannot parse tree"))?;
+    let line_index = LineIndex::new(content);
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    for m in cursor.matches(query, tree.root_node(), content.as_bytes()) {
+        let Some(item) = get_captured_node(query, &m, "item") else {
+            continue;
+        };
+        let Some((pod, pod_node)) = get_pod_node(content, &item) else {
+            continue;
+        };
+        let Some(captures) = REGEX_POD.captures(pod.as_str()) else {
+            continue;
+        };
+        let (name, version) = (&captures[1], &captures[2]);
+
+        // subspecs (e.g. `Firebase/Core`) are a subpath of the pod
+        let purl = match name.split_once('/') {
+            Some((root, subspec)) => add_purl_subpath(
+                get_purl("cocoapods", None, root, Some(version)).as_str(),
+                subspec,
+            ),
+            None => get_purl("cocoapods", None, name, Some(version)),
+        };
+
+        let location = match (
+            get_node_location(&path_string, &line_index, &item),
+            get_node_location(&path_string, &line_index, &pod_node),
+        ) {
+            (Ok(block), Ok(name_location)) => Some(DependencyLocation {
+                block,
+                name: name_location.clone(),
+                version: Some(name_location),
+            }),
+            _ => None,
+        };
+
+        result.push(
+            DependencyBuilder::default()
+                .name(name.to_string())
+                .version(Some(version.to_string()))
+                .location(location)
+                .r#type(DependencyType::Library)
+                .purl(purl)
+                .build()?,
+        );
+    }
+
+    Ok(result)
+}
+
+impl SbomProducer for PodfileLockProducer {
+    fn name(&self) -> &str {
+        "podfile-lock"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "cocoapods"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec!["**/Podfile.lock"]
+    }
+
+    fn use_file(&self, path: &Path, _configuration: &SbomProducerConfiguration) -> bool {
+        match path.file_name() {
+            Some(e) => e == "Podfile.lock",
+            None => false,
+        }
+    }
+
+    fn ignored_directories(&self) -> Vec<&str> {
+        vec!["Pods"]
+    }
+
+    fn is_per_file(&self) -> bool {
+        true
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+        let language = get_tree_sitter_yaml();
+        let query =
+            tree_sitter::Query::new(&language, TREE_SITTER_QUERY_PODS).expect("got query pods");
+
+        for path in paths {
+            let dependencies = configuration.vfs.read_to_string(path).and_then(|content| {
+                get_dependencies_from_content(
+                    path,
+                    &content,
+                    &language,
+                    &query,
+                    configuration.trees.as_deref(),
+                )
+            });
+            match dependencies {
+                Ok(deps) => result.dependencies.extend(deps),
+                Err(e) => result.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    path,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_podfile_lock() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/cocoapods/Podfile.lock");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let dependencies = PodfileLockProducerBuilder::default()
+            .build()
+            .unwrap()
+            .find_dependencies(&[d], &configuration)
+            .unwrap()
+            .dependencies;
+
+        let purls: Vec<&str> = dependencies.iter().map(|d| d.purl.as_str()).collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:cocoapods/Alamofire@5.8.1",
+                "pkg:cocoapods/Firebase@10.22.0#Core",
+                "pkg:cocoapods/Firebase@10.22.0#CoreOnly",
+                "pkg:cocoapods/GoogleUtilities@7.13.0#NSData+zlib",
+            ]
+        );
+        assert_eq!(dependencies[1].name, "Firebase/Core");
+        assert_eq!(
+            dependencies[1]
+                .location
+                .clone()
+                .unwrap()
+                .block
+                .start
+                .line
+                .get(),
+            3
+        );
+    }
+}
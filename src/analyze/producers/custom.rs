@@ -0,0 +1,2 @@
+pub mod custom_producer;
+pub mod definition;
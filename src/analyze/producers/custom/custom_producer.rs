@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::custom::definition::{
+    CAPTURE_BLOCK, CAPTURE_GROUP, CAPTURE_NAME, CAPTURE_VERSION,
+};
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{Dependency, DependencyBuilder, DependencyLocation, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::position::LineIndex;
+use crate::model::purl::encode_purl_component;
+use crate::utils::tree_sitter::language::get_tree_sitter_language;
+use crate::utils::tree_sitter::tree::{
+    get_captured_node, get_node_location, get_node_text, get_tree, TreeCache,
+};
+use derive_builder::Builder;
+
+/// Producer defined in the configuration file: the files matching a glob are parsed with
+/// a grammar and each match of the query is a dependency.
+#[derive(Clone, Builder)]
+pub struct CustomProducer {
+    pub name: String,
+    glob: globset::GlobMatcher,
+    grammar: String,
+    query: String,
+    purl_template: String,
+    component_type: DependencyType,
+}
+
+/// Replace the `{capture}` placeholders of a purl template by the encoded values. When a
+/// value is missing, the placeholder is removed with the `/` that follows it or the `@`
+/// that precedes it, so that `pkg:generic/{group}/{name}@{version}` works without group
+/// or version.
+pub fn render_purl_template(template: &str, values: &HashMap<&str, String>) -> String {
+    let mut result = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|e| start + e) else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        let key = &rest[start + 1..end];
+        rest = &rest[end + 1..];
+
+        match values.get(key).filter(|v| !v.is_empty()) {
+            Some(v) => result.push_str(&encode_purl_component(v)),
+            None => {
+                if let Some(r) = rest.strip_prefix('/') {
+                    rest = r;
+                } else if result.ends_with('@') {
+                    result.pop();
+                }
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Get the text of a captured node, without the quotes of a string.
+fn get_capture_value(code: &str, node: &tree_sitter::Node) -> String {
+    get_node_text(code, node)
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'')
+        .to_string()
+}
+
+impl CustomProducer {
+    fn get_dependencies_from_content(
+        &self,
+        path: &Path,
+        content: &str,
+        language: &tree_sitter::Language,
+        query: &tree_sitter::Query,
+        trees: Option<&TreeCache>,
+    ) -> anyhow::Result<Vec<Dependency>> {
+        let mut result = vec![];
+        let path_string = path.display().to_string();
+        let tree =
+            get_tree(path, content, language, trees).ok_or(anyhow::anyhow!("cannot parse tree"))?;
+        let line_index = LineIndex::new(content);
+
+        let mut cursor = tree_sitter::QueryCursor::new();
+        for m in cursor.matches(query, tree.root_node(), content.as_bytes()) {
+            let Some(name_node) = get_captured_node(query, &m, CAPTURE_NAME) else {
+                continue;
+            };
+
+            // all the captures can be used in the template, not only name, version and group
+            let values: HashMap<&str, String> = query
+                .capture_names()
+                .iter()
+                .filter_map(|c| {
+                    get_captured_node(query, &m, c).map(|n| (*c, get_capture_value(content, &n)))
+                })
+                .collect();
+            let name = values[CAPTURE_NAME].clone();
+            let version = values.get(CAPTURE_VERSION).cloned();
+            let version_node = get_captured_node(query, &m, CAPTURE_VERSION);
+            let block_node = get_captured_node(query, &m, CAPTURE_BLOCK).unwrap_or(name_node);
+
+            let location = match (
+                get_node_location(&path_string, &line_index, &block_node),
+                get_node_location(&path_string, &line_index, &name_node),
+            ) {
+                (Ok(block), Ok(name_location)) => Some(DependencyLocation {
+                    block,
+                    name: name_location,
+                    version: version_node
+                        .and_then(|n| get_node_location(&path_string, &line_index, &n).ok()),
+                }),
+                _ => None,
+            };
+
+            result.push(
+                DependencyBuilder::default()
+                    .name(match values.get(CAPTURE_GROUP) {
+                        Some(group) => format!("{}/{}", group, name),
+                        None => name,
+                    })
+                    .version(version)
+                    .purl(render_purl_template(&self.purl_template, &values))
+                    .location(location)
+                    .r#type(self.component_type)
+                    .build()?,
+            );
+        }
+
+        Ok(result)
+    }
+}
+
+impl SbomProducer for CustomProducer {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn ecosystem(&self) -> &str {
+        "custom"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec![self.glob.glob().glob()]
+    }
+
+    fn use_file(&self, path: &Path, configuration: &SbomProducerConfiguration) -> bool {
+        let relative_path = path
+            .strip_prefix(configuration.vfs.root_of(path))
+            .unwrap_or(path);
+        self.glob.is_match(relative_path)
+    }
+
+    fn is_per_file(&self) -> bool {
+        true
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+        let language = get_tree_sitter_language(&self.grammar)
+            .ok_or(anyhow::anyhow!("unknown grammar {}", self.grammar))?;
+        let query = tree_sitter::Query::new(&language, &self.query)?;
+
+        for path in paths {
+            let dependencies = configuration.vfs.read_to_string(path).and_then(|content| {
+                self.get_dependencies_from_content(
+                    path,
+                    &content,
+                    &language,
+                    &query,
+                    configuration.trees.as_deref(),
+                )
+            });
+            match dependencies {
+                Ok(deps) => result.dependencies.extend(deps),
+                Err(e) => result.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    path,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyze::producers::custom::definition::load_custom_producers;
+
+    #[test]
+    fn test_render_purl_template() {
+        let template = "pkg:generic/{group}/{name}@{version}";
+        let mut values = HashMap::from([
+            ("group", "acme".to_string()),
+            ("name", "billing client".to_string()),
+            ("version", "1.0".to_string()),
+        ]);
+        assert_eq!(
+            render_purl_template(template, &values),
+            "pkg:generic/acme/billing%20client@1.0"
+        );
+
+        values.remove("group");
+        values.remove("version");
+        assert_eq!(
+            render_purl_template(template, &values),
+            "pkg:generic/billing%20client"
+        );
+    }
+
+    #[test]
+    fn test_custom_producer() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/custom");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let producers = load_custom_producers(&d.join("sbom-generator.json")).unwrap();
+        assert_eq!(producers.len(), 1);
+        let producer = &producers[0];
+
+        let file = d.join("project/components.json");
+        assert!(producer.use_file(&file, &configuration));
+        assert!(!producer.use_file(&d.join("sbom-generator.json"), &configuration));
+
+        let dependencies = producer
+            .find_dependencies(&[file], &configuration)
+            .unwrap()
+            .dependencies;
+        let purls: Vec<&str> = dependencies.iter().map(|d| d.purl.as_str()).collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:generic/acme/billing-client@2.3.1",
+                "pkg:generic/internal-logger@0.9.0",
+            ]
+        );
+        assert_eq!(dependencies[0].name, "acme/billing-client");
+        assert_eq!(dependencies[1].r#type, DependencyType::Application);
+        let location = dependencies[1].location.clone().unwrap();
+        assert_eq!(location.block.start.line.get(), 4);
+        assert_eq!(location.version.unwrap().start.line.get(), 4);
+    }
+}
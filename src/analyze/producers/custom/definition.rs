@@ -0,0 +1,144 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+
+use crate::analyze::producers::custom::custom_producer::{CustomProducer, CustomProducerBuilder};
+use crate::model::dependency::DependencyType;
+use crate::utils::tree_sitter::language::get_tree_sitter_language;
+
+/// Capture that must be present in the query of a custom producer
+pub const CAPTURE_NAME: &str = "name";
+pub const CAPTURE_VERSION: &str = "version";
+pub const CAPTURE_GROUP: &str = "group";
+/// Optional capture for the location of the whole dependency declaration
+pub const CAPTURE_BLOCK: &str = "block";
+
+/// The configuration file given with `--config`, e.g.
+/// ```json
+/// {
+///   "producers": [
+///     {
+///       "name": "components",
+///       "files": "**/components.json",
+///       "grammar": "json",
+///       "query_file": "queries/components.scm",
+///       "purl": "pkg:generic/{group}/{name}@{version}"
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigurationFile {
+    #[serde(default)]
+    pub producers: Vec<CustomProducerDefinition>,
+}
+
+/// The definition of a producer in the configuration file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomProducerDefinition {
+    pub name: String,
+    /// Glob matched against the path of the files relative to the scanned directory
+    pub files: String,
+    /// Grammar used to parse the files: `xml`, `json` or `yaml`
+    pub grammar: String,
+    /// The tree-sitter query, inline
+    pub query: Option<String>,
+    /// The tree-sitter query, in a file relative to the configuration file
+    pub query_file: Option<String>,
+    /// Template of the purl, e.g. `pkg:generic/{group}/{name}@{version}`
+    pub purl: String,
+    /// Type of the components: `library` (default), `application` or `container`
+    #[serde(rename = "type")]
+    pub component_type: Option<String>,
+}
+
+impl CustomProducerDefinition {
+    /// Check the definition and build the producer. The query is compiled to report errors
+    /// when the configuration is loaded rather than when files are parsed.
+    fn to_producer(&self, configuration_directory: &Path) -> anyhow::Result<CustomProducer> {
+        let query = match (&self.query, &self.query_file) {
+            (Some(q), None) => q.clone(),
+            (None, Some(f)) => fs::read_to_string(configuration_directory.join(f))
+                .with_context(|| format!("cannot read query file {}", f))?,
+            _ => anyhow::bail!("exactly one of query and query_file must be set"),
+        };
+
+        let language = get_tree_sitter_language(&self.grammar)
+            .ok_or(anyhow!("unknown grammar {}", self.grammar))?;
+        let compiled_query = tree_sitter::Query::new(&language, &query)
+            .map_err(|e| anyhow!("invalid query: {}", e))?;
+        if compiled_query
+            .capture_index_for_name(CAPTURE_NAME)
+            .is_none()
+        {
+            anyhow::bail!("the query must have a @{} capture", CAPTURE_NAME);
+        }
+
+        let glob = globset::Glob::new(&self.files)
+            .with_context(|| format!("invalid glob {}", self.files))?
+            .compile_matcher();
+
+        Ok(CustomProducerBuilder::default()
+            .name(self.name.clone())
+            .glob(glob)
+            .grammar(self.grammar.clone())
+            .query(query)
+            .purl_template(self.purl.clone())
+            .component_type(match self.component_type.as_deref() {
+                Some(t) => DependencyType::from_str(t)
+                    .map_err(|_| anyhow!("unknown component type {}", t))?,
+                None => DependencyType::Library,
+            })
+            .build()?)
+    }
+}
+
+/// Read the configuration file and build all the custom producers it defines.
+pub fn load_custom_producers(path: &Path) -> anyhow::Result<Vec<CustomProducer>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("cannot read configuration {}", path.display()))?;
+    let configuration: ConfigurationFile = serde_json::from_str(&content)
+        .with_context(|| format!("invalid configuration {}", path.display()))?;
+    let configuration_directory = path.parent().unwrap_or(Path::new("."));
+
+    configuration
+        .producers
+        .iter()
+        .map(|d| {
+            d.to_producer(configuration_directory)
+                .with_context(|| format!("invalid producer {}", d.name))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_definitions() {
+        let definition = CustomProducerDefinition {
+            name: "test".to_string(),
+            files: "**/deps.json".to_string(),
+            grammar: "json".to_string(),
+            query: Some("(string) @value".to_string()),
+            query_file: None,
+            purl: "pkg:generic/{name}".to_string(),
+            component_type: None,
+        };
+        let error = definition.to_producer(Path::new(".")).err().unwrap();
+        assert!(error.to_string().contains("@name"));
+
+        let definition = CustomProducerDefinition {
+            grammar: "toml".to_string(),
+            query: Some("(string) @name".to_string()),
+            ..definition
+        };
+        assert!(definition.to_producer(Path::new(".")).is_err());
+    }
+}
@@ -0,0 +1 @@
+pub mod pubspec_lock_producer;
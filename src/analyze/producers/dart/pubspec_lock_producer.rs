@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{Dependency, DependencyBuilder, DependencyLocation, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::position::LineIndex;
+use crate::model::purl::get_purl_with_qualifiers;
+use crate::utils::tree_sitter::language::get_tree_sitter_yaml;
+use crate::utils::tree_sitter::tree::{get_captured_node, get_node_location, get_tree, TreeCache};
+use crate::utils::tree_sitter::yaml::{get_mapping_scalar, get_mapping_value, get_scalar_value};
+use derive_builder::Builder;
+
+const TREE_SITTER_QUERY_PACKAGES: &str = r###"
+(block_mapping_pair
+  key: (flow_node) @packages
+  value: (block_node
+    (block_mapping
+      (block_mapping_pair
+        key: (flow_node) @name
+        value: (block_node) @package
+      ) @block
+    )
+  )
+  (#eq? @packages "packages")
+)
+"###;
+
+/// Packages from the SDK (e.g. `flutter`) are not versioned independently.
+const SOURCE_SDK: &str = "sdk";
+const DEFAULT_REPOSITORIES: [&str; 2] = ["https://pub.dev", "https://pub.dartlang.org"];
+
+/// Producer for the `pubspec.lock` file of Dart and Flutter projects.
+#[derive(Clone, Builder)]
+pub struct PubspecLockProducer {}
+
+fn get_dependencies_from_content(
+    path: &Path,
+    content: &str,
+    language: &tree_sitter::Language,
+    query: &tree_sitter::Query,
+    trees: Option<&TreeCache>,
+) -> anyhow::Result<Vec<Dependency>> {
+    let mut result = vec![];
+    let path_string = path.display().to_string();
+    let tree =
+        get_tree(path, content, language, trees).ok_or(anyhow::anyhow!("cannot parse tree"))?;
+    let line_index = LineIndex::new(content);
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    for m in cursor.matches(query, tree.root_node(), content.as_bytes()) {
+        let (Some(name_node), Some(package), Some(block)) = (
+            get_captured_node(query, &m, "name"),
+            get_captured_node(query, &m, "package"),
+            get_captured_node(query, &m, "block"),
+        ) else {
+            continue;
+        };
+        let Some(name) = get_scalar_value(content, &name_node) else {
+            continue;
+        };
+        let Some((version, version_node)) = get_mapping_scalar(content, &package, "version") else {
+            continue;
+        };
+        let source = get_mapping_scalar(content, &package, "source").map(|(s, _)| s);
+        if source.as_deref() == Some(SOURCE_SDK) {
+            continue;
+        }
+
+        // packages hosted on another repository than pub.dev
+        let repository_url = get_mapping_value(content, &package, "description")
+            .and_then(|d| get_mapping_scalar(content, &d, "url"))
+            .map(|(u, _)| u)
+            .filter(|u| {
+                source.as_deref() == Some("hosted") && !DEFAULT_REPOSITORIES.contains(&u.as_str())
+            });
+        let qualifiers: Vec<(&str, &str)> = repository_url
+            .iter()
+            .map(|u| ("repository_url", u.as_str()))
+            .collect();
+
+        let location = match (
+            get_node_location(&path_string, &line_index, &block),
+            get_node_location(&path_string, &line_index, &name_node),
+        ) {
+            (Ok(block_location), Ok(name_location)) => Some(DependencyLocation {
+                block: block_location,
+                name: name_location,
+                version: get_node_location(&path_string, &line_index, &version_node).ok(),
+            }),
+            _ => None,
+        };
+
+        result.push(
+            DependencyBuilder::default()
+                .purl(get_purl_with_qualifiers(
+                    "pub",
+                    None,
+                    name.as_str(),
+                    Some(version.as_str()),
+                    qualifiers.as_slice(),
+                ))
+                .name(name)
+                .version(Some(version))
+                .location(location)
+                .r#type(DependencyType::Library)
+                .build()?,
+        );
+    }
+
+    Ok(result)
+}
+
+impl SbomProducer for PubspecLockProducer {
+    fn name(&self) -> &str {
+        "pubspec-lock"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "pub"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec!["**/pubspec.lock"]
+    }
+
+    fn use_file(&self, path: &Path, _configuration: &SbomProducerConfiguration) -> bool {
+        match path.file_name() {
+            Some(e) => e == "pubspec.lock",
+            None => false,
+        }
+    }
+
+    fn ignored_directories(&self) -> Vec<&str> {
+        vec![".dart_tool", "build"]
+    }
+
+    fn is_per_file(&self) -> bool {
+        true
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+        let language = get_tree_sitter_yaml();
+        let query = tree_sitter::Query::new(&language, TREE_SITTER_QUERY_PACKAGES)
+            .expect("got query packages");
+
+        for path in paths {
+            let dependencies = configuration.vfs.read_to_string(path).and_then(|content| {
+                get_dependencies_from_content(
+                    path,
+                    &content,
+                    &language,
+                    &query,
+                    configuration.trees.as_deref(),
+                )
+            });
+            match dependencies {
+                Ok(deps) => result.dependencies.extend(deps),
+                Err(e) => result.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    path,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pubspec_lock() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/dart/pubspec.lock");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let dependencies = PubspecLockProducerBuilder::default()
+            .build()
+            .unwrap()
+            .find_dependencies(&[d], &configuration)
+            .unwrap()
+            .dependencies;
+
+        let purls: Vec<&str> = dependencies.iter().map(|d| d.purl.as_str()).collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:pub/async@2.11.0",
+                "pkg:pub/http@1.2.1",
+                "pkg:pub/internal_utils@0.3.0?repository_url=https%3A%2F%2Fpub.example.com",
+            ]
+        );
+        let location = dependencies[1].location.clone().unwrap();
+        assert_eq!(location.name.start.line.get(), 17);
+        assert_eq!(location.version.unwrap().start.line.get(), 23);
+    }
+}
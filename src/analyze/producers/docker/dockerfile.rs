@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+
+use crate::model::dependency::{DependencyHash, HashAlgorithm};
+use crate::model::purl::get_purl_with_qualifiers;
+
+/// A word of an instruction with its byte offsets in the Dockerfile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Word {
+    pub value: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// An instruction of a Dockerfile (e.g. `FROM node:20 AS build`). Instructions spread on
+/// several lines with `\` are joined.
+#[derive(Clone, Debug)]
+pub struct Instruction {
+    pub keyword: String,
+    pub arguments: Vec<Word>,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A base image of a stage, after the build arguments have been substituted.
+#[derive(Clone, Debug)]
+pub struct BaseImage {
+    pub image: String,
+    pub platform: Option<String>,
+    /// The instruction that declares the image
+    pub instruction: Instruction,
+    /// The word that contains the image
+    pub word: Word,
+}
+
+/// Split a Dockerfile into instructions. Comments and empty lines are ignored, also
+/// in the middle of an instruction spread on several lines.
+pub fn get_instructions(content: &str) -> Vec<Instruction> {
+    let mut result = vec![];
+    let mut current: Option<Instruction> = None;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let (text, continued) = match line.trim_end().strip_suffix('\\') {
+            Some(t) => (t, true),
+            None => (line.trim_end(), false),
+        };
+
+        let mut words = vec![];
+        let mut word_start = None;
+        for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+            match (c.is_whitespace(), word_start) {
+                (true, Some(s)) => {
+                    words.push(Word {
+                        value: text[s..i].to_string(),
+                        start: line_start + s,
+                        end: line_start + i,
+                    });
+                    word_start = None;
+                }
+                (false, None) => word_start = Some(i),
+                _ => {}
+            }
+        }
+
+        let instruction = match current.as_mut() {
+            Some(i) => {
+                i.arguments.extend(words);
+                i
+            }
+            None => {
+                if words.is_empty() {
+                    continue;
+                }
+                let keyword = words.remove(0);
+                current.insert(Instruction {
+                    keyword: keyword.value.to_uppercase(),
+                    arguments: words,
+                    start: keyword.start,
+                    end: keyword.end,
+                })
+            }
+        };
+        if let Some(last) = instruction.arguments.last() {
+            instruction.end = last.end;
+        }
+
+        if !continued {
+            result.extend(current.take());
+        }
+    }
+    result.extend(current.take());
+    result
+}
+
+/// Substitute the variables `$VAR`, `${VAR}`, `${VAR:-default}` and `${VAR:+value}`.
+/// Unknown variables are replaced by an empty string, like `docker build` does.
+pub fn expand_variables(value: &str, variables: &HashMap<String, String>) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+
+        if chars.peek() == Some(&'{') {
+            chars.next();
+            let expression: String = chars.by_ref().take_while(|c| *c != '}').collect();
+            let (name, modifier) = match expression.split_once(':') {
+                Some((n, m)) => (n, Some(m)),
+                None => (expression.as_str(), None),
+            };
+            let variable = variables.get(name).filter(|v| !v.is_empty());
+            match (modifier, variable) {
+                (Some(m), None) if m.starts_with('-') => result.push_str(&m[1..]),
+                (Some(m), Some(_)) if m.starts_with('+') => result.push_str(&m[1..]),
+                (Some(m), None) if m.starts_with('+') => {}
+                (_, Some(v)) => result.push_str(v),
+                (_, None) => {}
+            }
+        } else {
+            let mut name = String::new();
+            while let Some(n) = chars.next_if(|n| n.is_ascii_alphanumeric() || *n == '_') {
+                name.push(n);
+            }
+            if name.is_empty() {
+                result.push('$');
+            } else if let Some(v) = variables.get(&name) {
+                result.push_str(v);
+            }
+        }
+    }
+    result
+}
+
+/// Get the base images of all the stages. The `ARG` declared before the first `FROM` are
+/// substituted in the image names. Stages built from a previous stage (`FROM build`) and
+/// `scratch` are not base images.
+pub fn get_base_images(content: &str) -> Vec<BaseImage> {
+    let mut result = vec![];
+    let mut global_arguments: HashMap<String, String> = HashMap::new();
+    let mut stages: Vec<String> = vec![];
+    let mut seen_from = false;
+
+    for instruction in get_instructions(content) {
+        match instruction.keyword.as_str() {
+            "ARG" if !seen_from => {
+                for argument in &instruction.arguments {
+                    let (name, value) = match argument.value.split_once('=') {
+                        Some((n, v)) => (n, v.trim_matches(|c| c == '"' || c == '\'')),
+                        None => (argument.value.as_str(), ""),
+                    };
+                    let value = expand_variables(value, &global_arguments);
+                    global_arguments.insert(name.to_string(), value);
+                }
+            }
+            "FROM" => {
+                seen_from = true;
+                let mut platform = None;
+                let mut words = instruction.arguments.iter();
+                let mut image_word = None;
+                for word in words.by_ref() {
+                    match word.value.strip_prefix("--platform=") {
+                        Some(p) => platform = Some(expand_variables(p, &global_arguments)),
+                        None if word.value.starts_with("--") => {}
+                        None => {
+                            image_word = Some(word.clone());
+                            break;
+                        }
+                    }
+                }
+                let Some(word) = image_word else {
+                    continue;
+                };
+
+                let alias = match (words.next(), words.next()) {
+                    (Some(a), Some(name)) if a.value.eq_ignore_ascii_case("as") => {
+                        Some(name.value.to_lowercase())
+                    }
+                    _ => None,
+                };
+
+                let image = expand_variables(&word.value, &global_arguments);
+                let is_stage = stages.contains(&image.to_lowercase());
+                stages.extend(alias);
+                if is_stage || image.is_empty() || image.eq_ignore_ascii_case("scratch") {
+                    continue;
+                }
+
+                result.push(BaseImage {
+                    image,
+                    // platforms that depend on the builder (e.g. $BUILDPLATFORM) are unknown
+                    platform: platform.filter(|p| !p.is_empty()),
+                    instruction: instruction.clone(),
+                    word,
+                });
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+pub const DEFAULT_REGISTRY: &str = "docker.io";
+
+/// A reference to an image, e.g. `ghcr.io/org/app:1.0@sha256:...`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageReference {
+    pub registry: String,
+    pub repository: String,
+    pub tag: Option<String>,
+    pub digest: Option<String>,
+}
+
+impl ImageReference {
+    /// Parse an image reference. Images on Docker Hub without namespace are in the
+    /// `library` namespace (e.g. `nginx` is `docker.io/library/nginx`).
+    pub fn parse(image: &str) -> Option<ImageReference> {
+        let (without_digest, digest) = match image.split_once('@') {
+            Some((i, d)) => (i, Some(d.to_string())),
+            None => (image, None),
+        };
+
+        // the tag is after the last `:` that is not part of the registry (`host:port/...`)
+        let (name, tag) = match without_digest.rsplit_once(':') {
+            Some((n, t)) if !t.contains('/') => (n, Some(t.to_string())),
+            _ => (without_digest, None),
+        };
+
+        let (registry, repository) = match name.split_once('/') {
+            Some((r, rest)) if r.contains('.') || r.contains(':') || r == "localhost" => {
+                (r.to_string(), rest.to_string())
+            }
+            _ => (DEFAULT_REGISTRY.to_string(), name.to_string()),
+        };
+        let registry = match registry.as_str() {
+            "index.docker.io" | "registry-1.docker.io" => DEFAULT_REGISTRY.to_string(),
+            _ => registry,
+        };
+        let repository = if registry == DEFAULT_REGISTRY && !repository.contains('/') {
+            format!("library/{}", repository)
+        } else {
+            repository
+        };
+
+        if repository.is_empty() || repository.ends_with('/') {
+            return None;
+        }
+
+        Some(ImageReference {
+            registry,
+            repository: repository.to_lowercase(),
+            tag,
+            digest,
+        })
+    }
+
+    /// The name of the image as written by users, e.g. `nginx` or `ghcr.io/org/app`
+    pub fn get_name(&self) -> String {
+        if self.registry == DEFAULT_REGISTRY {
+            self.repository
+                .strip_prefix("library/")
+                .unwrap_or(self.repository.as_str())
+                .to_string()
+        } else {
+            format!("{}/{}", self.registry, self.repository)
+        }
+    }
+
+    /// Get the purl of the image. Images pinned by digest on a registry other than Docker
+    /// Hub use the `oci` type, the digest being the version. Other images use the `docker`
+    /// type.
+    pub fn get_purl(&self, platform: Option<&str>) -> String {
+        let (namespace, name) = match self.repository.rsplit_once('/') {
+            Some((n, r)) => (Some(n), r),
+            None => (None, self.repository.as_str()),
+        };
+        let mut qualifiers: Vec<(&str, &str)> = platform.iter().map(|p| ("platform", *p)).collect();
+        if self.digest.is_some() {
+            if let Some(t) = self.tag.as_deref() {
+                qualifiers.push(("tag", t));
+            }
+        }
+
+        let version = self
+            .digest
+            .as_deref()
+            .or(self.tag.as_deref())
+            .unwrap_or("latest");
+
+        if self.registry == DEFAULT_REGISTRY {
+            return get_purl_with_qualifiers("docker", namespace, name, Some(version), &qualifiers);
+        }
+
+        let repository_url = format!("{}/{}", self.registry, self.repository);
+        match self.digest {
+            Some(_) => {
+                qualifiers.push(("repository_url", repository_url.as_str()));
+                get_purl_with_qualifiers("oci", None, name, Some(version), &qualifiers)
+            }
+            None => {
+                qualifiers.push(("repository_url", self.registry.as_str()));
+                get_purl_with_qualifiers("docker", namespace, name, Some(version), &qualifiers)
+            }
+        }
+    }
+
+    /// Get the SHA-256 digest of the image, if the image is pinned by digest.
+    pub fn get_hashes(&self) -> Vec<DependencyHash> {
+        self.digest
+            .as_deref()
+            .and_then(|d| d.strip_prefix("sha256:"))
+            .filter(|d| d.len() == 64 && d.chars().all(|c| c.is_ascii_hexdigit()))
+            .map(|d| DependencyHash {
+                algorithm: HashAlgorithm::Sha256,
+                content: d.to_lowercase(),
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_variables() {
+        let variables = HashMap::from([
+            ("VERSION".to_string(), "3.19".to_string()),
+            ("EMPTY".to_string(), "".to_string()),
+        ]);
+        assert_eq!(
+            expand_variables("alpine:$VERSION", &variables),
+            "alpine:3.19"
+        );
+        assert_eq!(
+            expand_variables("alpine:${VERSION}-slim", &variables),
+            "alpine:3.19-slim"
+        );
+        assert_eq!(expand_variables("node:${NODE:-20}", &variables), "node:20");
+        assert_eq!(expand_variables("node:${EMPTY:-20}", &variables), "node:20");
+        assert_eq!(expand_variables("a${VERSION:+-x}", &variables), "a-x");
+        assert_eq!(expand_variables("a$UNKNOWN", &variables), "a");
+    }
+
+    #[test]
+    fn test_parse_image_reference() {
+        assert_eq!(
+            ImageReference::parse("nginx"),
+            Some(ImageReference {
+                registry: "docker.io".to_string(),
+                repository: "library/nginx".to_string(),
+                tag: None,
+                digest: None,
+            })
+        );
+        assert_eq!(
+            ImageReference::parse("localhost:5000/team/app:1.2@sha256:abc"),
+            Some(ImageReference {
+                registry: "localhost:5000".to_string(),
+                repository: "team/app".to_string(),
+                tag: Some("1.2".to_string()),
+                digest: Some("sha256:abc".to_string()),
+            })
+        );
+        assert_eq!(
+            ImageReference::parse("bitnami/redis:7.2")
+                .unwrap()
+                .get_name(),
+            "bitnami/redis"
+        );
+    }
+}
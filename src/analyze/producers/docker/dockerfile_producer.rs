@@ -0,0 +1,190 @@
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::docker::dockerfile::{get_base_images, BaseImage, ImageReference};
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{Dependency, DependencyBuilder, DependencyLocation, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::location::Location;
+use crate::model::position::LineIndex;
+use derive_builder::Builder;
+
+/// Producer for the base images of the stages of a `Dockerfile` (or `Containerfile`).
+#[derive(Clone, Builder)]
+pub struct DockerfileProducer {}
+
+fn get_location(
+    file: &str,
+    line_index: &LineIndex,
+    start: usize,
+    end: usize,
+) -> anyhow::Result<Location> {
+    Ok(Location {
+        file: file.to_string(),
+        start: line_index.get_position(start)?,
+        end: line_index.get_position(end)?,
+    })
+}
+
+fn get_dependency(
+    path_string: &str,
+    line_index: &LineIndex,
+    base_image: &BaseImage,
+) -> anyhow::Result<Dependency> {
+    let reference = ImageReference::parse(&base_image.image)
+        .ok_or(anyhow::anyhow!("invalid image {}", base_image.image))?;
+
+    let location = match (
+        get_location(
+            path_string,
+            line_index,
+            base_image.instruction.start,
+            base_image.instruction.end,
+        ),
+        get_location(
+            path_string,
+            line_index,
+            base_image.word.start,
+            base_image.word.end,
+        ),
+    ) {
+        (Ok(block), Ok(name)) => Some(DependencyLocation {
+            block,
+            version: Some(name.clone()),
+            name,
+        }),
+        _ => None,
+    };
+
+    Ok(DependencyBuilder::default()
+        .name(reference.get_name())
+        .version(reference.tag.clone().or(reference.digest.clone()))
+        .purl(reference.get_purl(base_image.platform.as_deref()))
+        .location(location)
+        .r#type(DependencyType::Container)
+        .hashes(reference.get_hashes())
+        .build()?)
+}
+
+impl SbomProducer for DockerfileProducer {
+    fn name(&self) -> &str {
+        "dockerfile"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "docker"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec![
+            "**/Dockerfile",
+            "**/Containerfile",
+            "**/Dockerfile.*",
+            "**/*.Dockerfile",
+            "**/*.dockerfile",
+        ]
+    }
+
+    fn use_file(&self, path: &Path, _configuration: &SbomProducerConfiguration) -> bool {
+        match path.file_name().and_then(|f| f.to_str()) {
+            Some(f) => {
+                f == "Dockerfile"
+                    || f == "Containerfile"
+                    || f.starts_with("Dockerfile.")
+                    || f.ends_with(".Dockerfile")
+                    || f.ends_with(".dockerfile")
+            }
+            None => false,
+        }
+    }
+
+    fn is_per_file(&self) -> bool {
+        true
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+
+        for path in paths {
+            let content = match configuration.vfs.read_to_string(path) {
+                Ok(c) => c,
+                Err(e) => {
+                    result.diagnostics.push(Diagnostic::error(
+                        DiagnosticKind::ParseError,
+                        path,
+                        format!("cannot read: {}", e),
+                    ));
+                    continue;
+                }
+            };
+            let path_string = path.display().to_string();
+            let line_index = LineIndex::new(&content);
+
+            for base_image in get_base_images(&content) {
+                match get_dependency(&path_string, &line_index, &base_image) {
+                    Ok(d) => result.dependencies.push(d),
+                    Err(e) => result.diagnostics.push(
+                        Diagnostic::warning(
+                            DiagnosticKind::DroppedDependency,
+                            path,
+                            format!("cannot get image {}: {}", base_image.image, e),
+                        )
+                        .with_location(
+                            get_location(
+                                &path_string,
+                                &line_index,
+                                base_image.word.start,
+                                base_image.word.end,
+                            )
+                            .ok(),
+                        ),
+                    ),
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dockerfile_multi_stage() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/docker/multi-stage/Dockerfile");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let dependencies = DockerfileProducerBuilder::default()
+            .build()
+            .unwrap()
+            .find_dependencies(&[d], &configuration)
+            .unwrap()
+            .dependencies;
+
+        let purls: Vec<&str> = dependencies.iter().map(|d| d.purl.as_str()).collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:docker/library/golang@1.22-alpine?platform=linux%2Famd64",
+                "pkg:oci/static@sha256%3A6ae5fe659f28c6afe9cc2903aebc78a5c6ad3aaa3d9d0369760ac6aaea2529c8?repository_url=gcr.io%2Fdistroless%2Fstatic&tag=nonroot",
+                "pkg:docker/bitnami/redis@7.2?repository_url=registry.example.com%3A5000",
+            ]
+        );
+        assert_eq!(dependencies[0].name, "golang");
+        assert_eq!(dependencies[0].version.as_deref(), Some("1.22-alpine"));
+        assert_eq!(dependencies[1].r#type, DependencyType::Container);
+        assert_eq!(dependencies[1].hashes.len(), 1);
+
+        // the instruction of the second image is on two lines
+        let location = dependencies[1].location.clone().unwrap();
+        assert_eq!(location.block.start.line.get(), 14);
+        assert_eq!(location.name.start.line.get(), 15);
+    }
+}
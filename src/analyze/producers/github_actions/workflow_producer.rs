@@ -0,0 +1,275 @@
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::docker::dockerfile::ImageReference;
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{DependencyBuilder, DependencyLocation, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind, DiagnosticSeverity};
+use crate::model::position::LineIndex;
+use crate::model::purl::{add_purl_subpath, get_purl};
+use crate::sbom::generate::PROPERTY_PINNED;
+use crate::utils::tree_sitter::language::get_tree_sitter_yaml;
+use crate::utils::tree_sitter::tree::{get_captured_node, get_node_location, get_tree, TreeCache};
+use crate::utils::tree_sitter::yaml::get_scalar_value;
+use derive_builder::Builder;
+
+const DOCKER_PREFIX: &str = "docker://";
+
+/// All the `uses:` of a workflow: actions used by steps and reusable workflows used by jobs.
+const TREE_SITTER_QUERY_USES: &str = r###"
+(block_mapping_pair
+  key: (flow_node) @key
+  value: (flow_node) @uses
+  (#eq? @key "uses")
+) @block
+"###;
+
+/// Producer for the actions, reusable workflows and Docker images used by the GitHub
+/// Actions workflows (`.github/workflows/*.yml`).
+#[derive(Clone, Builder)]
+pub struct WorkflowProducer {}
+
+fn is_workflow_file(path: &Path) -> bool {
+    let is_yaml = path
+        .extension()
+        .map(|e| e == "yml" || e == "yaml")
+        .unwrap_or(false);
+    let Some(workflows) = path.parent() else {
+        return false;
+    };
+    is_yaml
+        && workflows
+            .file_name()
+            .map(|f| f == "workflows")
+            .unwrap_or(false)
+        && workflows
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|f| f == ".github")
+            .unwrap_or(false)
+}
+
+/// A commit SHA is the only reference that cannot be moved, a tag or a branch can be
+/// changed to point to different code.
+fn is_commit_sha(reference: &str) -> bool {
+    reference.len() == 40 && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Get the dependency used by `uses`, without its location. Local actions (`./path`)
+/// are part of the repository and return `None`.
+fn get_used_dependency(uses: &str) -> Option<DependencyBuilder> {
+    let mut builder = DependencyBuilder::default();
+
+    if let Some(image) = uses.strip_prefix(DOCKER_PREFIX) {
+        let reference = ImageReference::parse(image)?;
+        builder
+            .name(reference.get_name())
+            .version(reference.tag.clone().or(reference.digest.clone()))
+            .purl(reference.get_purl(None))
+            .hashes(reference.get_hashes())
+            .properties(vec![(
+                PROPERTY_PINNED.to_string(),
+                reference.digest.is_some().to_string(),
+            )])
+            .r#type(DependencyType::Container);
+        return Some(builder);
+    }
+
+    // `owner/repo[/path]@ref`, the path is a subdirectory of the action or a reusable workflow
+    let (name, reference) = uses.rsplit_once('@')?;
+    let mut segments = name.splitn(3, '/');
+    let (Some(owner), Some(repository)) = (segments.next(), segments.next()) else {
+        return None;
+    };
+    if owner.is_empty() || owner == "." || owner == ".." || repository.is_empty() {
+        return None;
+    }
+    let purl = get_purl("github", Some(owner), repository, Some(reference));
+    let purl = match segments.next() {
+        Some(path) => add_purl_subpath(&purl, path),
+        None => purl,
+    };
+
+    builder
+        .name(name.to_string())
+        .version(Some(reference.to_string()))
+        .purl(purl)
+        .properties(vec![(
+            PROPERTY_PINNED.to_string(),
+            is_commit_sha(reference).to_string(),
+        )])
+        .r#type(DependencyType::Application);
+    Some(builder)
+}
+
+fn get_dependencies_from_content(
+    path: &Path,
+    content: &str,
+    language: &tree_sitter::Language,
+    query: &tree_sitter::Query,
+    trees: Option<&TreeCache>,
+) -> anyhow::Result<SbomProducerResult> {
+    let mut result = SbomProducerResult::default();
+    let path_string = path.display().to_string();
+    let tree =
+        get_tree(path, content, language, trees).ok_or(anyhow::anyhow!("cannot parse tree"))?;
+    let line_index = LineIndex::new(content);
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    for m in cursor.matches(query, tree.root_node(), content.as_bytes()) {
+        let (Some(block), Some(uses_node)) = (
+            get_captured_node(query, &m, "block"),
+            get_captured_node(query, &m, "uses"),
+        ) else {
+            continue;
+        };
+        let Some(uses) = get_scalar_value(content, &uses_node) else {
+            continue;
+        };
+        let Some(mut builder) = get_used_dependency(uses.as_str()) else {
+            continue;
+        };
+
+        let location = match (
+            get_node_location(&path_string, &line_index, &block),
+            get_node_location(&path_string, &line_index, &uses_node),
+        ) {
+            (Ok(block_location), Ok(uses_location)) => Some(DependencyLocation {
+                block: block_location,
+                name: uses_location.clone(),
+                version: Some(uses_location),
+            }),
+            _ => None,
+        };
+
+        let dependency = builder.location(location.clone()).build()?;
+        if dependency
+            .properties
+            .contains(&(PROPERTY_PINNED.to_string(), false.to_string()))
+        {
+            result.diagnostics.push(
+                Diagnostic::new(
+                    DiagnosticSeverity::Info,
+                    DiagnosticKind::Notice,
+                    path,
+                    format!("{} is used with a mutable reference", uses),
+                )
+                .with_location(location.map(|l| l.name)),
+            );
+        }
+        result.dependencies.push(dependency);
+    }
+
+    Ok(result)
+}
+
+impl SbomProducer for WorkflowProducer {
+    fn name(&self) -> &str {
+        "github-workflow"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "github"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec![".github/workflows/*.yml", ".github/workflows/*.yaml"]
+    }
+
+    fn use_file(&self, path: &Path, _configuration: &SbomProducerConfiguration) -> bool {
+        is_workflow_file(path)
+    }
+
+    fn is_per_file(&self) -> bool {
+        true
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+        let language = get_tree_sitter_yaml();
+        let query =
+            tree_sitter::Query::new(&language, TREE_SITTER_QUERY_USES).expect("got query uses");
+
+        for path in paths {
+            let dependencies = configuration.vfs.read_to_string(path).and_then(|content| {
+                get_dependencies_from_content(
+                    path,
+                    &content,
+                    &language,
+                    &query,
+                    configuration.trees.as_deref(),
+                )
+            });
+            match dependencies {
+                Ok(r) => {
+                    result.dependencies.extend(r.dependencies);
+                    result.diagnostics.extend(r.diagnostics);
+                }
+                Err(e) => result.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    path,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workflow() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/github_actions/.github/workflows/ci.yml");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let producer = WorkflowProducerBuilder::default().build().unwrap();
+        assert!(producer.use_file(&d, &configuration));
+        let result = producer.find_dependencies(&[d], &configuration).unwrap();
+        let dependencies = result.dependencies;
+
+        let purls: Vec<&str> = dependencies.iter().map(|d| d.purl.as_str()).collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:github/actions/checkout@b4ffde65f46336ab88eb53be808477a3936bae11",
+                "pkg:github/actions/setup-node@v4",
+                "pkg:github/github/codeql-action@v3#init",
+                "pkg:docker/library/alpine@3.19",
+                "pkg:github/octo-org/shared-workflows@main#.github/workflows/release.yml",
+            ]
+        );
+
+        let pinned: Vec<&str> = dependencies
+            .iter()
+            .map(|d| d.properties[0].1.as_str())
+            .collect();
+        assert_eq!(pinned, vec!["true", "false", "false", "false", "false"]);
+        // the mutable references are reported, but are not problems
+        assert_eq!(result.diagnostics.len(), 4);
+        assert!(result
+            .diagnostics
+            .iter()
+            .all(|d| d.severity == DiagnosticSeverity::Info));
+        assert_eq!(dependencies[3].r#type, DependencyType::Container);
+        assert_eq!(
+            dependencies[1]
+                .location
+                .clone()
+                .unwrap()
+                .name
+                .start
+                .line
+                .get(),
+            14
+        );
+    }
+}
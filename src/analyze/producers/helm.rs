@@ -0,0 +1 @@
+pub mod helm_chart_producer;
@@ -0,0 +1,221 @@
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{Dependency, DependencyBuilder, DependencyLocation, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::position::LineIndex;
+use crate::model::purl::get_purl_with_qualifiers;
+use crate::utils::tree_sitter::language::get_tree_sitter_yaml;
+use crate::utils::tree_sitter::tree::{get_captured_node, get_node_location, get_tree, TreeCache};
+use crate::utils::tree_sitter::yaml::{get_mapping_scalar, get_sequence_items};
+use derive_builder::Builder;
+
+const CHART_YAML: &str = "Chart.yaml";
+const CHART_LOCK: &str = "Chart.lock";
+
+/// Subcharts stored next to the chart are part of the chart, not dependencies.
+const LOCAL_REPOSITORY_PREFIX: &str = "file://";
+
+const TREE_SITTER_QUERY_DEPENDENCIES: &str = r###"
+(stream
+  (document
+    (block_node
+      (block_mapping
+        (block_mapping_pair
+          key: (flow_node) @dependencies
+          value: (block_node) @list
+          (#eq? @dependencies "dependencies")
+        )
+      )
+    )
+  )
+)
+"###;
+
+/// Producer for the dependencies of a Helm chart. The versions come from `Chart.lock` when
+/// the chart has one, from `Chart.yaml` otherwise.
+#[derive(Clone, Builder)]
+pub struct HelmChartProducer {}
+
+fn get_dependencies_from_content(
+    path: &Path,
+    content: &str,
+    language: &tree_sitter::Language,
+    query: &tree_sitter::Query,
+    trees: Option<&TreeCache>,
+) -> anyhow::Result<Vec<Dependency>> {
+    let mut result = vec![];
+    let path_string = path.display().to_string();
+    let tree =
+        get_tree(path, content, language, trees).ok_or(anyhow::anyhow!("cannot parse tree"))?;
+    let line_index = LineIndex::new(content);
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    for m in cursor.matches(query, tree.root_node(), content.as_bytes()) {
+        let Some(list) = get_captured_node(query, &m, "list") else {
+            continue;
+        };
+
+        for item in get_sequence_items(&list) {
+            let (Some((name, name_node)), Some((version, version_node))) = (
+                get_mapping_scalar(content, &item, "name"),
+                get_mapping_scalar(content, &item, "version"),
+            ) else {
+                continue;
+            };
+            let repository = get_mapping_scalar(content, &item, "repository").map(|(r, _)| r);
+            if repository
+                .as_deref()
+                .is_some_and(|r| r.starts_with(LOCAL_REPOSITORY_PREFIX))
+            {
+                continue;
+            }
+
+            // repositories referenced by their alias (`@bitnami`) are not URLs
+            let qualifiers: Vec<(&str, &str)> = repository
+                .iter()
+                .filter(|r| r.contains("://"))
+                .map(|r| ("repository_url", r.as_str()))
+                .collect();
+
+            let location = match (
+                get_node_location(&path_string, &line_index, &item),
+                get_node_location(&path_string, &line_index, &name_node),
+            ) {
+                (Ok(block), Ok(name_location)) => Some(DependencyLocation {
+                    block,
+                    name: name_location,
+                    version: get_node_location(&path_string, &line_index, &version_node).ok(),
+                }),
+                _ => None,
+            };
+
+            result.push(
+                DependencyBuilder::default()
+                    .purl(get_purl_with_qualifiers(
+                        "helm",
+                        None,
+                        name.as_str(),
+                        Some(version.as_str()),
+                        qualifiers.as_slice(),
+                    ))
+                    .name(name)
+                    .version(Some(version))
+                    .location(location)
+                    .r#type(DependencyType::Application)
+                    .build()?,
+            );
+        }
+    }
+
+    Ok(result)
+}
+
+impl SbomProducer for HelmChartProducer {
+    fn name(&self) -> &str {
+        "helm-chart"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "helm"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec!["**/Chart.lock", "**/Chart.yaml"]
+    }
+
+    fn use_file(&self, path: &Path, configuration: &SbomProducerConfiguration) -> bool {
+        match path.file_name() {
+            Some(f) if f == CHART_LOCK => true,
+            Some(f) if f == CHART_YAML => {
+                !configuration.vfs.is_file(&path.with_file_name(CHART_LOCK))
+            }
+            _ => false,
+        }
+    }
+
+    fn is_per_file(&self) -> bool {
+        true
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+        let language = get_tree_sitter_yaml();
+        let query = tree_sitter::Query::new(&language, TREE_SITTER_QUERY_DEPENDENCIES)
+            .expect("got query dependencies");
+
+        for path in paths {
+            let dependencies = configuration.vfs.read_to_string(path).and_then(|content| {
+                get_dependencies_from_content(
+                    path,
+                    &content,
+                    &language,
+                    &query,
+                    configuration.trees.as_deref(),
+                )
+            });
+            match dependencies {
+                Ok(deps) => result.dependencies.extend(deps),
+                Err(e) => result.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    path,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_dependencies(directory: &str) -> Vec<Dependency> {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push(directory);
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let producer = HelmChartProducerBuilder::default().build().unwrap();
+        let paths: Vec<PathBuf> = [CHART_YAML, CHART_LOCK]
+            .iter()
+            .map(|f| d.join(f))
+            .filter(|p| p.exists() && producer.use_file(p, &configuration))
+            .collect();
+        producer
+            .find_dependencies(paths.as_slice(), &configuration)
+            .unwrap()
+            .dependencies
+    }
+
+    #[test]
+    fn test_chart_lock() {
+        let dependencies = get_dependencies("resources/helm/locked");
+        let purls: Vec<&str> = dependencies.iter().map(|d| d.purl.as_str()).collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:helm/postgresql@13.4.4?repository_url=https%3A%2F%2Fcharts.bitnami.com%2Fbitnami",
+                "pkg:helm/redis@18.6.1?repository_url=oci%3A%2F%2Fregistry-1.docker.io%2Fbitnamicharts",
+            ]
+        );
+        assert_eq!(dependencies[0].r#type, DependencyType::Application);
+        let location = dependencies[1].location.clone().unwrap();
+        assert!(location.block.file.ends_with(CHART_LOCK));
+        assert_eq!(location.version.unwrap().start.line.get(), 7);
+    }
+
+    #[test]
+    fn test_chart_yaml() {
+        let dependencies = get_dependencies("resources/helm/unlocked");
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(dependencies[0].name, "kube-prometheus-stack");
+        assert_eq!(dependencies[0].version.as_deref(), Some("56.6.2"));
+    }
+}
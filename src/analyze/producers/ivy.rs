@@ -0,0 +1 @@
+pub mod ivy_producer;
@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::maven::maven_file::{MavenDependency, MavenDependencyBuilder};
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{Dependency, DependencyLocation};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::position::LineIndex;
+use crate::utils::tree_sitter::language::get_tree_sitter_xml;
+use crate::utils::tree_sitter::tree::{get_captured_node, get_node_location, get_tree, TreeCache};
+use crate::utils::tree_sitter::xml::{get_element_query, get_tag_attribute};
+use crate::utils::vfs::Vfs;
+use derive_builder::Builder;
+
+const IVY_FILE: &str = "ivy.xml";
+const IVY_SETTINGS_FILE: &str = "ivysettings.xml";
+
+/// The queries of the Ivy files, compiled once. The dependencies are [MavenDependency]
+/// resolved like the ones of a `pom.xml`.
+struct IvyProducerContext {
+    query_info: tree_sitter::Query,
+    query_dependencies: tree_sitter::Query,
+    /// Properties declared in `ivysettings.xml` (`<property name="..." value="..."/>`) and
+    /// the property files it loads (`<properties file="..."/>`)
+    query_properties: tree_sitter::Query,
+    language: tree_sitter::Language,
+}
+
+impl IvyProducerContext {
+    fn new() -> Self {
+        IvyProducerContext {
+            query_info: get_element_query(&["info"]),
+            query_dependencies: get_element_query(&["dependency"]),
+            query_properties: get_element_query(&["property", "properties"]),
+            language: get_tree_sitter_xml(),
+        }
+    }
+}
+
+/// Producer for the dependencies declared in `ivy.xml` files. The properties used in the
+/// revisions are resolved with the closest `ivysettings.xml` in the parent directories.
+#[derive(Clone, Builder)]
+pub struct IvyProducer {}
+
+/// Read a Java properties file, only the `key=value` and `key: value` forms are supported.
+fn read_properties_file(path: &Path, vfs: &dyn Vfs) -> anyhow::Result<HashMap<String, String>> {
+    let content = vfs.read_to_string(path)?;
+    Ok(content
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with('!'))
+        .filter_map(|l| l.split_once(['=', ':']))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect())
+}
+
+/// Get the properties of an `ivysettings.xml` file. Properties loaded from a file are
+/// overridden by the ones declared in the settings. A properties file that is missing or
+/// outside of the scanned directory is not read and is reported in [diagnostics].
+fn get_settings_properties(
+    path: &Path,
+    vfs: &dyn Vfs,
+    trees: Option<&TreeCache>,
+    context: &IvyProducerContext,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> anyhow::Result<HashMap<String, String>> {
+    let content = vfs.read_to_string(path)?;
+    let tree = get_tree(path, &content, &context.language, trees)
+        .ok_or(anyhow::anyhow!("cannot parse"))?;
+    let mut from_files = HashMap::new();
+    let mut declared = HashMap::new();
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    for m in cursor.matches(
+        &context.query_properties,
+        tree.root_node(),
+        content.as_bytes(),
+    ) {
+        let Some(tag) = get_captured_node(&context.query_properties, &m, "tag") else {
+            continue;
+        };
+        if let Some((file, _)) = get_tag_attribute(&content, &tag, "file") {
+            // the path is confined before anything is checked on the disk: a missing file
+            // and a file outside of the root are reported the same way
+            match vfs.resolve_path(&path.with_file_name(&file)) {
+                Ok(p) if vfs.is_file(&p) => {
+                    if let Ok(properties) = read_properties_file(&p, vfs) {
+                        from_files.extend(properties);
+                    }
+                }
+                _ => diagnostics.push(Diagnostic::warning(
+                    DiagnosticKind::PathOutsideRoot,
+                    path,
+                    format!(
+                        "properties file {} is not read: it is missing or outside of the scanned directory",
+                        file
+                    ),
+                )),
+            }
+        } else if let (Some((name, _)), Some((value, _))) = (
+            get_tag_attribute(&content, &tag, "name"),
+            get_tag_attribute(&content, &tag, "value"),
+        ) {
+            declared.insert(name, value);
+        }
+    }
+
+    from_files.extend(declared);
+    Ok(from_files)
+}
+
+fn get_dependencies_from_ivy_file(
+    path: &Path,
+    vfs: &dyn Vfs,
+    trees: Option<&TreeCache>,
+    context: &IvyProducerContext,
+) -> anyhow::Result<Vec<MavenDependency>> {
+    let content = vfs.read_to_string(path)?;
+    let path_string = path.display().to_string();
+    let tree = get_tree(path, &content, &context.language, trees)
+        .ok_or(anyhow::anyhow!("cannot parse"))?;
+    let line_index = LineIndex::new(&content);
+
+    // the organisation of a dependency defaults to the one of the module
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let module_organisation = cursor
+        .matches(&context.query_info, tree.root_node(), content.as_bytes())
+        .find_map(|m| get_captured_node(&context.query_info, &m, "tag"))
+        .and_then(|info| get_tag_attribute(&content, &info, "organisation"))
+        .map(|(o, _)| o);
+
+    let mut result = vec![];
+    let mut cursor = tree_sitter::QueryCursor::new();
+    for m in cursor.matches(
+        &context.query_dependencies,
+        tree.root_node(),
+        content.as_bytes(),
+    ) {
+        let (Some(element), Some(tag)) = (
+            get_captured_node(&context.query_dependencies, &m, "element"),
+            get_captured_node(&context.query_dependencies, &m, "tag"),
+        ) else {
+            continue;
+        };
+        let Some((name, name_node)) = get_tag_attribute(&content, &tag, "name") else {
+            continue;
+        };
+        let Some(organisation) = get_tag_attribute(&content, &tag, "org")
+            .map(|(o, _)| o)
+            .or(module_organisation.clone())
+        else {
+            continue;
+        };
+        let revision = get_tag_attribute(&content, &tag, "rev");
+
+        let location = match (
+            get_node_location(&path_string, &line_index, &element),
+            get_node_location(&path_string, &line_index, &name_node),
+        ) {
+            (Ok(block), Ok(name_location)) => Some(DependencyLocation {
+                block,
+                name: name_location,
+                version: revision
+                    .as_ref()
+                    .and_then(|(_, n)| get_node_location(&path_string, &line_index, n).ok()),
+            }),
+            _ => None,
+        };
+
+        result.push(
+            MavenDependencyBuilder::default()
+                .group_id(organisation)
+                .artifact_id(name)
+                .version(revision.map(|(r, _)| r))
+                .location(location)
+                .build()?,
+        );
+    }
+
+    Ok(result)
+}
+
+/// Get the properties of the closest `ivysettings.xml` in the directory of the file or
+/// one of its parents.
+fn get_closest_properties<'a>(
+    path: &Path,
+    properties_by_directory: &'a HashMap<PathBuf, HashMap<String, String>>,
+) -> Option<&'a HashMap<String, String>> {
+    path.ancestors()
+        .skip(1)
+        .find_map(|d| properties_by_directory.get(d))
+}
+
+impl SbomProducer for IvyProducer {
+    fn name(&self) -> &str {
+        "ivy"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "maven"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec!["**/ivy.xml", "**/ivysettings.xml"]
+    }
+
+    fn use_file(&self, path: &Path, _configuration: &SbomProducerConfiguration) -> bool {
+        match path.file_name() {
+            Some(e) => e == IVY_FILE || e == IVY_SETTINGS_FILE,
+            None => false,
+        }
+    }
+
+    fn ignored_directories(&self) -> Vec<&str> {
+        vec!["target"]
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+        let context = IvyProducerContext::new();
+        let (settings_files, ivy_files): (Vec<&PathBuf>, Vec<&PathBuf>) =
+            paths.iter().partition(|p| {
+                p.file_name()
+                    .map(|f| f == IVY_SETTINGS_FILE)
+                    .unwrap_or(false)
+            });
+
+        // First pass, we are getting the properties from the settings
+        let mut properties_by_directory = HashMap::new();
+        for path in settings_files {
+            match get_settings_properties(
+                path,
+                configuration.vfs.as_ref(),
+                configuration.trees.as_deref(),
+                &context,
+                &mut result.diagnostics,
+            ) {
+                Ok(properties) => {
+                    if let Some(directory) = path.parent() {
+                        properties_by_directory.insert(directory.to_path_buf(), properties);
+                    }
+                }
+                Err(e) => result.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    path,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        // Second pass, we are extracting dependencies and resolving properties
+        let no_properties = HashMap::new();
+        for path in ivy_files {
+            let properties =
+                get_closest_properties(path, &properties_by_directory).unwrap_or(&no_properties);
+            match get_dependencies_from_ivy_file(
+                path,
+                configuration.vfs.as_ref(),
+                configuration.trees.as_deref(),
+                &context,
+            ) {
+                Ok(dependencies) => {
+                    for dependency in dependencies.iter().map(|d| d.enrich(properties)) {
+                        if dependency.is_valid_for_sbom() {
+                            result.dependencies.push(Dependency::from(&dependency));
+                        } else {
+                            result
+                                .diagnostics
+                                .push(dependency.get_invalid_diagnostic(path));
+                        }
+                    }
+                }
+                Err(e) => result.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    path,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ivy_file() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/ivy");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let dependencies = IvyProducerBuilder::default()
+            .build()
+            .unwrap()
+            .find_dependencies(
+                &[d.join("app").join(IVY_FILE), d.join(IVY_SETTINGS_FILE)],
+                &configuration,
+            )
+            .unwrap()
+            .dependencies;
+
+        let purls: Vec<&str> = dependencies.iter().map(|d| d.purl.as_str()).collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:maven/com.google.guava/guava@33.0.0-jre",
+                "pkg:maven/org.slf4j/slf4j-api@2.0.9",
+                "pkg:maven/junit/junit@4.13.2",
+                "pkg:maven/com.example/app-common@1.0.0",
+            ]
+        );
+        let location = dependencies[2].location.clone().unwrap();
+        assert_eq!(location.block.start.line.get(), 11);
+        assert_eq!(location.block.end.line.get(), 13);
+    }
+
+    #[test]
+    fn test_properties_file_outside_root() {
+        let temp_directory = tempfile::tempdir().unwrap();
+        let outside = temp_directory.path();
+        let d = outside.join("project");
+        std::fs::create_dir(&d).unwrap();
+        std::fs::write(outside.join("secret.properties"), "guava.version=1.0\n").unwrap();
+        std::fs::write(
+            d.join(IVY_SETTINGS_FILE),
+            r#"<ivysettings>
+  <properties file="../secret.properties"/>
+  <properties file="missing.properties"/>
+</ivysettings>"#,
+        )
+        .unwrap();
+
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let mut diagnostics = vec![];
+        let properties = get_settings_properties(
+            &d.join(IVY_SETTINGS_FILE),
+            configuration.vfs.as_ref(),
+            None,
+            &IvyProducerContext::new(),
+            &mut diagnostics,
+        )
+        .unwrap();
+
+        assert!(properties.is_empty());
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "properties file ../secret.properties is not read: it is missing or outside of the scanned directory",
+                "properties file missing.properties is not read: it is missing or outside of the scanned directory",
+            ]
+        );
+    }
+}
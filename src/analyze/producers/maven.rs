@@ -1,6 +1,8 @@
 pub mod constants;
 pub mod context;
+pub mod effective_pom;
 pub mod import;
 pub mod maven_file;
 pub mod maven_producer;
 pub mod model;
+pub mod resolution;
@@ -1,7 +1,16 @@
 use crate::analyze::producers::maven::maven_file::{MavenFile, MavenProjectInfo};
+use crate::analyze::producers::maven::resolution::MavenParentChain;
 use crate::utils::tree_sitter::language::get_tree_sitter_xml;
+use crate::utils::tree_sitter::tree::TreeCache;
+use crate::utils::tree_sitter::xml::get_xml_query;
+use crate::utils::vfs::disk::DiskVfs;
+use crate::utils::vfs::Vfs;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of parents followed from a `pom.xml`, a deeper hierarchy is cut
+pub const DEFAULT_MAX_PARENT_DEPTH: usize = 32;
 
 const TREE_SITTER_PARENT_INFORMATION: &str = r###"
 (document
@@ -190,6 +199,10 @@ pub struct MavenProducerContext {
     pub query_dependencies: tree_sitter::Query,
     pub query_dependency_management: tree_sitter::Query,
     pub language: tree_sitter::Language,
+    /// The files are read through the VFS of the scan
+    pub vfs: Arc<dyn Vfs>,
+    /// The trees of the previous scan, to parse an edited file from its previous tree
+    pub trees: Option<Arc<TreeCache>>,
     /// hold a copy of the maven file data indexed by path for easy retrieval and indexing
     /// when looking for a parent.
     maven_files_by_path: HashMap<PathBuf, MavenFile>,
@@ -198,6 +211,12 @@ pub struct MavenProducerContext {
     maven_files_by_project_info: HashMap<MavenProjectInfo, MavenFile>,
     /// Copy of all maven files that have been found.
     maven_files: Vec<MavenFile>,
+    /// Maximum number of parents followed from a file
+    pub max_parent_depth: usize,
+    /// The parents of each file, computed once by [MavenFile::get_parent_chain].
+    parent_chains: Mutex<HashMap<PathBuf, MavenParentChain>>,
+    /// Hash of the content of the files, when the results are cached
+    content_hashes: HashMap<PathBuf, String>,
 }
 
 impl Default for MavenProducerContext {
@@ -208,17 +227,60 @@ impl Default for MavenProducerContext {
 
 impl MavenProducerContext {
     pub fn add_maven_file(&mut self, maven_file: &MavenFile) {
-        let relative_path = &maven_file
-            .path
-            .strip_prefix(&self.base_path)
-            .expect("can get base path")
-            .to_path_buf();
+        self.add_external_maven_file(maven_file);
+        self.maven_files.push(maven_file.clone());
+    }
 
+    /// Add a file that is not scanned, a parent in an allowed external root: it is used to
+    /// resolve its children but it is not in [get_all_files].
+    pub fn add_external_maven_file(&mut self, maven_file: &MavenFile) {
+        // indexed by the path read from the VFS, the scanned directories can have files
+        // with the same relative path
         self.maven_files_by_path
-            .insert(relative_path.clone(), maven_file.clone());
+            .insert(maven_file.path.clone(), maven_file.clone());
         self.maven_files_by_project_info
             .insert(maven_file.project_info.clone(), maven_file.clone());
-        self.maven_files.push(maven_file.clone());
+        // a new file can be the parent of a file already walked
+        self.parent_chains
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Get a file by its path, as read from the VFS.
+    pub fn get_maven_file(&self, path: &Path) -> Option<&MavenFile> {
+        self.maven_files_by_path.get(path)
+    }
+
+    pub fn add_content_hash(&mut self, path: &Path, hash: String) {
+        self.content_hashes.insert(path.to_path_buf(), hash);
+    }
+
+    /// Get the hash of the content of a file, set when the results are cached.
+    pub fn get_content_hash(&self, path: &Path) -> Option<&str> {
+        self.content_hashes.get(path).map(String::as_str)
+    }
+
+    /// Get the parents of the file at [path], computed with [compute] the first time.
+    pub fn get_parent_chain(
+        &self,
+        path: &Path,
+        compute: impl FnOnce() -> MavenParentChain,
+    ) -> MavenParentChain {
+        if let Some(chain) = self
+            .parent_chains
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(path)
+        {
+            return chain.clone();
+        }
+        let chain = compute();
+        self.parent_chains
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(path.to_path_buf(), chain.clone());
+        chain
     }
 
     pub fn get_maven_file_by_project_info(
@@ -236,40 +298,41 @@ impl MavenProducerContext {
         &self.maven_files
     }
 
-    pub fn new(bp: PathBuf) -> Self {
-        let xml_language = get_tree_sitter_xml();
+    /// Use another filesystem than the local disk, e.g. an archive.
+    pub fn with_vfs(self, vfs: Arc<dyn Vfs>) -> Self {
+        MavenProducerContext { vfs, ..self }
+    }
+
+    /// Parse the files from their previous tree in [trees] (`watch`).
+    pub fn with_trees(self, trees: Option<Arc<TreeCache>>) -> Self {
+        MavenProducerContext { trees, ..self }
+    }
+
+    /// Follow at most [max_parent_depth] parents from a file.
+    pub fn with_max_parent_depth(self, max_parent_depth: usize) -> Self {
+        MavenProducerContext {
+            max_parent_depth,
+            ..self
+        }
+    }
 
+    pub fn new(bp: PathBuf) -> Self {
         MavenProducerContext {
+            vfs: Arc::new(DiskVfs::new(&bp)),
+            trees: None,
             base_path: bp,
-            query_project_metadata: tree_sitter::Query::new(
-                &xml_language,
-                TREE_SITTER_PROJECT_METADATA,
-            )
-            .expect("got query variables"),
-            query_dependencies: tree_sitter::Query::new(
-                &xml_language,
-                TREE_SITTER_QUERY_DEPENDENCIES,
-            )
-            .expect("got query dependencies"),
-            query_dependency_management: tree_sitter::Query::new(
-                &xml_language,
-                TREE_SITTER_QUERY_DEPENDENCY_MANAGEMENT,
-            )
-            .expect("got query dependency management"),
-            query_project_properties: tree_sitter::Query::new(
-                &xml_language,
-                TREE_SITTER_PROJECT_PROPERTIES,
-            )
-            .expect("got query project properties"),
-            query_parent_information: tree_sitter::Query::new(
-                &xml_language,
-                TREE_SITTER_PARENT_INFORMATION,
-            )
-            .expect("got query parent info"),
-            language: xml_language,
+            query_project_metadata: get_xml_query(TREE_SITTER_PROJECT_METADATA),
+            query_dependencies: get_xml_query(TREE_SITTER_QUERY_DEPENDENCIES),
+            query_dependency_management: get_xml_query(TREE_SITTER_QUERY_DEPENDENCY_MANAGEMENT),
+            query_project_properties: get_xml_query(TREE_SITTER_PROJECT_PROPERTIES),
+            query_parent_information: get_xml_query(TREE_SITTER_PARENT_INFORMATION),
+            language: get_tree_sitter_xml(),
             maven_files_by_path: HashMap::new(),
             maven_files_by_project_info: HashMap::new(),
             maven_files: vec![],
+            max_parent_depth: DEFAULT_MAX_PARENT_DEPTH,
+            parent_chains: Mutex::new(HashMap::new()),
+            content_hashes: HashMap::new(),
         }
     }
 }
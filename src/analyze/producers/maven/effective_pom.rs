@@ -0,0 +1,230 @@
+use crate::analyze::producers::maven::maven_file::MavenDependency;
+use crate::utils::tree_sitter::xml::decode_xml_text;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Output format of the effective POMs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EffectivePomFormat {
+    Xml,
+    Json,
+}
+
+impl FromStr for EffectivePomFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "xml" => Ok(EffectivePomFormat::Xml),
+            "json" => Ok(EffectivePomFormat::Json),
+            _ => anyhow::bail!("unknown format {} (xml or json)", value),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveDependency {
+    pub group_id: String,
+    pub artifact_id: String,
+    /// The version after inheritance and interpolation, still with `${...}` if a property
+    /// is not defined
+    pub version: Option<String>,
+    pub scope: Option<String>,
+    #[serde(rename = "type")]
+    pub r#type: Option<String>,
+    /// Where the dependency is declared, `file:line:column`
+    pub location: Option<String>,
+}
+
+impl EffectiveDependency {
+    /// The dependency [resolved] (version from the dependency management, properties
+    /// replaced) as [declared] in the file.
+    pub fn new(declared: &MavenDependency, resolved: &MavenDependency) -> Self {
+        EffectiveDependency {
+            group_id: resolved.group_id.clone(),
+            artifact_id: resolved.artifact_id.clone(),
+            version: resolved.version.clone(),
+            scope: declared
+                .scope
+                .as_ref()
+                .or(resolved.scope.as_ref())
+                .map(|s| s.as_str().to_string()),
+            r#type: declared
+                .r#type
+                .as_ref()
+                .or(resolved.r#type.as_ref())
+                .map(|t| t.as_str().to_string()),
+            location: declared.location.as_ref().map(|l| l.block.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveParent {
+    pub group_id: Option<String>,
+    pub artifact_id: Option<String>,
+    pub version: Option<String>,
+    pub relative_path: Option<String>,
+    /// The file of the parent, none if it is not one of the scanned files
+    pub file: Option<String>,
+}
+
+/// The model of a `pom.xml` after parent inheritance, property interpolation and
+/// dependency management merging, like `mvn help:effective-pom`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectivePom {
+    pub file: String,
+    pub group_id: Option<String>,
+    pub artifact_id: String,
+    pub version: Option<String>,
+    pub parent: Option<EffectiveParent>,
+    pub properties: BTreeMap<String, String>,
+    pub dependency_management: Vec<EffectiveDependency>,
+    pub dependencies: Vec<EffectiveDependency>,
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Sanitize the text of a comment: a comment cannot contain `--` nor end with `-`.
+fn escape_comment(value: &str) -> String {
+    let mut result = value.to_string();
+    while result.contains("--") {
+        result = result.replace("--", "- -");
+    }
+    if result.ends_with('-') {
+        result.push(' ');
+    }
+    result
+}
+
+/// Push an element with a [value] from the text of a `pom.xml`: it is decoded first, so
+/// that an entity is not escaped twice.
+fn push_element(result: &mut String, indent: usize, name: &str, value: Option<&str>) {
+    if let Some(v) = value {
+        result.push_str(&format!(
+            "{}<{}>{}</{}>\n",
+            " ".repeat(indent),
+            name,
+            escape_xml(&decode_xml_text(v)),
+            name
+        ));
+    }
+}
+
+fn push_dependencies(result: &mut String, indent: usize, dependencies: &[EffectiveDependency]) {
+    let pad = " ".repeat(indent);
+    result.push_str(&format!("{}<dependencies>\n", pad));
+    for d in dependencies {
+        if let Some(location) = &d.location {
+            result.push_str(&format!("{}  <!-- {} -->\n", pad, escape_comment(location)));
+        }
+        result.push_str(&format!("{}  <dependency>\n", pad));
+        push_element(result, indent + 4, "groupId", Some(&d.group_id));
+        push_element(result, indent + 4, "artifactId", Some(&d.artifact_id));
+        push_element(result, indent + 4, "version", d.version.as_deref());
+        push_element(result, indent + 4, "type", d.r#type.as_deref());
+        push_element(result, indent + 4, "scope", d.scope.as_deref());
+        result.push_str(&format!("{}  </dependency>\n", pad));
+    }
+    result.push_str(&format!("{}</dependencies>\n", pad));
+}
+
+impl EffectivePom {
+    /// Render the model as a `<project>` element.
+    pub fn to_xml(&self) -> String {
+        let mut result = format!("  <!-- {} -->\n  <project>\n", escape_comment(&self.file));
+        if let Some(parent) = &self.parent {
+            result.push_str("    <parent>\n");
+            push_element(&mut result, 6, "groupId", parent.group_id.as_deref());
+            push_element(&mut result, 6, "artifactId", parent.artifact_id.as_deref());
+            push_element(&mut result, 6, "version", parent.version.as_deref());
+            push_element(
+                &mut result,
+                6,
+                "relativePath",
+                parent.relative_path.as_deref(),
+            );
+            result.push_str("    </parent>\n");
+        }
+        push_element(&mut result, 4, "groupId", self.group_id.as_deref());
+        push_element(&mut result, 4, "artifactId", Some(&self.artifact_id));
+        push_element(&mut result, 4, "version", self.version.as_deref());
+
+        if !self.properties.is_empty() {
+            result.push_str("    <properties>\n");
+            for (name, value) in &self.properties {
+                push_element(&mut result, 6, name, Some(value));
+            }
+            result.push_str("    </properties>\n");
+        }
+        if !self.dependency_management.is_empty() {
+            result.push_str("    <dependencyManagement>\n");
+            push_dependencies(&mut result, 6, self.dependency_management.as_slice());
+            result.push_str("    </dependencyManagement>\n");
+        }
+        if !self.dependencies.is_empty() {
+            push_dependencies(&mut result, 4, self.dependencies.as_slice());
+        }
+        result.push_str("  </project>\n");
+        result
+    }
+}
+
+/// Render the effective POMs of all the files.
+pub fn render_effective_poms(
+    poms: &[EffectivePom],
+    format: EffectivePomFormat,
+) -> anyhow::Result<String> {
+    match format {
+        EffectivePomFormat::Json => Ok(serde_json::to_string_pretty(poms)?),
+        EffectivePomFormat::Xml => {
+            let mut result =
+                String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<projects>\n");
+            for pom in poms {
+                result.push_str(&pom.to_xml());
+            }
+            result.push_str("</projects>\n");
+            Ok(result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_comment() {
+        assert_eq!(escape_comment("pom.xml:12"), "pom.xml:12");
+        assert_eq!(escape_comment("a--b---c"), "a- -b- - -c");
+        assert_eq!(escape_comment("module-"), "module- ");
+        assert_eq!(escape_comment("<a>&"), "<a>&");
+    }
+
+    #[test]
+    fn test_entities_escaped_once() {
+        let pom = EffectivePom {
+            file: "pom.xml".to_string(),
+            group_id: Some("com.example".to_string()),
+            artifact_id: "a&amp;b".to_string(),
+            version: Some("1.0".to_string()),
+            parent: None,
+            properties: BTreeMap::from([("name".to_string(), "&lt;A &amp; B&gt;".to_string())]),
+            dependency_management: vec![],
+            dependencies: vec![],
+        };
+        let xml = pom.to_xml();
+        assert!(xml.contains("<artifactId>a&amp;b</artifactId>"));
+        assert!(xml.contains("<name>&lt;A &amp; B&gt;</name>"));
+    }
+}
@@ -1,16 +1,24 @@
 use crate::analyze::producers::maven::constants::{ARTIFACT_ID, GROUP_ID, SCOPE, TYPE, VERSION};
 use crate::analyze::producers::maven::context::MavenProducerContext;
+use crate::analyze::producers::maven::effective_pom::{
+    EffectiveDependency, EffectiveParent, EffectivePom,
+};
 use crate::analyze::producers::maven::model::{MavenDependencyScope, MavenDependencyType};
+use crate::analyze::producers::maven::resolution::{
+    MavenParentChain, MavenPropertyDefinition, MavenPropertySubstitution, MavenResolution,
+};
 use crate::model::dependency::{Dependency, DependencyBuilder, DependencyLocation, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
 use crate::model::location::Location;
-use crate::model::position::get_position_in_string;
+use crate::model::position::LineIndex;
+use crate::model::purl::get_purl;
 use crate::utils::tree_sitter::tree::get_tree;
 use anyhow::anyhow;
 use derive_builder::Builder;
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::collections::HashMap;
-use std::fs;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
@@ -18,6 +26,10 @@ lazy_static! {
     static ref REGEX_VARIABLE: Regex = Regex::new(r"\$\{(.+)\}").unwrap();
 }
 
+/// Maximum number of properties replaced to explain a value, properties can refer to
+/// each other.
+const MAX_SUBSTITUTIONS: usize = 16;
+
 fn enrich_string_with_properties(s: &str, properties: &HashMap<String, String>) -> String {
     if let Some(caps) = REGEX_VARIABLE.captures(s) {
         let total_capture_opt = caps.get(0);
@@ -27,10 +39,8 @@ fn enrich_string_with_properties(s: &str, properties: &HashMap<String, String>)
             (Some(total_capture), Some(var_capture)) => {
                 let var_val = s;
                 let var_name = var_val.get(var_capture.start()..var_capture.end()).unwrap();
-                println!("var capture: {}", var_name);
 
                 if let Some(prop) = properties.get(var_name) {
-                    println!("val: {}", prop);
                     let mut to_replace = var_val.get(0..total_capture.start()).unwrap().to_string();
                     to_replace.push_str(prop.as_str());
                     to_replace.push_str(var_val.get(total_capture.end()..var_val.len()).unwrap());
@@ -45,14 +55,14 @@ fn enrich_string_with_properties(s: &str, properties: &HashMap<String, String>)
     s.to_string()
 }
 
-#[derive(Default, Eq, Hash, Clone, Debug, Builder, PartialEq)]
+#[derive(Default, Eq, Hash, Clone, Debug, Builder, PartialEq, Serialize, Deserialize)]
 pub struct MavenProjectInfo {
     pub group_id: Option<String>,
     pub artifact_id: String,
     pub version: Option<String>,
 }
 
-#[derive(Clone, Debug, Builder)]
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
 pub struct MavenDependency {
     pub group_id: String,
     pub artifact_id: String,
@@ -82,9 +92,60 @@ impl MavenDependency {
         }
     }
 
+    /// Get the key of the dependency in a dependency management: the groupId, the
+    /// artifactId and the type, `jar` when it is not set.
+    pub fn get_management_key(&self) -> (&str, &str, &str) {
+        (
+            self.group_id.as_str(),
+            self.artifact_id.as_str(),
+            self.r#type.as_ref().map_or("jar", |t| t.as_str()),
+        )
+    }
+
     pub fn is_valid_for_sbom(&self) -> bool {
         self.version.is_some() && !self.group_id.contains("$") && !self.artifact_id.contains("$")
     }
+
+    /// Explain why a dependency that is not valid for the SBOM is dropped.
+    pub fn get_invalid_diagnostic(&self, path: &Path) -> Diagnostic {
+        let name = format!("{}:{}", self.group_id, self.artifact_id);
+        let unresolved = [
+            Some(&self.group_id),
+            Some(&self.artifact_id),
+            self.version.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|v| REGEX_VARIABLE.captures(v))
+        .and_then(|c| c.get(1))
+        .map(|c| c.as_str().to_string());
+
+        let diagnostic = match (&self.version, unresolved) {
+            (_, Some(property)) => Diagnostic::warning(
+                DiagnosticKind::UnresolvedProperty,
+                path,
+                format!("property {} is not defined, {} is dropped", property, name),
+            ),
+            (None, None) => Diagnostic::warning(
+                DiagnosticKind::DroppedDependency,
+                path,
+                format!("{} has no version, it is dropped", name),
+            ),
+            (Some(_), None) => Diagnostic::warning(
+                DiagnosticKind::DroppedDependency,
+                path,
+                format!("{} is dropped", name),
+            ),
+        };
+        diagnostic
+            .with_location(self.location.as_ref().map(|l| l.block.clone()))
+            .with_package(get_purl(
+                "maven",
+                Some(&self.group_id),
+                &self.artifact_id,
+                None,
+            ))
+    }
 }
 
 impl From<&MavenDependency> for Dependency {
@@ -94,13 +155,18 @@ impl From<&MavenDependency> for Dependency {
             .version(value.version.clone())
             .location(value.location.clone())
             .r#type(DependencyType::Library)
-            .purl("bla".to_string())
+            .purl(get_purl(
+                "maven",
+                Some(value.group_id.as_str()),
+                value.artifact_id.as_str(),
+                value.version.as_deref(),
+            ))
             .build()
             .unwrap()
     }
 }
 
-#[derive(Clone, Debug, Builder, Default)]
+#[derive(Clone, Debug, Builder, Default, Serialize, Deserialize)]
 pub struct MavenFileParent {
     pub relative_path: Option<String>,
     pub group_id: Option<String>,
@@ -108,11 +174,13 @@ pub struct MavenFileParent {
     pub version: Option<String>,
 }
 
-#[derive(Clone, Debug, Builder, Default)]
+#[derive(Clone, Debug, Builder, Default, Serialize, Deserialize)]
 pub struct MavenFile {
     pub project_info: MavenProjectInfo,
     pub path: PathBuf,
     pub properties: HashMap<String, String>,
+    /// Where each property of [properties] is defined in the file
+    pub property_locations: HashMap<String, Location>,
     pub dependency_management: Vec<MavenDependency>,
     pub dependencies: Vec<MavenDependency>,
     pub parent: Option<MavenFileParent>,
@@ -122,19 +190,21 @@ fn get_dependencies_from_dependency_management(
     tree: &tree_sitter::Tree,
     path: &Path,
     content: &str,
+    line_index: &LineIndex,
     context: &MavenProducerContext,
 ) -> anyhow::Result<Vec<MavenDependency>> {
     let mut cursor = tree_sitter::QueryCursor::new();
     let mut dependencies: Vec<MavenDependency> = vec![];
 
+    let element_index = context
+        .query_dependency_management
+        .capture_index_for_name("element");
     let matches = cursor.matches(
         &context.query_dependency_management,
         tree.root_node(),
         content.as_bytes(),
     );
 
-    let path_string = path.display().to_string();
-
     for m in matches {
         let mut group_id_opt = None;
         let mut artifact_id_opt = None;
@@ -149,16 +219,18 @@ fn get_dependencies_from_dependency_management(
             continue;
         }
 
-        // the @element query
-        let element_block = m.captures[0].node;
+        // the @element capture, the captures are sorted by position and the first one is
+        // the name of the project
+        let Some(element_block) = m
+            .captures
+            .iter()
+            .find(|c| Some(c.index) == element_index)
+            .map(|c| c.node)
+        else {
+            continue;
+        };
 
-        let block_position_opt = Some(Location {
-            file: path_string.clone(),
-            start: get_position_in_string(content, element_block.start_byte())
-                .expect("cannot find start"),
-            end: get_position_in_string(content, element_block.end_byte())
-                .expect("cannot find end"),
-        });
+        let block_position_opt = get_node_location(path, line_index, element_block);
 
         // get the version, name, option, artifact id, etc.
         for i in (5..m.captures.len()).step_by(2) {
@@ -173,13 +245,7 @@ fn get_dependencies_from_dependency_management(
             if tag == ARTIFACT_ID {
                 artifact_id_opt = Some(value.clone());
 
-                name_position_opt = Some(Location {
-                    file: path_string.clone(),
-                    start: get_position_in_string(content, value_node.start_byte())
-                        .expect("cannot find start"),
-                    end: get_position_in_string(content, value_node.end_byte())
-                        .expect("cannot find end"),
-                });
+                name_position_opt = get_node_location(path, line_index, value_node);
             }
             if tag == GROUP_ID {
                 group_id_opt = Some(value.clone());
@@ -187,13 +253,7 @@ fn get_dependencies_from_dependency_management(
             if tag == VERSION {
                 version_opt = Some(value.clone());
 
-                version_position_opt = Some(Location {
-                    file: path_string.clone(),
-                    start: get_position_in_string(content, value_node.start_byte())
-                        .expect("cannot find start"),
-                    end: get_position_in_string(content, value_node.end_byte())
-                        .expect("cannot find end"),
-                });
+                version_position_opt = get_node_location(path, line_index, value_node);
             }
             if tag == TYPE {
                 type_opt = MavenDependencyType::from_str(value.as_str()).ok();
@@ -239,23 +299,24 @@ fn get_dependencies(
     tree: &tree_sitter::Tree,
     path: &Path,
     content: &str,
+    line_index: &LineIndex,
     context: &MavenProducerContext,
 ) -> anyhow::Result<Vec<MavenDependency>> {
     let mut cursor = tree_sitter::QueryCursor::new();
     let mut dependencies: Vec<MavenDependency> = vec![];
 
+    let element_index = context.query_dependencies.capture_index_for_name("element");
     let matches = cursor.matches(
         &context.query_dependencies,
         tree.root_node(),
         content.as_bytes(),
     );
 
-    let path_string = path.display().to_string();
-
     for m in matches {
         let mut group_id_opt = None;
         let mut artifact_id_opt = None;
         let mut version_opt = None;
+        let mut type_opt = None;
         let mut scope_opt = None;
 
         let mut name_position_opt: Option<Location> = None;
@@ -265,16 +326,18 @@ fn get_dependencies(
             continue;
         }
 
-        // the @element query
-        let element_block = m.captures[0].node;
+        // the @element capture, the captures are sorted by position and the first one is
+        // the name of the project
+        let Some(element_block) = m
+            .captures
+            .iter()
+            .find(|c| Some(c.index) == element_index)
+            .map(|c| c.node)
+        else {
+            continue;
+        };
 
-        let block_position_opt = Some(Location {
-            file: path_string.clone(),
-            start: get_position_in_string(content, element_block.start_byte())
-                .expect("cannot find start"),
-            end: get_position_in_string(content, element_block.end_byte())
-                .expect("cannot find end"),
-        });
+        let block_position_opt = get_node_location(path, line_index, element_block);
 
         // get the version, name, option, artifact id, etc.
         for i in (0..m.captures.len()).step_by(2) {
@@ -289,13 +352,7 @@ fn get_dependencies(
             if tag == ARTIFACT_ID {
                 artifact_id_opt = Some(value.clone());
 
-                name_position_opt = Some(Location {
-                    file: path_string.clone(),
-                    start: get_position_in_string(content, value_node.start_byte())
-                        .expect("cannot find start"),
-                    end: get_position_in_string(content, value_node.end_byte())
-                        .expect("cannot find end"),
-                });
+                name_position_opt = get_node_location(path, line_index, value_node);
             }
             if tag == GROUP_ID {
                 group_id_opt = Some(value.clone());
@@ -303,13 +360,10 @@ fn get_dependencies(
             if tag == VERSION {
                 version_opt = Some(value.clone());
 
-                version_position_opt = Some(Location {
-                    file: path_string.clone(),
-                    start: get_position_in_string(content, value_node.start_byte())
-                        .expect("cannot find start"),
-                    end: get_position_in_string(content, value_node.end_byte())
-                        .expect("cannot find end"),
-                });
+                version_position_opt = get_node_location(path, line_index, value_node);
+            }
+            if tag == TYPE {
+                type_opt = MavenDependencyType::from_str(value.as_str()).ok();
             }
             if tag == SCOPE {
                 scope_opt = MavenDependencyScope::from_str(value.as_str()).ok();
@@ -335,6 +389,7 @@ fn get_dependencies(
                     .group_id(group_id)
                     .artifact_id(artifact_id)
                     .version(version_opt)
+                    .r#type(type_opt)
                     .scope(scope_opt)
                     .location(location)
                     .build()
@@ -347,12 +402,30 @@ fn get_dependencies(
     Ok(dependencies)
 }
 
+/// Get the location of a node, used for the values of the properties.
+fn get_node_location(
+    path: &Path,
+    line_index: &LineIndex,
+    node: tree_sitter::Node,
+) -> Option<Location> {
+    Some(Location {
+        file: path.display().to_string(),
+        start: line_index.get_position(node.start_byte()).ok()?,
+        end: line_index.get_position(node.end_byte()).ok()?,
+    })
+}
+
+/// Get the properties of the file (and `project.version`) with the location of their
+/// value.
 pub fn get_variables(
     tree: &tree_sitter::Tree,
+    path: &Path,
     file_content: &str,
+    line_index: &LineIndex,
     maven_producer_context: &MavenProducerContext,
-) -> HashMap<String, String> {
+) -> (HashMap<String, String>, HashMap<String, Location>) {
     let mut variables = HashMap::new();
+    let mut locations = HashMap::new();
 
     // Get the project version is any
     let mut cursor = tree_sitter::QueryCursor::new();
@@ -370,6 +443,9 @@ pub fn get_variables(
 
         if key == "version" {
             variables.insert("project.version".to_string(), value);
+            if let Some(location) = get_node_location(path, line_index, value_node) {
+                locations.insert("project.version".to_string(), location);
+            }
         }
     }
 
@@ -385,10 +461,13 @@ pub fn get_variables(
         let value_node = m.captures[3].node;
         let key = file_content[key_node.start_byte()..key_node.end_byte()].to_string();
         let value = file_content[value_node.start_byte()..value_node.end_byte()].to_string();
+        if let Some(location) = get_node_location(path, line_index, value_node) {
+            locations.insert(key.clone(), location);
+        }
         variables.insert(key, value);
     }
 
-    variables
+    (variables, locations)
 }
 
 pub fn get_project_info(
@@ -523,156 +602,301 @@ fn replace_properties(properties: HashMap<String, String>) -> HashMap<String, St
 }
 
 impl MavenFile {
-    pub fn new(path: &PathBuf, context: &MavenProducerContext) -> anyhow::Result<Self> {
-        let file_content = fs::read_to_string(path);
-        if let Ok(content) = file_content {
-            if let Some(t) = get_tree(content.as_str(), &context.language) {
-                let project_info = get_project_info(&t, content.as_str(), context);
-
-                if project_info.is_none() {
-                    return Err(anyhow!("cannot get project info"));
-                }
+    pub fn new(path: &Path, context: &MavenProducerContext) -> anyhow::Result<Self> {
+        let Ok(content) = context.vfs.read_to_string(path) else {
+            return Err(anyhow!("cannot parse file"));
+        };
+        Self::from_content(path, content.as_str(), context)
+    }
 
-                let variables = get_variables(&t, content.as_str(), context);
-                let dependencies = get_dependencies(&t, path, content.as_str(), context);
-                let dependency_management = get_dependencies_from_dependency_management(
-                    &t,
-                    path,
-                    content.as_str(),
-                    context,
-                );
-                let parent_information =
-                    get_parent_information(&t, path, content.as_str(), context);
-                let maven_file = MavenFile {
-                    project_info: project_info.unwrap(),
-                    path: path.clone(),
-                    properties: variables,
-                    dependency_management: dependency_management?,
-                    dependencies: dependencies?,
-                    parent: parent_information,
-                };
-                Ok(maven_file)
-            } else {
-                Err(anyhow!("cannot parse tree"))
-            }
-        } else {
-            Err(anyhow!("cannot parse file"))
-        }
+    /// Parse the [content] of the file at [path].
+    pub fn from_content(
+        path: &Path,
+        content: &str,
+        context: &MavenProducerContext,
+    ) -> anyhow::Result<Self> {
+        let Some(t) = get_tree(path, content, &context.language, context.trees.as_deref()) else {
+            return Err(anyhow!("cannot parse tree"));
+        };
+        let Some(project_info) = get_project_info(&t, content, context) else {
+            return Err(anyhow!("cannot get project info"));
+        };
+
+        let line_index = LineIndex::new(content);
+        let (variables, variable_locations) =
+            get_variables(&t, path, content, &line_index, context);
+        let dependencies = get_dependencies(&t, path, content, &line_index, context);
+        let dependency_management =
+            get_dependencies_from_dependency_management(&t, path, content, &line_index, context);
+        let parent_information = get_parent_information(&t, path, content, context);
+        Ok(MavenFile {
+            project_info,
+            path: path.to_path_buf(),
+            properties: variables,
+            property_locations: variable_locations,
+            dependency_management: dependency_management?,
+            dependencies: dependencies?,
+            parent: parent_information,
+        })
     }
 
-    fn get_parent_file_path(&self, context: &MavenProducerContext) -> Option<PathBuf> {
-        if let Some(relative_path) = self.parent.clone().and_then(|x| x.relative_path) {
-            let bp = fs::canonicalize(&context.base_path).expect("cannot get base path");
-            let mut f = self.path.clone().parent().unwrap().to_path_buf();
-            f.push(&relative_path);
-            let full_path = fs::canonicalize(f).expect("cannot get full path");
+    /// Resolve the `relativePath` of the parent to its path in the VFS (the full path for a
+    /// parent in an allowed external root). None if there is no `relativePath` or no file,
+    /// an error if the file is outside of the scanned directories.
+    fn resolve_parent_file_path(
+        &self,
+        context: &MavenProducerContext,
+    ) -> Option<anyhow::Result<PathBuf>> {
+        let relative_path = self.parent.as_ref()?.relative_path.as_ref()?;
+        let mut f = self.path.parent()?.to_path_buf();
+        f.push(relative_path);
+        if !relative_path.ends_with("pom.xml") {
+            f.push("pom.xml");
+        }
+        if !context.vfs.is_file(&f) {
+            return None;
+        }
 
-            let mut rel_path = full_path
-                .strip_prefix(&bp)
-                .expect("get rel path")
-                .to_path_buf();
+        Some(context.vfs.resolve_path(&f))
+    }
 
-            if !relative_path.ends_with("pom.xml") {
-                rel_path.push("pom.xml");
-            }
-            println!("rel path: {:?}", rel_path);
-            return Some(rel_path);
-        }
-        None
+    /// Get the path of the parent from the `relativePath`. None if the parent does not exist
+    /// or is not under a scanned directory (or an allowed root).
+    pub fn get_parent_file_path(&self, context: &MavenProducerContext) -> Option<PathBuf> {
+        self.resolve_parent_file_path(context)?.ok()
     }
 
-    fn get_parent_by_project_info(&self, context: &MavenProducerContext) -> Option<MavenFile> {
-        if let Some(p) = &self.parent {
-            if let Some(a) = &p.artifact_id {
-                let project_info = MavenProjectInfo {
-                    artifact_id: a.to_string(),
-                    group_id: p.group_id.clone(),
-                    version: p.version.clone(),
-                };
+    /// Report a `relativePath` that goes out of the scanned directory, the parent is then
+    /// only searched by group, artifact and version.
+    fn get_parent_path_diagnostic(&self, context: &MavenProducerContext) -> Option<Diagnostic> {
+        let error = self.resolve_parent_file_path(context)?.err()?;
+        Some(Diagnostic::warning(
+            DiagnosticKind::PathOutsideRoot,
+            &self.path,
+            format!("parent is not read: {}", error),
+        ))
+    }
 
-                if let Some(p) = context.get_maven_file_by_project_info(&project_info) {
-                    return Some(p.clone());
-                }
-            }
+    fn get_parent_by_project_info<'a>(
+        &self,
+        context: &'a MavenProducerContext,
+    ) -> Option<&'a MavenFile> {
+        let p = self.parent.as_ref()?;
+        let project_info = MavenProjectInfo {
+            artifact_id: p.artifact_id.clone()?,
+            group_id: p.group_id.clone(),
+            version: p.version.clone(),
+        };
+        context.get_maven_file_by_project_info(&project_info)
+    }
+
+    /// Get the parent the properties and the dependency management are inherited from:
+    /// the file of the `relativePath` or the file with the same group, artifact and
+    /// version.
+    fn get_parent<'a>(&self, context: &'a MavenProducerContext) -> Option<&'a MavenFile> {
+        match self.get_parent_file_path(context) {
+            Some(parent) => context.get_maven_file_by_path(&parent),
+            None => self.get_parent_by_project_info(context),
         }
-        None
     }
 
-    /// Get all properties related to this file and sub-files and put them in a HashMap.
-    /// Also resolve variables when appropriate/possible.
-    fn get_all_properties(&self, context: &MavenProducerContext) -> HashMap<String, String> {
-        fn get_all_properties_int(
-            maven_file: &MavenFile,
-            context: &MavenProducerContext,
-        ) -> HashMap<String, String> {
-            let mut res: HashMap<String, String> = HashMap::new();
-
-            let parent_path = maven_file.get_parent_file_path(context);
-
-            if let Some(parent) = parent_path {
-                if let Some(parent_maven_file) = context.get_maven_file_by_path(&parent) {
-                    println!("found parent file");
-                    res.extend(parent_maven_file.get_all_properties(context));
+    /// Walk the parents of the file, up to the maximum depth of the context. The walk stops
+    /// at the first parent found twice. The result is kept in the context, each file is
+    /// walked once.
+    pub fn get_parent_chain(&self, context: &MavenProducerContext) -> MavenParentChain {
+        context.get_parent_chain(&self.path, || {
+            let mut chain = MavenParentChain::default();
+            let mut current = self;
+            while let Some(parent) = current.get_parent(context) {
+                if parent.path == self.path || chain.parents.contains(&parent.path) {
+                    let mut cycle = vec![self.path.clone()];
+                    cycle.extend(chain.parents.iter().cloned());
+                    cycle.push(parent.path.clone());
+                    chain.cycle = Some(cycle);
+                    break;
                 }
-            } else if let Some(p) = &maven_file.parent {
-                if let (Some(g), Some(a), Some(v)) =
-                    (p.clone().group_id, p.clone().artifact_id, p.clone().version)
-                {
-                    let key = MavenProjectInfo {
-                        artifact_id: a,
-                        group_id: Some(g),
-                        version: Some(v),
-                    };
-
-                    if let Some(m) = context.get_maven_file_by_project_info(&key) {
-                        res.extend(m.get_all_properties(context));
-                    }
+                if chain.parents.len() >= context.max_parent_depth {
+                    chain.truncated = true;
+                    break;
                 }
+                chain.parents.push(parent.path.clone());
+                current = parent;
             }
+            chain
+        })
+    }
+
+    /// Get what the dependencies of the file depend on, besides the content of the file and
+    /// of its parents: the parents found and where the `relativePath` leads.
+    pub fn get_resolution_inputs(&self, context: &MavenProducerContext) -> String {
+        let parent_path = self
+            .resolve_parent_file_path(context)
+            .map(|r| r.map_err(|e| e.to_string()));
+        format!("{:?} {:?}", self.get_parent_chain(context), parent_path)
+    }
+
+    /// Get the parents of the file, the root of the hierarchy first.
+    fn get_parents<'a>(&self, context: &'a MavenProducerContext) -> Vec<&'a MavenFile> {
+        self.get_parent_chain(context)
+            .parents
+            .iter()
+            .rev()
+            .filter_map(|p| context.get_maven_file(p))
+            .collect()
+    }
 
-            res.extend(maven_file.properties.clone());
+    /// Get all properties related to this file and its parents and put them in a HashMap.
+    /// Also resolve variables when appropriate/possible.
+    fn get_all_properties(&self, context: &MavenProducerContext) -> HashMap<String, String> {
+        let mut res: HashMap<String, String> = HashMap::new();
+        for file in self.get_parents(context).into_iter().chain([self]) {
+            res.extend(file.properties.clone());
+            res = replace_properties(res);
+        }
+        res
+    }
 
-            res
+    /// Get the definition of all the properties of this file and its parents, as
+    /// [get_all_properties] but without resolving the variables.
+    fn get_property_definitions(
+        &self,
+        context: &MavenProducerContext,
+    ) -> HashMap<String, MavenPropertyDefinition> {
+        let mut res = HashMap::new();
+        for file in self.get_parents(context).into_iter().chain([self]) {
+            for (name, value) in &file.properties {
+                res.insert(
+                    name.clone(),
+                    MavenPropertyDefinition {
+                        value: value.clone(),
+                        location: file.property_locations.get(name).cloned(),
+                    },
+                );
+            }
         }
-        replace_properties(get_all_properties_int(self, context))
+        res
     }
 
+    /// Get the dependency management of this file and its parents. An entry replaces the
+    /// entries of the parents with the same groupId, artifactId and type: the nearest file
+    /// wins, as with Maven. The entries of this file come first.
     fn get_all_dependencies_from_dependency_management(
         &self,
         context: &MavenProducerContext,
     ) -> Vec<MavenDependency> {
-        let mut res: Vec<MavenDependency> = vec![];
-
-        let parent_path = self.get_parent_file_path(context);
-
-        if let Some(parent) = parent_path {
-            if let Some(parent_maven_file) = context.get_maven_file_by_path(&parent) {
-                res.extend(
-                    parent_maven_file.get_all_dependencies_from_dependency_management(context),
-                );
+        let mut keys = HashSet::new();
+        let mut res = vec![];
+        for file in [self]
+            .into_iter()
+            .chain(self.get_parents(context).into_iter().rev())
+        {
+            for dependency in &file.dependency_management {
+                if keys.insert(dependency.get_management_key()) {
+                    res.push(dependency.clone());
+                }
             }
-        } else {
-            println!("getting parent by key");
-            if let Some(parent_maven_file) = self.get_parent_by_project_info(context) {
-                println!("found parent by key");
-                res.extend(
-                    parent_maven_file.get_all_dependencies_from_dependency_management(context),
-                );
-            } else {
-                println!("not found parent by key");
+        }
+        res
+    }
+
+    /// Get the dependencies of this file and the dependencies inherited from its parents.
+    /// A dependency replaces the dependencies of the parents with the same groupId,
+    /// artifactId and type: the nearest file wins, as for the dependency management.
+    fn get_all_dependencies<'a>(
+        &'a self,
+        context: &'a MavenProducerContext,
+    ) -> Vec<&'a MavenDependency> {
+        let mut keys = HashSet::new();
+        let mut res = vec![];
+        for file in [self]
+            .into_iter()
+            .chain(self.get_parents(context).into_iter().rev())
+        {
+            for dependency in &file.dependencies {
+                if keys.insert(dependency.get_management_key()) {
+                    res.push(dependency);
+                }
             }
         }
+        res
+    }
 
-        res.extend(self.dependency_management.clone());
+    /// Report parents that form a cycle or a hierarchy deeper than the maximum depth: the
+    /// properties and dependency management of the parents not walked are missing.
+    fn get_parent_chain_diagnostic(&self, context: &MavenProducerContext) -> Option<Diagnostic> {
+        let chain = self.get_parent_chain(context);
+        if let Some(cycle) = &chain.cycle {
+            let files: Vec<String> = cycle.iter().map(|p| p.display().to_string()).collect();
+            return Some(Diagnostic::warning(
+                DiagnosticKind::ParentCycle,
+                &self.path,
+                format!("parents form a cycle: {}", files.join(" -> ")),
+            ));
+        }
+        chain.truncated.then(|| {
+            Diagnostic::warning(
+                DiagnosticKind::ParentTooDeep,
+                &self.path,
+                format!(
+                    "more than {} parents, the next ones are not used",
+                    context.max_parent_depth
+                ),
+            )
+        })
+    }
 
-        res
+    /// Report a parent that is declared but is not one of the scanned files: its properties
+    /// and dependency management are missing.
+    fn get_missing_parent_diagnostic(&self, context: &MavenProducerContext) -> Option<Diagnostic> {
+        let parent = self.parent.as_ref()?;
+        if self.get_parent(context).is_some()
+            || self
+                .resolve_parent_file_path(context)
+                .is_some_and(|p| p.is_err())
+        {
+            return None;
+        }
+
+        let name = match (&parent.group_id, &parent.artifact_id, &parent.version) {
+            (Some(g), Some(a), Some(v)) => format!("{}:{}:{}", g, a, v),
+            (_, Some(a), _) => a.clone(),
+            _ => parent.relative_path.clone().unwrap_or_default(),
+        };
+        Some(Diagnostic::warning(
+            DiagnosticKind::MissingParent,
+            &self.path,
+            format!("parent {} not found, its properties are not used", name),
+        ))
+    }
+
+    /// Get a dependency with the properties replaced. A dependency without version is
+    /// replaced by its entry in the [dependency_management] with the same groupId,
+    /// artifactId and type, none if there is no entry.
+    fn resolve_dependency(
+        dependency: &MavenDependency,
+        properties: &HashMap<String, String>,
+        dependency_management: &[MavenDependency],
+    ) -> Option<MavenDependency> {
+        if dependency.version.is_some() {
+            return Some(dependency.enrich(properties));
+        }
+        dependency_management
+            .iter()
+            .find(|x| x.get_management_key() == dependency.get_management_key())
+            .map(|d| d.enrich(properties))
     }
 
+    /// Get the dependencies to report in the SBOM. The dependencies that cannot be
+    /// resolved are reported in [diagnostics].
     pub fn get_dependencies_for_sbom(
         &self,
         context: &MavenProducerContext,
+        diagnostics: &mut Vec<Diagnostic>,
     ) -> Vec<MavenDependency> {
         let mut res = vec![];
+        diagnostics.extend(self.get_parent_path_diagnostic(context));
+        diagnostics.extend(self.get_missing_parent_diagnostic(context));
+        diagnostics.extend(self.get_parent_chain_diagnostic(context));
 
         // get all properties from the current file and its parent
         let properties = &self.get_all_properties(context);
@@ -681,35 +905,161 @@ impl MavenFile {
             &self.get_all_dependencies_from_dependency_management(context);
 
         for dependency in &self.dependencies {
-            println!("dependency {}", dependency.artifact_id);
-            if dependency.version.is_none() {
-                println!("dependency {} has no version", dependency.artifact_id);
-                let dep_from_dep_management =
-                    dependencies_from_property_management.iter().find(|x| {
-                        x.artifact_id == dependency.artifact_id && x.group_id == dependency.group_id
-                    });
-
-                if let Some(dep) = dep_from_dep_management {
-                    println!(
-                        "dependency {} found in dependency management",
-                        dependency.artifact_id
-                    );
-                    let enriched = dep.clone().enrich(properties);
-
-                    if enriched.is_valid_for_sbom() {
-                        res.push(enriched);
-                    }
-                }
-            } else {
-                let enriched = dependency.clone().enrich(properties);
-                if enriched.is_valid_for_sbom() {
-                    res.push(enriched);
-                }
+            match Self::resolve_dependency(
+                dependency,
+                properties,
+                dependencies_from_property_management,
+            ) {
+                Some(enriched) if enriched.is_valid_for_sbom() => res.push(enriched),
+                Some(enriched) => diagnostics.push(enriched.get_invalid_diagnostic(&self.path)),
+                None => diagnostics.push(dependency.get_invalid_diagnostic(&self.path)),
             }
         }
 
         res
     }
+
+    /// Get the model of the file after parent inheritance, property interpolation and
+    /// dependency management merging. The dependencies of the parents are inherited, their
+    /// location is in the file of the parent.
+    pub fn get_effective_pom(&self, context: &MavenProducerContext) -> EffectivePom {
+        let properties = self.get_all_properties(context);
+        let dependency_management = self.get_all_dependencies_from_dependency_management(context);
+        let enrich = |v: &String| enrich_string_with_properties(v, &properties);
+
+        let parent = self.parent.as_ref().map(|p| EffectiveParent {
+            group_id: p.group_id.clone(),
+            artifact_id: p.artifact_id.clone(),
+            version: p.version.clone(),
+            relative_path: p.relative_path.clone(),
+            file: self
+                .get_parent(context)
+                .map(|f| f.path.display().to_string()),
+        });
+
+        EffectivePom {
+            file: self.path.display().to_string(),
+            group_id: self
+                .project_info
+                .group_id
+                .as_ref()
+                .or(self.parent.as_ref().and_then(|p| p.group_id.as_ref()))
+                .map(enrich),
+            artifact_id: enrich(&self.project_info.artifact_id),
+            version: self
+                .project_info
+                .version
+                .as_ref()
+                .or(self.parent.as_ref().and_then(|p| p.version.as_ref()))
+                .map(enrich),
+            parent,
+            properties: properties
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect::<BTreeMap<String, String>>(),
+            dependency_management: dependency_management
+                .iter()
+                .map(|d| EffectiveDependency::new(d, &d.enrich(&properties)))
+                .collect(),
+            dependencies: self
+                .get_all_dependencies(context)
+                .into_iter()
+                .map(|d| {
+                    let resolved = Self::resolve_dependency(d, &properties, &dependency_management)
+                        .unwrap_or_else(|| d.enrich(&properties));
+                    EffectiveDependency::new(d, &resolved)
+                })
+                .collect(),
+        }
+    }
+
+    /// Follow the properties used in [value], e.g. `${slf4j.version}` then the value of
+    /// `slf4j.version` if it refers to another property.
+    fn get_substitutions(
+        value: &str,
+        definitions: &HashMap<String, MavenPropertyDefinition>,
+        substitutions: &mut Vec<MavenPropertySubstitution>,
+    ) {
+        let mut current = Some(value.to_string());
+        while let Some(value) = current.take() {
+            let Some(name) = REGEX_VARIABLE
+                .captures(&value)
+                .and_then(|c| c.get(1))
+                .map(|c| c.as_str().to_string())
+            else {
+                break;
+            };
+            if substitutions.len() >= MAX_SUBSTITUTIONS
+                || substitutions.iter().any(|s| s.name == name)
+            {
+                break;
+            }
+            let definition = definitions.get(&name).cloned();
+            current = definition.as_ref().map(|d| d.value.clone());
+            substitutions.push(MavenPropertySubstitution { name, definition });
+        }
+    }
+
+    /// Explain how the dependencies of this file with the group [group_id] and the
+    /// artifact [artifact_id] are resolved, as done by [get_dependencies_for_sbom].
+    pub fn explain_dependency(
+        &self,
+        context: &MavenProducerContext,
+        group_id: &str,
+        artifact_id: &str,
+    ) -> Vec<MavenResolution> {
+        let properties = self.get_all_properties(context);
+        let definitions = self.get_property_definitions(context);
+        let dependency_management = self.get_all_dependencies_from_dependency_management(context);
+        let missing_parent = self
+            .get_missing_parent_diagnostic(context)
+            .or_else(|| self.get_parent_path_diagnostic(context))
+            .or_else(|| self.get_parent_chain_diagnostic(context))
+            .map(|d| d.message);
+
+        self.dependencies
+            .iter()
+            .filter(|d| {
+                let enriched = d.enrich(&properties);
+                enriched.group_id == group_id && enriched.artifact_id == artifact_id
+            })
+            .map(|dependency| {
+                let managed_by = match dependency.version {
+                    Some(_) => None,
+                    None => dependency_management
+                        .iter()
+                        .find(|x| x.get_management_key() == dependency.get_management_key())
+                        .cloned(),
+                };
+                let resolved = managed_by.as_ref().unwrap_or(dependency);
+
+                let mut substitutions = vec![];
+                for value in [
+                    Some(&dependency.group_id),
+                    Some(&dependency.artifact_id),
+                    resolved.version.as_ref(),
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    Self::get_substitutions(value, &definitions, &mut substitutions);
+                }
+
+                let enriched = resolved.enrich(&properties);
+                MavenResolution {
+                    file: self.path.clone(),
+                    declaration: dependency.clone(),
+                    managed_by: managed_by.clone(),
+                    substitutions,
+                    missing_parent: missing_parent.clone(),
+                    version: enriched
+                        .is_valid_for_sbom()
+                        .then_some(enriched.version)
+                        .flatten(),
+                }
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -954,4 +1304,220 @@ mod tests {
             "${akka.version}"
         );
     }
+
+    #[test]
+    fn test_explain_dependency() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/maven/hierarchy/");
+        let mut context = MavenProducerContext::new(d.clone());
+        let parent = MavenFile::new(&d.join("pom.xml"), &context).expect("maven file is parsed");
+        context.add_maven_file(&parent);
+        let subfile =
+            MavenFile::new(&d.join("subproject/pom.xml"), &context).expect("maven file is parsed");
+
+        let resolutions = subfile.explain_dependency(&context, "org.slf4j", "slf4j-api");
+        assert_eq!(resolutions.len(), 1);
+        let resolution = &resolutions[0];
+        assert!(resolution.declaration.version.is_none());
+        let managed_by = resolution.managed_by.as_ref().unwrap();
+        assert!(managed_by
+            .location
+            .as_ref()
+            .unwrap()
+            .block
+            .file
+            .ends_with("hierarchy/pom.xml"));
+        assert_eq!(
+            managed_by.location.as_ref().unwrap().block.start.line.get(),
+            47
+        );
+        assert_eq!(resolution.substitutions.len(), 1);
+        assert_eq!(resolution.substitutions[0].name, "slf4j.version");
+        let definition = resolution.substitutions[0].definition.as_ref().unwrap();
+        assert_eq!(definition.value, "2.0.13");
+        assert_eq!(definition.location.as_ref().unwrap().start.line.get(), 23);
+        assert_eq!(resolution.version, Some("2.0.13".to_string()));
+
+        // the artifact uses a property that refers to another one
+        let resolutions =
+            subfile.explain_dependency(&context, "com.typesafe.akka", "akka-actor_2.12");
+        assert_eq!(resolutions.len(), 1);
+        let names: Vec<&str> = resolutions[0]
+            .substitutions
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["akka-scala.version", "scala.version", "akka.version"]
+        );
+        assert_eq!(resolutions[0].version, Some("2.6.21".to_string()));
+
+        assert!(subfile
+            .explain_dependency(&context, "org.slf4j", "slf4j-simple")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_effective_pom() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/maven/hierarchy/");
+        let mut context = MavenProducerContext::new(d.clone());
+        let parent = MavenFile::new(&d.join("pom.xml"), &context).expect("maven file is parsed");
+        context.add_maven_file(&parent);
+        let subfile =
+            MavenFile::new(&d.join("subproject/pom.xml"), &context).expect("maven file is parsed");
+
+        let effective_pom = subfile.get_effective_pom(&context);
+        // inherited from the parent
+        assert_eq!(effective_pom.group_id, Some("com.project".to_string()));
+        assert_eq!(effective_pom.version, Some("1.0-SNAPSHOT".to_string()));
+        assert!(effective_pom
+            .parent
+            .unwrap()
+            .file
+            .unwrap()
+            .ends_with("hierarchy/pom.xml"));
+        assert_eq!(
+            effective_pom.properties.get("akka-scala.version").unwrap(),
+            "2.12"
+        );
+        assert_eq!(effective_pom.dependency_management.len(), 11);
+
+        // the dependencies of the file, then the dependencies of the parent
+        assert_eq!(effective_pom.dependencies.len(), 4);
+        assert_eq!(effective_pom.dependencies[0].artifact_id, "akka-actor_2.12");
+        assert_eq!(
+            effective_pom.dependencies[0].version,
+            Some("2.6.21".to_string())
+        );
+        assert!(effective_pom.dependencies[0]
+            .location
+            .as_ref()
+            .unwrap()
+            .ends_with("subproject/pom.xml:20:5"));
+        assert_eq!(effective_pom.dependencies[1].artifact_id, "slf4j-api");
+        assert_eq!(
+            effective_pom.dependencies[1].version,
+            Some("2.0.13".to_string())
+        );
+        assert_eq!(effective_pom.dependencies[2].artifact_id, "jsr305");
+        assert!(effective_pom.dependencies[2]
+            .location
+            .as_ref()
+            .unwrap()
+            .ends_with("hierarchy/pom.xml:98:5"));
+        assert_eq!(
+            effective_pom.dependencies[3].artifact_id,
+            "value-annotations"
+        );
+    }
+
+    #[test]
+    fn test_dependency_management_of_child_wins() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/maven/managed/");
+        let mut context = MavenProducerContext::new(d.clone());
+        let parent = MavenFile::new(&d.join("pom.xml"), &context).expect("maven file is parsed");
+        context.add_maven_file(&parent);
+        let child =
+            MavenFile::new(&d.join("child/pom.xml"), &context).expect("maven file is parsed");
+
+        let effective_pom = child.get_effective_pom(&context);
+        let managed: Vec<(&str, Option<&str>)> = effective_pom
+            .dependency_management
+            .iter()
+            .map(|d| (d.artifact_id.as_str(), d.version.as_deref()))
+            .collect();
+        assert_eq!(
+            managed,
+            vec![
+                ("slf4j-api", Some("2.0.16")),
+                ("junit", Some("4.13.2")),
+                ("common", Some("1.1")),
+                ("common", Some("1.0"))
+            ]
+        );
+
+        let versions: Vec<Option<String>> = child
+            .get_dependencies_for_sbom(&context, &mut vec![])
+            .into_iter()
+            .map(|d| d.version)
+            .collect();
+        assert_eq!(
+            versions,
+            vec![
+                Some("2.0.16".to_string()),
+                Some("4.13.2".to_string()),
+                Some("1.0".to_string()),
+                Some("1.1".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_effective_pom_inherits_dependencies() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/maven/managed/");
+        let mut context = MavenProducerContext::new(d.clone());
+        let parent = MavenFile::new(&d.join("pom.xml"), &context).expect("maven file is parsed");
+        context.add_maven_file(&parent);
+        let child =
+            MavenFile::new(&d.join("child/pom.xml"), &context).expect("maven file is parsed");
+
+        // junit of the child replaces junit of the parent
+        let dependencies: Vec<(String, Option<String>, bool)> = child
+            .get_effective_pom(&context)
+            .dependencies
+            .into_iter()
+            .map(|d| {
+                let in_parent = d.location.is_some_and(|l| l.contains("managed/pom.xml"));
+                (d.artifact_id, d.version, in_parent)
+            })
+            .collect();
+        assert_eq!(
+            dependencies,
+            vec![
+                ("slf4j-api".to_string(), Some("2.0.16".to_string()), false),
+                ("junit".to_string(), Some("4.13.2".to_string()), false),
+                ("common".to_string(), Some("1.0".to_string()), false),
+                ("common".to_string(), Some("1.1".to_string()), false),
+                (
+                    "commons-lang3".to_string(),
+                    Some("3.14.0".to_string()),
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dependency_management_by_type() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/maven/managed/");
+        let mut context = MavenProducerContext::new(d.clone());
+        let parent = MavenFile::new(&d.join("pom.xml"), &context).expect("maven file is parsed");
+        context.add_maven_file(&parent);
+        let child =
+            MavenFile::new(&d.join("child/pom.xml"), &context).expect("maven file is parsed");
+
+        // the jar is managed without type, the test-jar with its type
+        let versions: Vec<(Option<String>, Option<String>)> = child
+            .explain_dependency(&context, "com.project", "common")
+            .into_iter()
+            .map(|r| {
+                (
+                    r.declaration.r#type.map(|t| t.as_str().to_string()),
+                    r.version,
+                )
+            })
+            .collect();
+        assert_eq!(
+            versions,
+            vec![
+                (None, Some("1.0".to_string())),
+                (Some("test-jar".to_string()), Some("1.1".to_string()))
+            ]
+        );
+    }
 }
@@ -1,17 +1,155 @@
 use std::path::{Path, PathBuf};
 
-use crate::analyze::producers::maven::context::MavenProducerContext;
+use crate::analyze::producers::maven::context::{MavenProducerContext, DEFAULT_MAX_PARENT_DEPTH};
 use crate::analyze::producers::maven::maven_file::MavenFile;
-use crate::analyze::producers::producer::{SbomProducer, SbomProducerConfiguration};
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
 use crate::model::dependency::Dependency;
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::utils::cache::{get_hash, Cache};
+use anyhow::anyhow;
 use derive_builder::Builder;
+use rayon::prelude::*;
 
 #[derive(Clone, Builder)]
-pub struct MavenProducer {}
+pub struct MavenProducer {
+    /// Maximum number of parents followed from a `pom.xml`
+    #[builder(default = "DEFAULT_MAX_PARENT_DEPTH")]
+    pub max_parent_depth: usize,
+}
+
+/// Parse a file, or get it from the [cache] when its content did not change. Returns the
+/// hash of the content when there is a cache.
+fn read_maven_file(
+    path: &Path,
+    context: &MavenProducerContext,
+    cache: Option<&Cache>,
+) -> anyhow::Result<(MavenFile, Option<String>)> {
+    let Some(cache) = cache else {
+        return Ok((MavenFile::new(path, context)?, None));
+    };
+    let Ok(content) = context.vfs.read_to_string(path) else {
+        return Err(anyhow!("cannot parse file"));
+    };
+    let hash = get_hash(&[content.as_bytes()]);
+    let key = cache.get_key(
+        "maven-file",
+        &[path.as_os_str().as_encoded_bytes(), hash.as_bytes()],
+    );
+    if let Some(maven_file) = cache.get(&key) {
+        return Ok((maven_file, Some(hash)));
+    }
+    let maven_file = MavenFile::from_content(path, content.as_str(), context)?;
+    cache.put(&key, &maven_file);
+    Ok((maven_file, Some(hash)))
+}
 
-impl MavenProducer {}
+/// Get the key of the dependencies of a file in the [cache]: they only change when the file,
+/// one of its parents or the parents found change. None if a file is not in the cache.
+fn get_resolution_key(
+    maven_file: &MavenFile,
+    context: &MavenProducerContext,
+    cache: &Cache,
+) -> Option<String> {
+    let chain = maven_file.get_parent_chain(context);
+    let inputs = maven_file.get_resolution_inputs(context);
+    let mut parts: Vec<&[u8]> = vec![inputs.as_bytes()];
+    for path in [&maven_file.path].into_iter().chain(chain.parents.iter()) {
+        parts.push(path.as_os_str().as_encoded_bytes());
+        parts.push(context.get_content_hash(path)?.as_bytes());
+    }
+    Some(cache.get_key("maven-dependencies", parts.as_slice()))
+}
+
+impl MavenProducer {
+    /// Parse all the `pom.xml` files so that the parents can be found when resolving the
+    /// dependencies, with their parents in the allowed external roots. The files that
+    /// cannot be parsed are reported in [diagnostics].
+    pub fn get_context(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> MavenProducerContext {
+        let mut maven_context = MavenProducerContext::new(configuration.base_path.clone())
+            .with_vfs(configuration.vfs.clone())
+            .with_trees(configuration.trees.clone())
+            .with_max_parent_depth(self.max_parent_depth);
+
+        // parsed on the thread pool, then added in the order of the paths
+        let cache = configuration.cache.as_deref();
+        let maven_files: Vec<anyhow::Result<(MavenFile, Option<String>)>> = paths
+            .par_iter()
+            .map(|p| read_maven_file(p, &maven_context, cache))
+            .collect();
+        for (p, maven_file) in paths.iter().zip(maven_files) {
+            match maven_file {
+                Ok((maven_file, hash)) => {
+                    maven_context.add_maven_file(&maven_file);
+                    if let Some(hash) = hash {
+                        maven_context.add_content_hash(p, hash);
+                    }
+                }
+                Err(e) => diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    p,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        // the parents in an allowed external root are not scanned, they are read when a
+        // file references them
+        let mut index = 0;
+        while let Some(maven_file) = maven_context.get_all_files().get(index).cloned() {
+            index += 1;
+            let mut current = maven_file;
+            for _ in 0..self.max_parent_depth {
+                let Some(parent_path) = current.get_parent_file_path(&maven_context) else {
+                    break;
+                };
+                if parent_path.starts_with(configuration.vfs.root_of(&parent_path))
+                    || maven_context.get_maven_file_by_path(&parent_path).is_some()
+                {
+                    break;
+                }
+                match read_maven_file(&parent_path, &maven_context, cache) {
+                    Ok((parent, hash)) => {
+                        maven_context.add_external_maven_file(&parent);
+                        if let Some(hash) = hash {
+                            maven_context.add_content_hash(&parent_path, hash);
+                        }
+                        current = parent;
+                    }
+                    Err(e) => {
+                        diagnostics.push(Diagnostic::error(
+                            DiagnosticKind::ParseError,
+                            &parent_path,
+                            format!("cannot parse: {}", e),
+                        ));
+                        break;
+                    }
+                }
+            }
+        }
+        maven_context
+    }
+}
 
 impl SbomProducer for MavenProducer {
+    fn name(&self) -> &str {
+        "maven"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "maven"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec!["**/pom.xml"]
+    }
+
     fn use_file(&self, path: &Path, _configuration: &SbomProducerConfiguration) -> bool {
         match path.file_name() {
             Some(e) => e.eq_ignore_ascii_case("pom.xml"),
@@ -19,32 +157,385 @@ impl SbomProducer for MavenProducer {
         }
     }
 
+    fn ignored_directories(&self) -> Vec<&str> {
+        vec!["target"]
+    }
+
     fn find_dependencies(
         &self,
         paths: &[PathBuf],
         configuration: &SbomProducerConfiguration,
-    ) -> anyhow::Result<Vec<Dependency>> {
-        let mut result = vec![];
-
-        let mut maven_context = MavenProducerContext::new(configuration.base_path.clone());
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
 
         // First pass, we are getting the dependency files
-        for p in paths.iter() {
-            let maven_file = MavenFile::new(p, &maven_context).expect("maven file is parsed");
-            maven_context.add_maven_file(&maven_file);
+        let maven_context = self.get_context(paths, configuration, &mut result.diagnostics);
+
+        // Second pass, we are resolving variables and extracting dependencies, only for the
+        // files whose hierarchy changed when there is a cache
+        let cache = configuration.cache.as_deref();
+        let results: Vec<SbomProducerResult> = maven_context
+            .get_all_files()
+            .par_iter()
+            .map(|maven_file| {
+                let key = cache.and_then(|c| get_resolution_key(maven_file, &maven_context, c));
+                if let Some(cached) = key.as_ref().and_then(|k| cache?.get(k)) {
+                    return cached;
+                }
+                let mut diagnostics = vec![];
+                let dependencies: Vec<Dependency> = maven_file
+                    .get_dependencies_for_sbom(&maven_context, &mut diagnostics)
+                    .iter()
+                    .map(|d| d.into())
+                    .collect();
+                let file_result = SbomProducerResult {
+                    dependencies,
+                    diagnostics,
+                };
+                if let (Some(cache), Some(key)) = (cache, key) {
+                    cache.put(&key, &file_result);
+                }
+                file_result
+            })
+            .collect();
+        for file_result in results {
+            result.dependencies.extend(file_result.dependencies);
+            result.diagnostics.extend(file_result.diagnostics);
         }
 
-        // Second pass, we are resolving variables and extracting dependencies
-        for maven_file in maven_context.get_all_files() {
-            let deps: Vec<Dependency> = maven_file
-                .get_dependencies_for_sbom(&maven_context)
+        anyhow::Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_utils::FileFilter;
+    use crate::utils::vfs::disk::DiskVfs;
+    use crate::utils::vfs::get_vfs;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_malformed_files() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/maven/malformed");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let result = MavenProducerBuilder::default()
+            .build()
+            .unwrap()
+            .find_dependencies(
+                &[d.join("broken/pom.xml"), d.join("app/pom.xml")],
+                &configuration,
+            )
+            .unwrap();
+
+        // the broken file and the parent outside of the directory are reported, the
+        // dependencies of the other file are still found
+        let purls: Vec<&str> = result
+            .dependencies
+            .iter()
+            .map(|d| d.purl.as_str())
+            .collect();
+        assert_eq!(purls, vec!["pkg:maven/junit/junit@4.13.2"]);
+        assert!(result
+            .diagnostics
+            .iter()
+            .any(|d| d.kind == DiagnosticKind::ParseError && d.file.ends_with("broken/pom.xml")));
+        assert!(result
+            .diagnostics
+            .iter()
+            .any(|d| d.package.as_deref() == Some("pkg:maven/org.slf4j/slf4j-api")));
+    }
+
+    #[test]
+    fn test_parent_cycle() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/maven/cycle");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let result = MavenProducerBuilder::default()
+            .build()
+            .unwrap()
+            .find_dependencies(
+                &[
+                    d.join("a/pom.xml"),
+                    d.join("b/pom.xml"),
+                    d.join("self/pom.xml"),
+                ],
+                &configuration,
+            )
+            .unwrap();
+
+        // each file uses the parents walked before the cycle
+        let purls: Vec<&str> = result
+            .dependencies
+            .iter()
+            .map(|d| d.purl.as_str())
+            .collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:maven/com.example/lib-a@1.0.2",
+                "pkg:maven/com.example/lib-b@1.0.1",
+                "pkg:maven/junit/junit@4.13.2",
+            ]
+        );
+
+        let cycles: Vec<&str> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.kind == DiagnosticKind::ParentCycle)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(cycles.len(), 3);
+        assert!(cycles[0].ends_with("cycle/a/pom.xml"));
+        assert!(cycles[0].contains("cycle/b/pom.xml -> "));
+    }
+
+    #[test]
+    fn test_max_parent_depth() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/maven/hierarchy");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let result = MavenProducerBuilder::default()
+            .max_parent_depth(0)
+            .build()
+            .unwrap()
+            .find_dependencies(
+                &[d.join("pom.xml"), d.join("subproject/pom.xml")],
+                &configuration,
+            )
+            .unwrap();
+
+        assert!(result
+            .diagnostics
+            .iter()
+            .any(|d| d.kind == DiagnosticKind::ParentTooDeep
+                && d.file.ends_with("subproject/pom.xml")));
+    }
+
+    #[test]
+    fn test_parent_outside_root() {
+        let temp_directory = tempfile::tempdir().unwrap();
+        let directory = temp_directory.path();
+        let root = directory.join("repository");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::create_dir_all(directory.join("parent")).unwrap();
+        std::fs::write(
+            directory.join("parent/pom.xml"),
+            "<project>\n<groupId>com.example</groupId>\n<artifactId>parent</artifactId>\n<version>1.0.0</version>\n<properties>\n<junit.version>4.13.2</junit.version>\n</properties>\n</project>\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("pom.xml"),
+            "<project>\n<parent>\n<groupId>com.example</groupId>\n<artifactId>parent</artifactId>\n<version>1.0.0</version>\n<relativePath>../parent/pom.xml</relativePath>\n</parent>\n<artifactId>app</artifactId>\n<dependencies>\n<dependency>\n<groupId>junit</groupId>\n<artifactId>junit</artifactId>\n<version>${junit.version}</version>\n</dependency>\n</dependencies>\n</project>\n",
+        )
+        .unwrap();
+
+        let find_dependencies = |allowed_roots: Vec<PathBuf>| {
+            let configuration = SbomProducerConfiguration {
+                use_debug: false,
+                base_path: root.clone(),
+                vfs: Arc::new(DiskVfs::new(&root).with_allowed_roots(allowed_roots)),
+                cache: None,
+                trees: None,
+            };
+            MavenProducerBuilder::default()
+                .build()
+                .unwrap()
+                .find_dependencies(&[root.join("pom.xml")], &configuration)
+                .unwrap()
+        };
+
+        // the parent is not read, the property is not defined
+        let result = find_dependencies(vec![]);
+        assert!(result
+            .dependencies
+            .iter()
+            .all(|d| d.version.as_deref() != Some("4.13.2")));
+        assert!(result
+            .diagnostics
+            .iter()
+            .any(|d| d.kind == DiagnosticKind::PathOutsideRoot));
+
+        // the parent is read from the allowed root, its dependencies are not reported
+        let result = find_dependencies(vec![directory.join("parent")]);
+        let purls: Vec<&str> = result
+            .dependencies
+            .iter()
+            .map(|d| d.purl.as_str())
+            .collect();
+        assert_eq!(purls, vec!["pkg:maven/junit/junit@4.13.2"]);
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_parent_in_other_directory() {
+        let temp_directory = tempfile::tempdir().unwrap();
+        let directory = temp_directory.path();
+        let service = directory.join("service");
+        let parent = directory.join("parent-poms");
+        std::fs::create_dir_all(service.join("worker")).unwrap();
+        std::fs::create_dir_all(&parent).unwrap();
+        std::fs::write(
+            parent.join("pom.xml"),
+            "<project>\n<groupId>com.example</groupId>\n<artifactId>parent</artifactId>\n<version>1.0.0</version>\n<properties>\n<junit.version>4.13.2</junit.version>\n</properties>\n</project>\n",
+        )
+        .unwrap();
+        // found with the relativePath, from a directory to the other
+        std::fs::write(
+            service.join("pom.xml"),
+            "<project>\n<parent>\n<groupId>com.example</groupId>\n<artifactId>parent</artifactId>\n<version>1.0.0</version>\n<relativePath>../parent-poms/pom.xml</relativePath>\n</parent>\n<artifactId>app</artifactId>\n<dependencies>\n<dependency>\n<groupId>junit</groupId>\n<artifactId>junit</artifactId>\n<version>${junit.version}</version>\n</dependency>\n</dependencies>\n</project>\n",
+        )
+        .unwrap();
+        // found with the group, artifact and version
+        std::fs::write(
+            service.join("worker/pom.xml"),
+            "<project>\n<parent>\n<groupId>com.example</groupId>\n<artifactId>parent</artifactId>\n<version>1.0.0</version>\n</parent>\n<artifactId>worker</artifactId>\n<dependencies>\n<dependency>\n<groupId>org.hamcrest</groupId>\n<artifactId>hamcrest</artifactId>\n<version>${junit.version}</version>\n</dependency>\n</dependencies>\n</project>\n",
+        )
+        .unwrap();
+
+        let vfs = get_vfs(
+            &[service.display().to_string(), parent.display().to_string()],
+            None,
+            &[],
+            FileFilter::default(),
+        )
+        .unwrap();
+        assert_eq!(vfs.root_of(&parent.join("pom.xml")), parent.as_path());
+        let files = vfs.get_files().unwrap();
+        assert_eq!(files.len(), 3);
+
+        let configuration = SbomProducerConfiguration {
+            use_debug: false,
+            base_path: vfs.root().to_path_buf(),
+            vfs,
+            cache: None,
+            trees: None,
+        };
+        let result = MavenProducerBuilder::default()
+            .build()
+            .unwrap()
+            .find_dependencies(&files, &configuration)
+            .unwrap();
+        let mut purls: Vec<&str> = result
+            .dependencies
+            .iter()
+            .map(|d| d.purl.as_str())
+            .collect();
+        purls.sort();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:maven/junit/junit@4.13.2",
+                "pkg:maven/org.hamcrest/hamcrest@4.13.2"
+            ]
+        );
+        assert!(result.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_same_result_with_threads() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/maven");
+        let configuration = SbomProducerConfiguration::new(d, false);
+        let producer = MavenProducerBuilder::default().build().unwrap();
+        let files = producer.select_files(
+            configuration.vfs.get_files().unwrap().as_slice(),
+            &configuration,
+        );
+
+        let find_dependencies = |threads: usize| {
+            let result = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| producer.find_dependencies(&files, &configuration))
+                .unwrap();
+            let dependencies: Vec<String> = result
+                .dependencies
                 .iter()
-                .map(|d| d.into())
+                .map(|d| format!("{} {:?}", d.purl, d.location))
                 .collect();
+            let diagnostics: Vec<String> =
+                result.diagnostics.iter().map(|d| d.to_string()).collect();
+            (dependencies, diagnostics)
+        };
 
-            result.extend(deps)
+        let (dependencies, diagnostics) = find_dependencies(1);
+        assert!(!dependencies.is_empty());
+        for _ in 0..3 {
+            assert_eq!(
+                find_dependencies(4),
+                (dependencies.clone(), diagnostics.clone())
+            );
         }
+    }
 
-        anyhow::Ok(result)
+    #[test]
+    fn test_cache() {
+        let temp_directory = tempfile::tempdir().unwrap();
+        let directory = temp_directory.path();
+        let root = directory.join("repository");
+        std::fs::create_dir_all(root.join("app")).unwrap();
+        std::fs::create_dir_all(root.join("tool")).unwrap();
+        let write_parent = |version: &str| {
+            std::fs::write(
+                root.join("pom.xml"),
+                format!("<project>\n<groupId>com.example</groupId>\n<artifactId>parent</artifactId>\n<version>1.0.0</version>\n<properties>\n<junit.version>{}</junit.version>\n</properties>\n</project>\n", version),
+            )
+            .unwrap();
+        };
+        write_parent("4.13.1");
+        std::fs::write(
+            root.join("app/pom.xml"),
+            "<project>\n<parent>\n<groupId>com.example</groupId>\n<artifactId>parent</artifactId>\n<version>1.0.0</version>\n<relativePath>../pom.xml</relativePath>\n</parent>\n<artifactId>app</artifactId>\n<dependencies>\n<dependency>\n<groupId>junit</groupId>\n<artifactId>junit</artifactId>\n<version>${junit.version}</version>\n</dependency>\n</dependencies>\n</project>\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("tool/pom.xml"),
+            "<project>\n<groupId>com.example</groupId>\n<artifactId>tool</artifactId>\n<version>1.0.0</version>\n<dependencies>\n<dependency>\n<groupId>org.slf4j</groupId>\n<artifactId>slf4j-api</artifactId>\n<version>2.0.16</version>\n</dependency>\n</dependencies>\n</project>\n",
+        )
+        .unwrap();
+
+        let find_dependencies = |cache: Option<Arc<Cache>>| {
+            let configuration = SbomProducerConfiguration {
+                use_debug: false,
+                base_path: root.clone(),
+                vfs: Arc::new(DiskVfs::new(&root)),
+                cache,
+                trees: None,
+            };
+            let producer = MavenProducerBuilder::default().build().unwrap();
+            let files = producer.select_files(
+                configuration.vfs.get_files().unwrap().as_slice(),
+                &configuration,
+            );
+            let result = producer.find_dependencies(&files, &configuration).unwrap();
+            result
+                .dependencies
+                .iter()
+                .map(|d| format!("{} {:?}", d.purl, d.location))
+                .chain(result.diagnostics.iter().map(|d| d.to_string()))
+                .collect::<Vec<String>>()
+        };
+        let get_cache = || Arc::new(Cache::new(&directory.join("cache"), &[]));
+
+        let uncached = find_dependencies(None);
+        let cache = get_cache();
+        assert_eq!(find_dependencies(Some(cache.clone())), uncached);
+        assert_eq!(cache.get_statistics(), (0, 6));
+
+        // the files and their dependencies are read from the cache
+        let cache = get_cache();
+        assert_eq!(find_dependencies(Some(cache.clone())), uncached);
+        assert_eq!(cache.get_statistics(), (6, 0));
+
+        // the parent and its child are read again, not the other file
+        write_parent("4.13.2");
+        let cache = get_cache();
+        let dependencies = find_dependencies(Some(cache.clone()));
+        assert_eq!(dependencies, find_dependencies(None));
+        assert!(dependencies[0].starts_with("pkg:maven/junit/junit@4.13.2"));
+        assert_eq!(cache.get_statistics(), (3, 3));
     }
 }
@@ -1,8 +1,21 @@
 use std::str::FromStr;
 
-#[derive(Clone, Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MavenDependencyType {
     Pom,
+    /// Any other type, e.g. `jar` or `test-jar`
+    Other(String),
+}
+
+impl MavenDependencyType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            MavenDependencyType::Pom => "pom",
+            MavenDependencyType::Other(t) => t.as_str(),
+        }
+    }
 }
 
 impl FromStr for MavenDependencyType {
@@ -11,18 +24,29 @@ impl FromStr for MavenDependencyType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "pom" => Ok(MavenDependencyType::Pom),
-            _ => Err(()),
+            "" => Err(()),
+            _ => Ok(MavenDependencyType::Other(s.to_string())),
         }
     }
 }
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum MavenDependencyScope {
     Import,
     Test,
     Provided,
 }
 
+impl MavenDependencyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MavenDependencyScope::Import => "import",
+            MavenDependencyScope::Test => "test",
+            MavenDependencyScope::Provided => "provided",
+        }
+    }
+}
+
 impl FromStr for MavenDependencyScope {
     type Err = ();
 
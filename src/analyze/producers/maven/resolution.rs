@@ -0,0 +1,106 @@
+use crate::analyze::producers::maven::maven_file::MavenDependency;
+use crate::model::location::Location;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// The parents of a `pom.xml`, walked from the file to the root of the hierarchy
+#[derive(Clone, Debug, Default)]
+pub struct MavenParentChain {
+    /// The path of the parents found, the nearest first
+    pub parents: Vec<PathBuf>,
+    /// The files from the `pom.xml` to the first parent found twice, when the parents form
+    /// a cycle
+    pub cycle: Option<Vec<PathBuf>>,
+    /// True if the walk stopped at the maximum depth
+    pub truncated: bool,
+}
+
+/// The value of a property and where it is defined
+#[derive(Clone, Debug)]
+pub struct MavenPropertyDefinition {
+    pub value: String,
+    pub location: Option<Location>,
+}
+
+/// A `${name}` replaced by the value of the property, if defined
+#[derive(Clone, Debug)]
+pub struct MavenPropertySubstitution {
+    pub name: String,
+    pub definition: Option<MavenPropertyDefinition>,
+}
+
+/// How the version of a dependency declared in a `pom.xml` is resolved
+#[derive(Clone, Debug)]
+pub struct MavenResolution {
+    /// The file that declares the dependency
+    pub file: PathBuf,
+    /// The dependency as declared, before the properties are replaced
+    pub declaration: MavenDependency,
+    /// The entry of the `dependencyManagement` (of the file or a parent) that gives the
+    /// version, when the declaration has no version
+    pub managed_by: Option<MavenDependency>,
+    /// The properties replaced in the group, the artifact and the version, in order
+    pub substitutions: Vec<MavenPropertySubstitution>,
+    /// The parent that is not found, its properties and dependency management are not used
+    pub missing_parent: Option<String>,
+    /// The version in the SBOM, none if the dependency is dropped
+    pub version: Option<String>,
+}
+
+fn get_location_string(location: Option<&Location>, file: &Path) -> String {
+    match location {
+        Some(l) => l.to_string(),
+        None => file.display().to_string(),
+    }
+}
+
+impl Display for MavenResolution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}:{} declared in {}",
+            self.declaration.group_id,
+            self.declaration.artifact_id,
+            get_location_string(
+                self.declaration.location.as_ref().map(|l| &l.block),
+                &self.file
+            )
+        )?;
+
+        match (&self.declaration.version, &self.managed_by) {
+            (Some(v), _) => writeln!(f, "  version {} declared in the pom", v)?,
+            (None, Some(managed_by)) => writeln!(
+                f,
+                "  version {} from the dependencyManagement in {}",
+                managed_by.version.as_deref().unwrap_or("(none)"),
+                get_location_string(managed_by.location.as_ref().map(|l| &l.block), &self.file)
+            )?,
+            (None, None) => writeln!(
+                f,
+                "  no version in the pom and not in any dependencyManagement"
+            )?,
+        }
+
+        for substitution in &self.substitutions {
+            match &substitution.definition {
+                Some(d) => writeln!(
+                    f,
+                    "  ${{{}}} = {} defined in {}",
+                    substitution.name,
+                    d.value,
+                    get_location_string(d.location.as_ref(), &self.file)
+                )?,
+                None => writeln!(f, "  ${{{}}} is not defined", substitution.name)?,
+            }
+        }
+
+        if let Some(parent) = &self.missing_parent {
+            writeln!(f, "  {}", parent)?;
+        }
+
+        match &self.version {
+            Some(v) => write!(f, "  resolved version: {}", v),
+            None => write!(f, "  not resolved, the dependency is dropped"),
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod node_modules_producer;
+pub mod package_json;
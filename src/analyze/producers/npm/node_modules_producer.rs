@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::npm::package_json::{PackageJson, PackageJsonContext};
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{DependencyBuilder, DependencyEvidence, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::purl::get_purl;
+use derive_builder::Builder;
+
+const NODE_MODULES: &str = "node_modules";
+
+/// Producer for npm packages installed in a `node_modules` directory. Unlike a lockfile,
+/// the `package.json` of each installed package tells what is actually on disk.
+#[derive(Clone, Builder)]
+pub struct NodeModulesProducer {}
+
+/// Indicates if the `package.json` file is the manifest of a package installed under
+/// `node_modules`, either `node_modules/<name>/package.json` or
+/// `node_modules/@<scope>/<name>/package.json`. Other `package.json` files (e.g. the ones
+/// used as test fixtures in an installed package) are ignored.
+fn is_installed_package_manifest(path: &Path) -> bool {
+    let Some(package_directory) = path.parent() else {
+        return false;
+    };
+    let Some(parent) = package_directory.parent() else {
+        return false;
+    };
+
+    match parent.file_name().and_then(|f| f.to_str()) {
+        Some(NODE_MODULES) => true,
+        Some(scope) if scope.starts_with('@') => parent
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|f| f == NODE_MODULES)
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+impl SbomProducer for NodeModulesProducer {
+    fn name(&self) -> &str {
+        "node-modules"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "npm"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec!["**/node_modules/**/package.json"]
+    }
+
+    fn use_file(&self, path: &Path, _configuration: &SbomProducerConfiguration) -> bool {
+        match path.file_name() {
+            Some(f) => f == "package.json" && is_installed_package_manifest(path),
+            None => false,
+        }
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+        let context = PackageJsonContext::default();
+
+        for path in paths {
+            let package_json = match PackageJson::new(
+                path,
+                configuration.vfs.as_ref(),
+                configuration.trees.as_deref(),
+                &context,
+            ) {
+                Ok(p) => p,
+                Err(e) => {
+                    result.diagnostics.push(Diagnostic::error(
+                        DiagnosticKind::ParseError,
+                        path,
+                        format!("cannot parse: {}", e),
+                    ));
+                    continue;
+                }
+            };
+
+            // an installed package always has a version, if not, this is not a real package.
+            if package_json.version.is_none() {
+                continue;
+            }
+
+            let (scope, name) = package_json.get_scope_and_name();
+            let purl = get_purl("npm", scope, name, package_json.version.as_deref());
+
+            result.dependencies.push(
+                DependencyBuilder::default()
+                    .name(package_json.name.clone())
+                    .version(package_json.version.clone())
+                    .location(package_json.location.clone())
+                    .r#type(DependencyType::Library)
+                    .purl(purl)
+                    .evidence(DependencyEvidence::Installed)
+                    .build()?,
+            );
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_utils::get_files;
+
+    #[test]
+    fn test_find_installed_packages() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/npm/installed");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let producer = NodeModulesProducerBuilder::default().build().unwrap();
+        let files: Vec<PathBuf> = get_files(d.display().to_string().as_str())
+            .unwrap()
+            .into_iter()
+            .filter(|f| producer.use_file(f, &configuration))
+            .collect();
+
+        // the project manifest and the fixture inside lodash are not installed packages.
+        assert_eq!(files.len(), 3);
+
+        let mut dependencies = producer
+            .find_dependencies(files.as_slice(), &configuration)
+            .unwrap()
+            .dependencies;
+        dependencies.sort_by(|a, b| a.purl.cmp(&b.purl));
+
+        assert_eq!(dependencies.len(), 3);
+        assert_eq!(dependencies[0].purl, "pkg:npm/%40babel/core@7.24.0");
+        assert_eq!(dependencies[1].purl, "pkg:npm/lodash@4.17.21");
+        assert_eq!(dependencies[2].purl, "pkg:npm/ms@2.1.3");
+        assert!(dependencies
+            .iter()
+            .all(|d| d.evidence == DependencyEvidence::Installed));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::model::dependency::DependencyLocation;
+use crate::model::location::Location;
+use crate::model::position::LineIndex;
+use crate::utils::tree_sitter::language::get_tree_sitter_json;
+use crate::utils::tree_sitter::tree::{
+    get_captured_node, get_node_location, get_node_text, get_tree, TreeCache,
+};
+use crate::utils::vfs::Vfs;
+use anyhow::anyhow;
+use std::path::Path;
+
+/// Query to get the top-level attributes of a `package.json` file.
+const TREE_SITTER_PACKAGE_ATTRIBUTES: &str = r###"
+(document
+  (object
+    (pair
+      key: (string (string_content) @key)
+      value: (string (string_content) @value)
+    ) @pair
+  )
+  (#any-of? @key "name" "version")
+)
+"###;
+
+/// Context to parse `package.json` files, it holds the tree-sitter language and queries
+/// so that we compile them only once.
+pub struct PackageJsonContext {
+    pub language: tree_sitter::Language,
+    pub query_attributes: tree_sitter::Query,
+}
+
+impl Default for PackageJsonContext {
+    fn default() -> Self {
+        let json_language = get_tree_sitter_json();
+        PackageJsonContext {
+            query_attributes: tree_sitter::Query::new(
+                &json_language,
+                TREE_SITTER_PACKAGE_ATTRIBUTES,
+            )
+            .expect("got query package attributes"),
+            language: json_language,
+        }
+    }
+}
+
+/// The information we need from a `package.json` file.
+#[derive(Clone, Debug)]
+pub struct PackageJson {
+    pub name: String,
+    pub version: Option<String>,
+    pub location: Option<DependencyLocation>,
+}
+
+impl PackageJson {
+    /// Parse a `package.json` file. Returns an error if the file cannot be read, parsed or
+    /// does not have a name.
+    pub fn new(
+        path: &Path,
+        vfs: &dyn Vfs,
+        trees: Option<&TreeCache>,
+        context: &PackageJsonContext,
+    ) -> anyhow::Result<Self> {
+        let content = vfs.read_to_string(path)?;
+        Self::from_content(path, content.as_str(), trees, context)
+    }
+
+    /// Parse the [content] of the file at [path], from its previous tree in [trees] when
+    /// it is set.
+    pub fn from_content(
+        path: &Path,
+        content: &str,
+        trees: Option<&TreeCache>,
+        context: &PackageJsonContext,
+    ) -> anyhow::Result<Self> {
+        let tree = get_tree(path, content, &context.language, trees)
+            .ok_or(anyhow!("cannot parse tree"))?;
+        let line_index = LineIndex::new(content);
+        let path_string = path.display().to_string();
+
+        let mut name: Option<String> = None;
+        let mut version: Option<String> = None;
+        let mut name_location: Option<Location> = None;
+        let mut version_location: Option<Location> = None;
+        let mut block_location: Option<Location> = None;
+
+        let mut cursor = tree_sitter::QueryCursor::new();
+        let matches = cursor.matches(
+            &context.query_attributes,
+            tree.root_node(),
+            content.as_bytes(),
+        );
+
+        let query = &context.query_attributes;
+        for m in matches {
+            let (Some(key_node), Some(value_node), Some(pair_node)) = (
+                get_captured_node(query, &m, "key"),
+                get_captured_node(query, &m, "value"),
+                get_captured_node(query, &m, "pair"),
+            ) else {
+                continue;
+            };
+            let key = get_node_text(content, &key_node);
+            let value = get_node_text(content, &value_node).to_string();
+
+            if key == "name" {
+                name = Some(value);
+                name_location = get_node_location(&path_string, &line_index, &value_node).ok();
+                block_location = get_node_location(&path_string, &line_index, &pair_node).ok();
+            } else if key == "version" {
+                version = Some(value);
+                version_location = get_node_location(&path_string, &line_index, &value_node).ok();
+            }
+        }
+
+        let location = match (block_location, name_location) {
+            (Some(block), Some(name)) => Some(DependencyLocation {
+                block,
+                name,
+                version: version_location,
+            }),
+            _ => None,
+        };
+
+        match name {
+            Some(n) => Ok(PackageJson {
+                name: n,
+                version,
+                location,
+            }),
+            None => Err(anyhow!("package.json without a name")),
+        }
+    }
+
+    /// Split the name between the scope and the actual name.
+    /// For `@angular/core`, returns `(Some("@angular"), "core")`.
+    pub fn get_scope_and_name(&self) -> (Option<&str>, &str) {
+        match self.name.split_once('/') {
+            Some((scope, n)) if scope.starts_with('@') => (Some(scope), n),
+            _ => (None, self.name.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::vfs::disk::DiskVfs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_package_json() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/npm/installed/node_modules/@babel/core/package.json");
+        let context = PackageJsonContext::default();
+        let package_json = PackageJson::new(&d, &DiskVfs::new(&d), None, &context)
+            .expect("package.json is parsed");
+
+        assert_eq!(package_json.name, "@babel/core");
+        assert_eq!(package_json.version.clone().unwrap(), "7.24.0");
+        assert_eq!(package_json.get_scope_and_name(), (Some("@babel"), "core"));
+        let location = package_json.location.unwrap();
+        assert_eq!(location.name.start.line.get(), 2);
+        assert_eq!(location.version.unwrap().start.line.get(), 3);
+    }
+
+    #[test]
+    fn test_nested_attributes_are_ignored() {
+        let content = r#"{
+  "dependencies": { "name": "not-the-name", "version": "0.0.0" },
+  "name": "lodash",
+  "version": "4.17.21"
+}"#;
+        let context = PackageJsonContext::default();
+        let package_json =
+            PackageJson::from_content(Path::new("package.json"), content, None, &context).unwrap();
+        assert_eq!(package_json.name, "lodash");
+        assert_eq!(package_json.version.unwrap(), "4.17.21");
+    }
+}
@@ -0,0 +1,2 @@
+pub mod abi;
+pub mod wasm_producer;
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+/// The ABI between the generator and the WebAssembly plugins (version 1).
+///
+/// A plugin is a WebAssembly module without any import (it has no access to the
+/// filesystem, the network or the clock) that exports:
+///  - `memory`: its linear memory
+///  - `sbom_abi_version() -> i32`: the version of the ABI, must be [ABI_VERSION]
+///  - `sbom_alloc(size: i32) -> i32`: allocate `size` bytes, used to pass the input
+///  - `sbom_info() -> i64`: a JSON [PluginInfo]
+///  - `sbom_find_dependencies(ptr: i32, len: i32) -> i64`: receive a JSON [PluginInput]
+///    and return a JSON [PluginOutput]
+///
+/// Values returned as `i64` are a pointer in the memory of the plugin in the 32 high bits
+/// and a length in the 32 low bits.
+pub const ABI_VERSION: i32 = 1;
+
+pub const EXPORT_MEMORY: &str = "memory";
+pub const EXPORT_ABI_VERSION: &str = "sbom_abi_version";
+pub const EXPORT_ALLOC: &str = "sbom_alloc";
+pub const EXPORT_INFO: &str = "sbom_info";
+pub const EXPORT_FIND_DEPENDENCIES: &str = "sbom_find_dependencies";
+
+/// Description of the plugin
+#[derive(Clone, Debug, Deserialize)]
+pub struct PluginInfo {
+    pub name: String,
+    /// Globs of the files handled by the plugin, relative to the scanned directory
+    pub files: Vec<String>,
+}
+
+/// A file given to the plugin
+#[derive(Clone, Debug, Serialize)]
+pub struct PluginInput<'a> {
+    /// Path relative to the scanned directory
+    pub path: &'a str,
+    pub content: &'a str,
+}
+
+/// A range of bytes in the content of the file
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct PluginRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PluginLocation {
+    pub block: PluginRange,
+    pub name: PluginRange,
+    pub version: Option<PluginRange>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PluginDependency {
+    pub name: String,
+    pub version: Option<String>,
+    pub purl: String,
+    /// `library` (default), `application` or `container`
+    #[serde(rename = "type")]
+    pub component_type: Option<String>,
+    pub location: Option<PluginLocation>,
+}
+
+/// The result for a file, `error` is set when the plugin cannot parse the file.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PluginOutput {
+    #[serde(default)]
+    pub dependencies: Vec<PluginDependency>,
+    pub error: Option<String>,
+}
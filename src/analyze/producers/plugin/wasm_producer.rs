@@ -0,0 +1,392 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use derive_builder::Builder;
+use wasmi::{Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::analyze::producers::plugin::abi::{
+    PluginDependency, PluginInfo, PluginInput, PluginOutput, PluginRange, ABI_VERSION,
+    EXPORT_ABI_VERSION, EXPORT_ALLOC, EXPORT_FIND_DEPENDENCIES, EXPORT_INFO, EXPORT_MEMORY,
+};
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{Dependency, DependencyBuilder, DependencyLocation, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::location::Location;
+use crate::model::position::LineIndex;
+
+const PLUGIN_EXTENSION: &str = "wasm";
+/// Fuel given to a plugin for each file, roughly the number of instructions it can
+/// execute before being stopped.
+const FUEL_PER_FILE: u64 = 1_000_000_000;
+const MAX_MEMORY_SIZE: usize = 256 * 1024 * 1024;
+const MAX_OUTPUT_SIZE: usize = 64 * 1024 * 1024;
+
+/// Producer implemented by a WebAssembly plugin, see [crate::analyze::producers::plugin::abi]
+/// for the interface. The plugin runs in an interpreter without any host function: it
+/// only sees the content of the files it is given.
+#[derive(Clone, Builder)]
+pub struct WasmProducer {
+    pub path: PathBuf,
+    info: PluginInfo,
+    globs: globset::GlobSet,
+    engine: Engine,
+    module: Arc<Module>,
+    fuel: u64,
+}
+
+/// An instance of a plugin. A new instance is created for each file so that a file
+/// cannot have an effect on the next ones.
+struct PluginInstance {
+    store: Store<StoreLimits>,
+    instance: Instance,
+    memory: Memory,
+}
+
+impl PluginInstance {
+    fn new(engine: &Engine, module: &Module, fuel: u64) -> anyhow::Result<Self> {
+        let mut store = Store::new(
+            engine,
+            StoreLimitsBuilder::new()
+                .memory_size(MAX_MEMORY_SIZE)
+                .build(),
+        );
+        store.limiter(|limits| limits);
+        store
+            .set_fuel(fuel)
+            .map_err(|e| anyhow!("cannot set fuel: {}", e))?;
+
+        // no host function is defined: a module that imports anything cannot be instantiated
+        let linker = <Linker<StoreLimits>>::new(engine);
+        let instance = linker
+            .instantiate(&mut store, module)
+            .and_then(|i| i.start(&mut store))
+            .map_err(|e| anyhow!("cannot instantiate plugin: {}", e))?;
+        let memory = instance
+            .get_memory(&store, EXPORT_MEMORY)
+            .ok_or(anyhow!("plugin does not export {}", EXPORT_MEMORY))?;
+
+        let abi_version = instance
+            .get_typed_func::<(), i32>(&store, EXPORT_ABI_VERSION)
+            .and_then(|f| f.call(&mut store, ()))
+            .map_err(|e| anyhow!("cannot get ABI version: {}", e))?;
+        if abi_version != ABI_VERSION {
+            anyhow::bail!(
+                "unsupported ABI version {} (expected {})",
+                abi_version,
+                ABI_VERSION
+            );
+        }
+
+        Ok(PluginInstance {
+            store,
+            instance,
+            memory,
+        })
+    }
+
+    /// Read the value returned by the plugin: a pointer and a length packed in an `i64`.
+    fn read_packed(&self, packed: i64) -> anyhow::Result<Vec<u8>> {
+        let pointer = (packed as u64 >> 32) as usize;
+        let length = (packed as u64 & 0xffff_ffff) as usize;
+        if length > MAX_OUTPUT_SIZE {
+            anyhow::bail!("output of the plugin is too large ({} bytes)", length);
+        }
+        let mut buffer = vec![0; length];
+        self.memory
+            .read(&self.store, pointer, &mut buffer)
+            .map_err(|e| anyhow!("cannot read output: {}", e))?;
+        Ok(buffer)
+    }
+
+    fn get_info(&mut self) -> anyhow::Result<PluginInfo> {
+        let packed = self
+            .instance
+            .get_typed_func::<(), i64>(&self.store, EXPORT_INFO)
+            .and_then(|f| f.call(&mut self.store, ()))
+            .map_err(|e| anyhow!("cannot get plugin information: {}", e))?;
+        Ok(serde_json::from_slice(&self.read_packed(packed)?)?)
+    }
+
+    fn find_dependencies(&mut self, input: &[u8]) -> anyhow::Result<PluginOutput> {
+        let length = i32::try_from(input.len())?;
+        let pointer = self
+            .instance
+            .get_typed_func::<i32, i32>(&self.store, EXPORT_ALLOC)
+            .and_then(|f| f.call(&mut self.store, length))
+            .map_err(|e| anyhow!("cannot allocate input: {}", e))?;
+        self.memory
+            .write(&mut self.store, pointer as u32 as usize, input)
+            .map_err(|e| anyhow!("cannot write input: {}", e))?;
+
+        let packed = self
+            .instance
+            .get_typed_func::<(i32, i32), i64>(&self.store, EXPORT_FIND_DEPENDENCIES)
+            .and_then(|f| f.call(&mut self.store, (pointer, length)))
+            .map_err(|e| anyhow!("plugin failed: {}", e))?;
+        Ok(serde_json::from_slice(&self.read_packed(packed)?)?)
+    }
+}
+
+fn get_location(
+    file: &str,
+    line_index: &LineIndex,
+    range: &PluginRange,
+) -> anyhow::Result<Location> {
+    Ok(Location {
+        file: file.to_string(),
+        start: line_index.get_position(range.start)?,
+        end: line_index.get_position(range.end)?,
+    })
+}
+
+/// Convert a dependency returned by a plugin, the ranges are checked against the content.
+fn to_dependency(
+    path: &Path,
+    line_index: &LineIndex,
+    dependency: &PluginDependency,
+) -> anyhow::Result<Dependency> {
+    if dependency.name.is_empty() || !dependency.purl.starts_with("pkg:") {
+        anyhow::bail!("invalid dependency {:?}", dependency);
+    }
+    let component_type = match dependency.component_type.as_deref() {
+        Some(t) => DependencyType::from_str(t).map_err(|_| anyhow!("unknown type {}", t))?,
+        None => DependencyType::Library,
+    };
+
+    let path_string = path.display().to_string();
+    let location = dependency.location.as_ref().and_then(|l| {
+        Some(DependencyLocation {
+            block: get_location(&path_string, line_index, &l.block).ok()?,
+            name: get_location(&path_string, line_index, &l.name).ok()?,
+            version: l
+                .version
+                .and_then(|v| get_location(&path_string, line_index, &v).ok()),
+        })
+    });
+
+    Ok(DependencyBuilder::default()
+        .name(dependency.name.clone())
+        .version(dependency.version.clone())
+        .purl(dependency.purl.clone())
+        .location(location)
+        .r#type(component_type)
+        .build()?)
+}
+
+impl WasmProducer {
+    /// Compile a plugin and check that it implements the ABI.
+    pub fn from_bytes(path: &Path, bytes: &[u8]) -> anyhow::Result<Self> {
+        Self::from_bytes_with_fuel(path, bytes, FUEL_PER_FILE)
+    }
+
+    fn from_bytes_with_fuel(path: &Path, bytes: &[u8], fuel: u64) -> anyhow::Result<Self> {
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, bytes).map_err(|e| anyhow!("invalid module: {}", e))?;
+        let info = PluginInstance::new(&engine, &module, fuel)?.get_info()?;
+
+        let mut globs = globset::GlobSetBuilder::new();
+        for file in &info.files {
+            globs.add(globset::Glob::new(file).with_context(|| format!("invalid glob {}", file))?);
+        }
+
+        Ok(WasmProducerBuilder::default()
+            .path(path.to_path_buf())
+            .globs(globs.build()?)
+            .info(info)
+            .engine(engine)
+            .module(Arc::new(module))
+            .fuel(fuel)
+            .build()?)
+    }
+
+    fn find_dependencies_in_file(
+        &self,
+        path: &Path,
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let content = configuration.vfs.read_to_string(path)?;
+        let relative_path = path
+            .strip_prefix(configuration.vfs.root_of(path))
+            .unwrap_or(path);
+        let input = serde_json::to_vec(&PluginInput {
+            path: &relative_path.display().to_string(),
+            content: &content,
+        })?;
+
+        let output = PluginInstance::new(&self.engine, &self.module, self.fuel)?
+            .find_dependencies(&input)?;
+        if let Some(error) = output.error {
+            anyhow::bail!("{}", error);
+        }
+
+        let mut result = SbomProducerResult::default();
+        let line_index = LineIndex::new(&content);
+        for dependency in &output.dependencies {
+            match to_dependency(path, &line_index, dependency) {
+                Ok(d) => result.dependencies.push(d),
+                Err(e) => result.diagnostics.push(
+                    Diagnostic::warning(
+                        DiagnosticKind::DroppedDependency,
+                        path,
+                        format!("invalid dependency {}: {}", dependency.name, e),
+                    )
+                    .with_package(dependency.purl.as_str()),
+                ),
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Load all the plugins (`*.wasm` files) of a directory.
+pub fn load_plugins(directory: &Path) -> anyhow::Result<Vec<WasmProducer>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)
+        .with_context(|| format!("cannot read plugins directory {}", directory.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .map(|e| e == PLUGIN_EXTENSION)
+                    .unwrap_or(false)
+        })
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|p| {
+            fs::read(p)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| WasmProducer::from_bytes(p, &bytes))
+                .with_context(|| format!("cannot load plugin {}", p.display()))
+        })
+        .collect()
+}
+
+impl SbomProducer for WasmProducer {
+    fn name(&self) -> &str {
+        self.info.name.as_str()
+    }
+
+    fn ecosystem(&self) -> &str {
+        "plugin"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        self.info.files.iter().map(String::as_str).collect()
+    }
+
+    fn use_file(&self, path: &Path, configuration: &SbomProducerConfiguration) -> bool {
+        let relative_path = path
+            .strip_prefix(configuration.vfs.root_of(path))
+            .unwrap_or(path);
+        self.globs.is_match(relative_path)
+    }
+
+    fn is_per_file(&self) -> bool {
+        true
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+
+        for path in paths {
+            match self.find_dependencies_in_file(path, configuration) {
+                Ok(file_result) => {
+                    result.dependencies.extend(file_result.dependencies);
+                    result.diagnostics.extend(file_result.diagnostics);
+                }
+                Err(e) => result.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    path,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plugin that returns the same dependency for every file, with the location of the
+    /// first 5 bytes of the file.
+    const PLUGIN: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 1024))
+  (data (i32.const 0) "{\"name\":\"lines\",\"files\":[\"**/*.deps\"]}")
+  (data (i32.const 256) "{\"dependencies\":[{\"name\":\"left-pad\",\"version\":\"1.3.0\",\"purl\":\"pkg:npm/left-pad@1.3.0\",\"location\":{\"block\":{\"start\":0,\"end\":5},\"name\":{\"start\":0,\"end\":5}}}]}")
+  (func (export "sbom_abi_version") (result i32) (i32.const 1))
+  (func (export "sbom_alloc") (param $size i32) (result i32)
+    (local $pointer i32)
+    (local.set $pointer (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $size)))
+    (local.get $pointer))
+  (func (export "sbom_info") (result i64)
+    (i64.const 38))
+  (func (export "sbom_find_dependencies") (param i32 i32) (result i64)
+    (i64.or (i64.shl (i64.const 256) (i64.const 32)) (i64.const 156)))
+)
+"#;
+
+    #[test]
+    fn test_wasm_plugin() {
+        let bytes = wat::parse_str(PLUGIN).unwrap();
+        let producer = WasmProducer::from_bytes(Path::new("lines.wasm"), &bytes).unwrap();
+        assert_eq!(producer.name(), "lines");
+
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/plugins");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let file = d.join("project/app.deps");
+        assert!(producer.use_file(&file, &configuration));
+
+        let dependencies = producer
+            .find_dependencies(&[file], &configuration)
+            .unwrap()
+            .dependencies;
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(dependencies[0].purl, "pkg:npm/left-pad@1.3.0");
+        assert_eq!(
+            dependencies[0].location.clone().unwrap().name.end.col.get(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_wasm_plugin_sandbox() {
+        // a plugin cannot import anything from the host
+        let bytes = wat::parse_str(
+            r#"(module (import "wasi_snapshot_preview1" "fd_read" (func (param i32 i32 i32 i32) (result i32))))"#,
+        )
+        .unwrap();
+        assert!(WasmProducer::from_bytes(Path::new("wasi.wasm"), &bytes).is_err());
+
+        // a plugin that never ends is stopped
+        let bytes = wat::parse_str(
+            r#"(module
+  (memory (export "memory") 1)
+  (func (export "sbom_abi_version") (result i32) (i32.const 1))
+  (func (export "sbom_info") (result i64) (loop (br 0)) (i64.const 0)))"#,
+        )
+        .unwrap();
+        assert!(
+            WasmProducer::from_bytes_with_fuel(Path::new("loop.wasm"), &bytes, 100_000).is_err()
+        );
+    }
+}
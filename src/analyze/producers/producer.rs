@@ -1,19 +1,94 @@
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 
 use crate::model::dependency::Dependency;
+use crate::model::diagnostic::Diagnostic;
+use crate::utils::cache::Cache;
+use crate::utils::file_utils::is_in_directories;
+use crate::utils::tree_sitter::tree::TreeCache;
+use crate::utils::vfs::disk::DiskVfs;
+use crate::utils::vfs::Vfs;
 
 pub struct SbomProducerConfiguration {
     pub use_debug: bool,
+    /// The scanned directory, the first one when several are scanned: the path of a file
+    /// is relative to [Vfs::root_of]
     pub base_path: PathBuf,
+    /// The files are read through the VFS, never with `std::fs`
+    pub vfs: Arc<dyn Vfs>,
+    /// Results of the previous scans, with `--cache`
+    pub cache: Option<Arc<Cache>>,
+    /// The trees of the previous scan, to parse an edited file from its previous tree
+    /// (`watch`)
+    pub trees: Option<Arc<TreeCache>>,
+}
+
+impl SbomProducerConfiguration {
+    /// Configuration to scan a directory on the local disk.
+    pub fn new(base_path: PathBuf, use_debug: bool) -> Self {
+        SbomProducerConfiguration {
+            use_debug,
+            vfs: Arc::new(DiskVfs::new(&base_path)),
+            base_path,
+            cache: None,
+            trees: None,
+        }
+    }
+}
+
+/// What a producer found in its files: the dependencies and the problems that may explain
+/// a missing or incomplete dependency.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SbomProducerResult {
+    pub dependencies: Vec<Dependency>,
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 /// Generic trait for SBOM producer
-pub trait SbomProducer {
+pub trait SbomProducer: Send + Sync {
+    /// Name of the producer, shown by `--list-producers`
+    fn name(&self) -> &str;
+    /// Ecosystem of the dependencies found (e.g. `maven`), several producers may share one
+    fn ecosystem(&self) -> &str;
+    /// Globs of the files read by the producer, for reports only: [SbomProducer::use_file]
+    /// decides which files are scanned
+    fn file_patterns(&self) -> Vec<&str>;
     /// Report if a file should be scanned or not
     fn use_file(&self, path: &Path, configuration: &SbomProducerConfiguration) -> bool;
+    /// Directories never scanned by the producer, e.g. a build output with a copy of the
+    /// manifests
+    fn ignored_directories(&self) -> Vec<&str> {
+        vec![]
+    }
+    /// True if the result of a file only depends on its path and its content, not on the
+    /// other files: the result of each file is then cached with `--cache`.
+    fn is_per_file(&self) -> bool {
+        false
+    }
+    /// Get the files of [files] to scan: the files used by the producer that are not in one
+    /// of its ignored directories.
+    fn select_files(
+        &self,
+        files: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> Vec<PathBuf> {
+        let ignored_directories = self.ignored_directories();
+        files
+            .iter()
+            .filter(|f| {
+                self.use_file(f, configuration)
+                    && !is_in_directories(f, configuration.vfs.root_of(f), &ignored_directories)
+            })
+            .cloned()
+            .collect()
+    }
+    /// Find the dependencies of the files. An error means that the producer failed for
+    /// all the files, problems with a single file are reported as diagnostics.
     fn find_dependencies(
         &self,
         paths: &[PathBuf],
         configuration: &SbomProducerConfiguration,
-    ) -> anyhow::Result<Vec<Dependency>>;
+    ) -> anyhow::Result<SbomProducerResult>;
 }
@@ -0,0 +1,2 @@
+pub mod dist_info;
+pub mod site_packages_producer;
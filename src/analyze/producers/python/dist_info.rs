@@ -0,0 +1,164 @@
+use crate::model::dependency::DependencyLocation;
+use crate::model::location::Location;
+use crate::model::position::LineIndex;
+use crate::utils::vfs::Vfs;
+use anyhow::anyhow;
+use std::path::Path;
+
+pub const DIST_INFO_EXTENSION: &str = ".dist-info";
+pub const METADATA_FILE: &str = "METADATA";
+pub const RECORD_FILE: &str = "RECORD";
+
+/// An installed Python distribution, found in a `<name>-<version>.dist-info` directory.
+#[derive(Clone, Debug)]
+pub struct DistInfo {
+    pub name: String,
+    pub version: String,
+    pub location: Option<DependencyLocation>,
+}
+
+fn get_location(path: &str, line_index: &LineIndex, start: usize, end: usize) -> Option<Location> {
+    Some(Location {
+        file: path.to_string(),
+        start: line_index.get_position(start).ok()?,
+        end: line_index.get_position(end).ok()?,
+    })
+}
+
+impl DistInfo {
+    /// Read the `METADATA` file of a distribution. The file uses the email header format,
+    /// we only read the headers before the first empty line (the rest is the description).
+    pub fn from_metadata_file(path: &Path, vfs: &dyn Vfs) -> anyhow::Result<Self> {
+        let content = vfs.read_to_string(path)?;
+        Self::from_metadata(path, content.as_str())
+    }
+
+    pub fn from_metadata(path: &Path, content: &str) -> anyhow::Result<Self> {
+        let path_string = path.display().to_string();
+        let line_index = LineIndex::new(content);
+        let mut name: Option<(String, Option<Location>)> = None;
+        let mut version: Option<(String, Option<Location>)> = None;
+        let mut block_end: usize = 0;
+
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            let trimmed = line.trim_end_matches(['\r', '\n']);
+
+            if trimmed.is_empty() {
+                break;
+            }
+            block_end = line_start + trimmed.len();
+
+            if let Some((key, value)) = trimmed.split_once(':') {
+                let value_trimmed = value.trim();
+                let value_start =
+                    line_start + key.len() + 1 + (value.len() - value.trim_start().len());
+                let value_end = value_start + value_trimmed.len();
+                let location = get_location(&path_string, &line_index, value_start, value_end);
+
+                match key.trim().to_lowercase().as_str() {
+                    "name" if name.is_none() => name = Some((value_trimmed.to_string(), location)),
+                    "version" if version.is_none() => {
+                        version = Some((value_trimmed.to_string(), location))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        match (name, version) {
+            (Some((n, name_location)), Some((v, version_location))) => {
+                let block_location = get_location(&path_string, &line_index, 0, block_end);
+                let location = match (block_location, name_location) {
+                    (Some(block), Some(name)) => Some(DependencyLocation {
+                        block,
+                        name,
+                        version: version_location,
+                    }),
+                    _ => None,
+                };
+                Ok(DistInfo {
+                    name: n,
+                    version: v,
+                    location,
+                })
+            }
+            _ => Err(anyhow!("METADATA without name or version")),
+        }
+    }
+
+    /// Get the distribution from the name of the `.dist-info` directory, used when the
+    /// `METADATA` file is missing or broken but the `RECORD` file shows the package is
+    /// installed. The name is `<name>-<version>.dist-info` where `-` in the name were
+    /// replaced by `_`.
+    pub fn from_directory(directory: &Path) -> Option<Self> {
+        let directory_name = directory.file_name()?.to_str()?;
+        let stem = directory_name.strip_suffix(DIST_INFO_EXTENSION)?;
+        let (name, version) = stem.split_once('-')?;
+
+        if name.is_empty() || version.is_empty() {
+            return None;
+        }
+
+        Some(DistInfo {
+            name: name.to_string(),
+            version: version.to_string(),
+            location: None,
+        })
+    }
+}
+
+/// Indicates if a `RECORD` file lists at least one installed file. An empty `RECORD`
+/// means the distribution was not (or no longer) installed.
+pub fn record_has_entries(content: &str) -> bool {
+    content.lines().any(|l| {
+        l.split(',')
+            .next()
+            .map(|f| !f.trim().is_empty())
+            .unwrap_or(false)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::purl::normalize_package_name;
+    use crate::utils::vfs::disk::DiskVfs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_metadata() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push(
+            "resources/python/venv/lib/python3.12/site-packages/requests-2.31.0.dist-info/METADATA",
+        );
+        let dist_info =
+            DistInfo::from_metadata_file(&d, &DiskVfs::new(&d)).expect("METADATA is parsed");
+
+        assert_eq!(dist_info.name, "requests");
+        assert_eq!(dist_info.version, "2.31.0");
+        let location = dist_info.location.unwrap();
+        assert_eq!(location.name.start.line.get(), 2);
+        assert_eq!(location.name.start.col.get(), 7);
+        assert_eq!(location.version.unwrap().start.line.get(), 3);
+    }
+
+    #[test]
+    fn test_description_is_ignored() {
+        let content = "Metadata-Version: 2.1\nName: foo\n\nVersion: 1.0\n";
+        assert!(DistInfo::from_metadata(Path::new("METADATA"), content).is_err());
+    }
+
+    #[test]
+    fn test_from_directory() {
+        let dist_info =
+            DistInfo::from_directory(Path::new("site-packages/Flask_SQLAlchemy-3.1.1.dist-info"))
+                .unwrap();
+        assert_eq!(dist_info.name, "Flask_SQLAlchemy");
+        assert_eq!(dist_info.version, "3.1.1");
+        assert_eq!(normalize_package_name(&dist_info.name), "flask-sqlalchemy");
+        assert!(DistInfo::from_directory(Path::new("site-packages/foo.dist-info")).is_none());
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::analyze::producers::python::dist_info::{
+    record_has_entries, DistInfo, DIST_INFO_EXTENSION, METADATA_FILE, RECORD_FILE,
+};
+use crate::model::dependency::{DependencyBuilder, DependencyEvidence, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::purl::{get_purl, normalize_package_name};
+use derive_builder::Builder;
+
+/// Producer for Python distributions installed in a `site-packages` directory (system
+/// installation, virtualenv, rootfs, etc). We read the `METADATA` and `RECORD` files of
+/// each `.dist-info` directory.
+#[derive(Clone, Builder)]
+pub struct SitePackagesProducer {}
+
+#[derive(Default)]
+struct DistInfoFiles {
+    metadata: Option<PathBuf>,
+    record: Option<PathBuf>,
+}
+
+impl SbomProducer for SitePackagesProducer {
+    fn name(&self) -> &str {
+        "site-packages"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "pypi"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec!["**/*.dist-info/METADATA", "**/*.dist-info/RECORD"]
+    }
+
+    fn use_file(&self, path: &Path, _configuration: &SbomProducerConfiguration) -> bool {
+        let in_dist_info = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|f| f.to_str())
+            .map(|f| f.ends_with(DIST_INFO_EXTENSION))
+            .unwrap_or(false);
+
+        match path.file_name() {
+            Some(f) => in_dist_info && (f == METADATA_FILE || f == RECORD_FILE),
+            None => false,
+        }
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+
+        // group the files by .dist-info directory
+        let mut distributions: BTreeMap<PathBuf, DistInfoFiles> = BTreeMap::new();
+        for path in paths {
+            let Some(directory) = path.parent() else {
+                continue;
+            };
+            let files = distributions.entry(directory.to_path_buf()).or_default();
+            match path.file_name() {
+                Some(f) if f == METADATA_FILE => files.metadata = Some(path.clone()),
+                Some(f) if f == RECORD_FILE => files.record = Some(path.clone()),
+                _ => {}
+            }
+        }
+
+        for (directory, files) in distributions {
+            let from_metadata = files.metadata.as_ref().and_then(|m| {
+                match DistInfo::from_metadata_file(m, configuration.vfs.as_ref()) {
+                    Ok(d) => Some(d),
+                    Err(e) => {
+                        result.diagnostics.push(Diagnostic::error(
+                            DiagnosticKind::ParseError,
+                            m,
+                            format!("cannot parse: {}", e),
+                        ));
+                        None
+                    }
+                }
+            });
+
+            // no usable METADATA: rely on the directory name, but only if RECORD shows
+            // that files were actually installed.
+            let dist_info = from_metadata.or_else(|| {
+                let record = files.record.as_ref()?;
+                let content = configuration.vfs.read_to_string(record).ok()?;
+                if record_has_entries(content.as_str()) {
+                    DistInfo::from_directory(&directory)
+                } else {
+                    None
+                }
+            });
+
+            if let Some(d) = dist_info {
+                let purl = get_purl(
+                    "pypi",
+                    None,
+                    normalize_package_name(d.name.as_str()).as_str(),
+                    Some(d.version.as_str()),
+                );
+                result.dependencies.push(
+                    DependencyBuilder::default()
+                        .name(d.name)
+                        .version(Some(d.version))
+                        .location(d.location)
+                        .r#type(DependencyType::Library)
+                        .purl(purl)
+                        .evidence(DependencyEvidence::Installed)
+                        .build()?,
+                );
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_utils::get_files;
+
+    #[test]
+    fn test_find_installed_distributions() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/python/venv");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let producer = SitePackagesProducerBuilder::default().build().unwrap();
+        let files: Vec<PathBuf> = get_files(d.display().to_string().as_str())
+            .unwrap()
+            .into_iter()
+            .filter(|f| producer.use_file(f, &configuration))
+            .collect();
+
+        let dependencies = producer
+            .find_dependencies(files.as_slice(), &configuration)
+            .unwrap()
+            .dependencies;
+
+        assert_eq!(dependencies.len(), 2);
+        assert_eq!(dependencies[0].purl, "pkg:pypi/flask-sqlalchemy@3.1.1");
+        assert!(dependencies[0].location.is_none());
+        assert_eq!(dependencies[1].purl, "pkg:pypi/requests@2.31.0");
+        assert!(dependencies[1].location.is_some());
+        assert!(dependencies
+            .iter()
+            .all(|d| d.evidence == DependencyEvidence::Installed));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::analyze::producers::binary::binary_producer::BinaryProducerBuilder;
+use crate::analyze::producers::cocoapods::podfile_lock_producer::PodfileLockProducerBuilder;
+use crate::analyze::producers::custom::definition::load_custom_producers;
+use crate::analyze::producers::dart::pubspec_lock_producer::PubspecLockProducerBuilder;
+use crate::analyze::producers::docker::dockerfile_producer::DockerfileProducerBuilder;
+use crate::analyze::producers::github_actions::workflow_producer::WorkflowProducerBuilder;
+use crate::analyze::producers::helm::helm_chart_producer::HelmChartProducerBuilder;
+use crate::analyze::producers::ivy::ivy_producer::IvyProducerBuilder;
+use crate::analyze::producers::maven::maven_producer::MavenProducerBuilder;
+use crate::analyze::producers::npm::node_modules_producer::NodeModulesProducerBuilder;
+use crate::analyze::producers::plugin::wasm_producer::load_plugins;
+use crate::analyze::producers::producer::SbomProducer;
+use crate::analyze::producers::python::site_packages_producer::SitePackagesProducerBuilder;
+use crate::analyze::producers::sbt::sbt_producer::SbtProducerBuilder;
+use crate::analyze::producers::swift::package_resolved_producer::PackageResolvedProducerBuilder;
+use crate::analyze::producers::terraform::terraform_lock_producer::TerraformLockProducerBuilder;
+use crate::model::configuration::Configuration;
+
+/// All the producers available for a scan: the built-in ones, then the custom producers
+/// of the configuration file and the WebAssembly plugins.
+pub struct ProducerRegistry {
+    producers: Vec<Box<dyn SbomProducer>>,
+}
+
+impl ProducerRegistry {
+    pub fn new(configuration: &Configuration) -> anyhow::Result<Self> {
+        let mut all_producers: Vec<Box<dyn SbomProducer>> = vec![
+            Box::new(
+                MavenProducerBuilder::default()
+                    .max_parent_depth(configuration.max_parent_depth)
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                IvyProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                SbtProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                NodeModulesProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                SitePackagesProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                BinaryProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                PackageResolvedProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                PodfileLockProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                PubspecLockProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                DockerfileProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                HelmChartProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                TerraformLockProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+            Box::new(
+                WorkflowProducerBuilder::default()
+                    .build()
+                    .expect("build producer"),
+            ),
+        ];
+
+        if let Some(config) = &configuration.config {
+            for custom_producer in load_custom_producers(Path::new(config))? {
+                all_producers.push(Box::new(custom_producer));
+            }
+        }
+
+        if let Some(plugins) = &configuration.plugins {
+            for plugin in load_plugins(Path::new(plugins))? {
+                all_producers.push(Box::new(plugin));
+            }
+        }
+
+        Ok(ProducerRegistry {
+            producers: all_producers,
+        })
+    }
+
+    pub fn producers(&self) -> &[Box<dyn SbomProducer>] {
+        self.producers.as_slice()
+    }
+
+    pub fn into_producers(self) -> Vec<Box<dyn SbomProducer>> {
+        self.producers
+    }
+
+    /// Keep the producers selected by `--producers` (all when empty) and not excluded by
+    /// `--exclude-producers`. A producer is selected by its name or its ecosystem, so that
+    /// `maven` selects the Maven, Ivy and sbt producers. Unknown names are errors to catch
+    /// typos that would silently produce an empty SBOM.
+    pub fn select(self, include: &[String], exclude: &[String]) -> anyhow::Result<Self> {
+        let known: HashSet<&str> = self
+            .producers
+            .iter()
+            .flat_map(|p| [p.name(), p.ecosystem()])
+            .collect();
+        if let Some(unknown) = include
+            .iter()
+            .chain(exclude.iter())
+            .find(|n| !known.contains(n.as_str()))
+        {
+            anyhow::bail!("unknown producer or ecosystem {}", unknown);
+        }
+
+        let matches = |producer: &dyn SbomProducer, names: &[String]| {
+            names
+                .iter()
+                .any(|n| n == producer.name() || n == producer.ecosystem())
+        };
+        let producers = self
+            .producers
+            .into_iter()
+            .filter(|p| include.is_empty() || matches(p.as_ref(), include))
+            .filter(|p| !matches(p.as_ref(), exclude))
+            .collect();
+        Ok(ProducerRegistry { producers })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_registry() -> ProducerRegistry {
+        let configuration = Configuration {
+            output: "sbom.json".to_string(),
+            ..Configuration::default()
+        };
+        ProducerRegistry::new(&configuration).unwrap()
+    }
+
+    fn get_names(registry: &ProducerRegistry) -> Vec<&str> {
+        registry.producers().iter().map(|p| p.name()).collect()
+    }
+
+    #[test]
+    fn test_select_producers() {
+        let registry = get_registry()
+            .select(&["maven".to_string(), "dockerfile".to_string()], &[])
+            .unwrap();
+        assert_eq!(
+            get_names(&registry),
+            vec!["maven", "ivy", "sbt", "dockerfile"]
+        );
+
+        let registry = get_registry()
+            .select(&["maven".to_string()], &["ivy".to_string()])
+            .unwrap();
+        assert_eq!(get_names(&registry), vec!["maven", "sbt"]);
+
+        let registry = get_registry().select(&[], &["binary".to_string()]).unwrap();
+        assert!(!get_names(&registry).contains(&"binary"));
+        assert!(get_names(&registry).contains(&"node-modules"));
+    }
+
+    #[test]
+    fn test_select_unknown_producer() {
+        let error = get_registry()
+            .select(&["gradle".to_string()], &[])
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("gradle"));
+    }
+}
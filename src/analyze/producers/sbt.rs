@@ -0,0 +1 @@
+pub mod sbt_producer;
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{DependencyBuilder, DependencyLocation, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::location::Location;
+use crate::model::position::LineIndex;
+use crate::model::purl::get_purl;
+use derive_builder::Builder;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    /// A module, e.g. `"org.typelevel" %% "cats-core" % "2.10.0"`, the version can be a
+    /// string or a `val`.
+    static ref REGEX_MODULE: Regex = Regex::new(
+        r#""(?P<group>[^"\s]+)"\s*(?P<operator>%{1,3})\s*"(?P<artifact>[^"\s]+)"\s*%\s*(?:"(?P<version>[^"\s]+)"|(?P<variable>[A-Za-z_][A-Za-z0-9_]*))"#
+    )
+    .unwrap();
+    /// A plugin of the build, e.g. `addSbtPlugin("com.github.sbt" % "sbt-git" % "2.0.1")`
+    static ref REGEX_PLUGIN: Regex = Regex::new(r"addSbtPlugin\s*\(\s*").unwrap();
+    static ref REGEX_SCALA_VERSION: Regex =
+        Regex::new(r#"scalaVersion\s*:=\s*"(?P<version>[^"]+)""#).unwrap();
+    static ref REGEX_VAL: Regex =
+        Regex::new(r#"(?m)^\s*(?:lazy\s+)?val\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*=\s*"(?P<value>[^"]*)""#)
+            .unwrap();
+}
+
+/// Version of Scala used when the build does not set `scalaVersion`, also the version
+/// used by sbt 1.x to compile the build definition and its plugins.
+const DEFAULT_SCALA_BINARY_VERSION: &str = "2.12";
+/// Plugins are published with the binary versions of Scala and sbt, e.g.
+/// `sbt-native-packager_2.12_1.0`.
+const SBT_BINARY_VERSION: &str = "1.0";
+const SBT_EXTENSION: &str = "sbt";
+/// The directory of the build definition (`project/plugins.sbt`)
+const META_BUILD_DIRECTORY: &str = "project";
+
+/// Producer for the dependencies and plugins declared in the `*.sbt` files of a Scala
+/// project. Modules declared with `%%` get the Scala binary version suffix
+/// (e.g. `cats-core_2.13`).
+#[derive(Clone, Builder)]
+pub struct SbtProducer {}
+
+/// Get the binary version of Scala: `2.13` for `2.13.12`, `3` for `3.3.1`.
+fn get_scala_binary_version(version: &str) -> String {
+    let mut parts = version.split('.');
+    match (parts.next(), parts.next()) {
+        (Some("2"), Some(minor)) => format!("2.{}", minor),
+        (Some(major), _) => major.to_string(),
+        _ => version.to_string(),
+    }
+}
+
+fn get_location(
+    file: &str,
+    line_index: &LineIndex,
+    start: usize,
+    end: usize,
+) -> anyhow::Result<Location> {
+    Ok(Location {
+        file: file.to_string(),
+        start: line_index.get_position(start)?,
+        end: line_index.get_position(end)?,
+    })
+}
+
+fn get_dependencies_from_content(
+    path: &Path,
+    content: &str,
+    is_meta_build: bool,
+) -> anyhow::Result<SbomProducerResult> {
+    let mut result = SbomProducerResult::default();
+    let path_string = path.display().to_string();
+    let line_index = LineIndex::new(content);
+
+    let scala_binary_version = match REGEX_SCALA_VERSION.captures(content) {
+        Some(c) if !is_meta_build => get_scala_binary_version(&c["version"]),
+        _ => DEFAULT_SCALA_BINARY_VERSION.to_string(),
+    };
+    let variables: HashMap<&str, &str> = REGEX_VAL
+        .captures_iter(content)
+        .filter_map(|c| Some((c.name("name")?.as_str(), c.name("value")?.as_str())))
+        .collect();
+    let plugin_starts: Vec<usize> = REGEX_PLUGIN.find_iter(content).map(|m| m.end()).collect();
+
+    for captures in REGEX_MODULE.captures_iter(content) {
+        let (Some(block), Some(group), Some(artifact)) = (
+            captures.get(0),
+            captures.name("group"),
+            captures.name("artifact"),
+        ) else {
+            continue;
+        };
+        let version_match = captures.name("version").or(captures.name("variable"));
+        let version = match (captures.name("version"), captures.name("variable")) {
+            (Some(v), _) => Some(v.as_str()),
+            (None, Some(v)) => variables.get(v.as_str()).copied(),
+            _ => None,
+        };
+        let is_plugin = plugin_starts.contains(&block.start());
+        let artifact_id = match captures["operator"].len() {
+            _ if is_plugin => format!(
+                "{}_{}_{}",
+                artifact.as_str(),
+                DEFAULT_SCALA_BINARY_VERSION,
+                SBT_BINARY_VERSION
+            ),
+            1 => artifact.as_str().to_string(),
+            2 => format!("{}_{}", artifact.as_str(), scala_binary_version),
+            // `%%%` depends on the platform (Scala.js or Scala Native) of the project
+            _ => {
+                result.diagnostics.push(
+                    Diagnostic::warning(
+                        DiagnosticKind::DroppedDependency,
+                        path,
+                        format!(
+                            "{}:{} is a Scala.js or Scala Native dependency (%%%), it is dropped",
+                            group.as_str(),
+                            artifact.as_str()
+                        ),
+                    )
+                    .with_location(
+                        get_location(&path_string, &line_index, block.start(), block.end()).ok(),
+                    ),
+                );
+                continue;
+            }
+        };
+
+        let Some(version) = version else {
+            result.diagnostics.push(
+                Diagnostic::warning(
+                    DiagnosticKind::UnresolvedProperty,
+                    path,
+                    format!(
+                        "cannot resolve the version of {}:{}, it is dropped",
+                        group.as_str(),
+                        artifact_id
+                    ),
+                )
+                .with_location(
+                    get_location(&path_string, &line_index, block.start(), block.end()).ok(),
+                )
+                .with_package(get_purl(
+                    "maven",
+                    Some(group.as_str()),
+                    artifact_id.as_str(),
+                    None,
+                )),
+            );
+            continue;
+        };
+
+        let location = match (
+            get_location(&path_string, &line_index, block.start(), block.end()),
+            get_location(&path_string, &line_index, artifact.start(), artifact.end()),
+        ) {
+            (Ok(block_location), Ok(name_location)) => Some(DependencyLocation {
+                block: block_location,
+                name: name_location,
+                version: version_match
+                    .and_then(|v| get_location(&path_string, &line_index, v.start(), v.end()).ok()),
+            }),
+            _ => None,
+        };
+
+        result.dependencies.push(
+            DependencyBuilder::default()
+                .name(format!("{}:{}", group.as_str(), artifact_id))
+                .version(Some(version.to_string()))
+                .purl(get_purl(
+                    "maven",
+                    Some(group.as_str()),
+                    artifact_id.as_str(),
+                    Some(version),
+                ))
+                .location(location)
+                .r#type(DependencyType::Library)
+                .build()?,
+        );
+    }
+
+    Ok(result)
+}
+
+impl SbomProducer for SbtProducer {
+    fn name(&self) -> &str {
+        "sbt"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "maven"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec!["**/*.sbt"]
+    }
+
+    fn use_file(&self, path: &Path, _configuration: &SbomProducerConfiguration) -> bool {
+        match path.extension() {
+            Some(e) => e == SBT_EXTENSION,
+            None => false,
+        }
+    }
+
+    fn ignored_directories(&self) -> Vec<&str> {
+        vec!["target"]
+    }
+
+    fn is_per_file(&self) -> bool {
+        true
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+
+        for path in paths {
+            let is_meta_build = path
+                .parent()
+                .and_then(|p| p.file_name())
+                .map(|f| f == META_BUILD_DIRECTORY)
+                .unwrap_or(false);
+            let dependencies = configuration
+                .vfs
+                .read_to_string(path)
+                .and_then(|content| get_dependencies_from_content(path, &content, is_meta_build));
+            match dependencies {
+                Ok(r) => {
+                    result.dependencies.extend(r.dependencies);
+                    result.diagnostics.extend(r.diagnostics);
+                }
+                Err(e) => result.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    path,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_scala_binary_version() {
+        assert_eq!(get_scala_binary_version("2.13.12"), "2.13");
+        assert_eq!(get_scala_binary_version("2.12.18"), "2.12");
+        assert_eq!(get_scala_binary_version("3.3.1"), "3");
+    }
+
+    #[test]
+    fn test_sbt_files() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/sbt");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let dependencies = SbtProducerBuilder::default()
+            .build()
+            .unwrap()
+            .find_dependencies(
+                &[d.join("build.sbt"), d.join("project/plugins.sbt")],
+                &configuration,
+            )
+            .unwrap()
+            .dependencies;
+
+        let purls: Vec<&str> = dependencies.iter().map(|d| d.purl.as_str()).collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:maven/io.circe/circe-core_2.13@0.14.6",
+                "pkg:maven/org.typelevel/cats-effect_2.13@3.5.2",
+                "pkg:maven/com.typesafe/config@1.4.3",
+                "pkg:maven/org.scalameta/munit_2.13@0.7.29",
+                "pkg:maven/com.github.sbt/sbt-native-packager_2.12_1.0@1.9.16",
+                "pkg:maven/org.scalameta/sbt-scalafmt_2.12_1.0@2.5.2",
+            ]
+        );
+        assert_eq!(dependencies[0].name, "io.circe:circe-core_2.13");
+
+        // the version comes from a val, its location is where the val is used
+        let location = dependencies[0].location.clone().unwrap();
+        assert_eq!(location.name.start.line.get(), 10);
+        assert_eq!(location.version.unwrap().start.col.get(), 36);
+    }
+
+    #[test]
+    fn test_plugin_and_platform_dependencies() {
+        let content = "addSbtPlugin( \"org.scala-js\" % \"sbt-scalajs\" % \"1.15.0\")\nlibraryDependencies += \"org.scala-js\" %%% \"scalajs-dom\" % \"2.8.0\"\n";
+        let result = get_dependencies_from_content(Path::new("build.sbt"), content, false).unwrap();
+        let purls: Vec<&str> = result
+            .dependencies
+            .iter()
+            .map(|d| d.purl.as_str())
+            .collect();
+        assert_eq!(
+            purls,
+            vec!["pkg:maven/org.scala-js/sbt-scalajs_2.12_1.0@1.15.0"]
+        );
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(
+            result.diagnostics[0].kind,
+            DiagnosticKind::DroppedDependency
+        );
+        assert!(result.diagnostics[0]
+            .message
+            .contains("org.scala-js:scalajs-dom"));
+    }
+
+    #[test]
+    fn test_unresolved_version() {
+        let content = "libraryDependencies += \"org.slf4j\" % \"slf4j-api\" % slf4jVersion\n";
+        let result = get_dependencies_from_content(Path::new("build.sbt"), content, false).unwrap();
+        assert!(result.dependencies.is_empty());
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(
+            result.diagnostics[0].kind,
+            DiagnosticKind::UnresolvedProperty
+        );
+        assert!(result.diagnostics[0]
+            .message
+            .contains("org.slf4j:slf4j-api"));
+    }
+}
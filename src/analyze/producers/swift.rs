@@ -0,0 +1 @@
+pub mod package_resolved_producer;
@@ -0,0 +1,250 @@
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{Dependency, DependencyBuilder, DependencyLocation, DependencyType};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::position::LineIndex;
+use crate::model::purl::get_purl;
+use crate::utils::tree_sitter::json::{get_object_attribute, get_object_string};
+use crate::utils::tree_sitter::language::get_tree_sitter_json;
+use crate::utils::tree_sitter::tree::{get_captured_node, get_node_location, get_tree, TreeCache};
+use derive_builder::Builder;
+
+/// Get all the pins. In v1, they are under `object.pins`, in v2 and v3 they are at the
+/// top-level. Both are matched by this query.
+const TREE_SITTER_QUERY_PINS: &str = r###"
+(pair
+  key: (string (string_content) @pins)
+  value: (array (object) @pin)
+  (#eq? @pins "pins")
+)
+"###;
+
+/// Producer for the `Package.resolved` file generated by the Swift Package Manager
+/// (formats v1, v2 and v3).
+#[derive(Clone, Builder)]
+pub struct PackageResolvedProducer {}
+
+/// Transform the URL of a repository into the namespace and name of the purl.
+/// `https://github.com/Alamofire/Alamofire.git` returns `("github.com/Alamofire", "Alamofire")`
+/// and `git@github.com:apple/swift-nio.git` returns `("github.com/apple", "swift-nio")`.
+fn get_namespace_and_name(url: &str) -> Option<(String, String)> {
+    let without_scheme = match url.split_once("://") {
+        Some((_, rest)) => rest.to_string(),
+        // scp-like syntax used by ssh
+        None => url.split_once('@')?.1.replacen(':', "/", 1),
+    };
+    let without_credentials = without_scheme
+        .split_once('@')
+        .map(|(_, r)| r.to_string())
+        .unwrap_or(without_scheme);
+    let path = without_credentials
+        .trim_end_matches('/')
+        .trim_end_matches(".git");
+    let (namespace, name) = path.rsplit_once('/')?;
+    Some((namespace.to_string(), name.to_string()))
+}
+
+fn get_dependencies_from_content(
+    path: &Path,
+    content: &str,
+    language: &tree_sitter::Language,
+    query: &tree_sitter::Query,
+    trees: Option<&TreeCache>,
+) -> anyhow::Result<Vec<Dependency>> {
+    let mut result = vec![];
+    let path_string = path.display().to_string();
+    let tree =
+        get_tree(path, content, language, trees).ok_or(anyhow::anyhow!("cannot parse tree"))?;
+    let line_index = LineIndex::new(content);
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    for m in cursor.matches(query, tree.root_node(), content.as_bytes()) {
+        let Some(pin) = get_captured_node(query, &m, "pin") else {
+            continue;
+        };
+
+        // v1 uses package/repositoryURL, v2 and later use identity/location
+        let Some((name, name_node)) = get_object_string(content, &pin, "identity")
+            .or_else(|| get_object_string(content, &pin, "package"))
+        else {
+            continue;
+        };
+        let url = get_object_string(content, &pin, "location")
+            .or_else(|| get_object_string(content, &pin, "repositoryURL"))
+            .map(|(u, _)| u);
+
+        let Some(state) = get_object_attribute(content, &pin, "state") else {
+            continue;
+        };
+        // a pin on a branch does not have a version, the revision is then the version
+        let Some((version, version_node)) = get_object_string(content, &state, "version")
+            .or_else(|| get_object_string(content, &state, "revision"))
+        else {
+            continue;
+        };
+
+        let purl = match url.and_then(get_namespace_and_name) {
+            Some((namespace, n)) => get_purl("swift", Some(&namespace), &n, Some(version)),
+            None => get_purl("swift", None, name, Some(version)),
+        };
+
+        let location = match (
+            get_node_location(&path_string, &line_index, &pin),
+            get_node_location(&path_string, &line_index, &name_node),
+        ) {
+            (Ok(block), Ok(name_location)) => Some(DependencyLocation {
+                block,
+                name: name_location,
+                version: get_node_location(&path_string, &line_index, &version_node).ok(),
+            }),
+            _ => None,
+        };
+
+        result.push(
+            DependencyBuilder::default()
+                .name(name.to_string())
+                .version(Some(version.to_string()))
+                .location(location)
+                .r#type(DependencyType::Library)
+                .purl(purl)
+                .build()?,
+        );
+    }
+
+    Ok(result)
+}
+
+impl SbomProducer for PackageResolvedProducer {
+    fn name(&self) -> &str {
+        "package-resolved"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "swift"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec!["**/Package.resolved"]
+    }
+
+    fn use_file(&self, path: &Path, _configuration: &SbomProducerConfiguration) -> bool {
+        match path.file_name() {
+            Some(e) => e == "Package.resolved",
+            None => false,
+        }
+    }
+
+    fn ignored_directories(&self) -> Vec<&str> {
+        vec![".build"]
+    }
+
+    fn is_per_file(&self) -> bool {
+        true
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+        let language = get_tree_sitter_json();
+        let query =
+            tree_sitter::Query::new(&language, TREE_SITTER_QUERY_PINS).expect("got query pins");
+
+        for path in paths {
+            let dependencies = configuration.vfs.read_to_string(path).and_then(|content| {
+                get_dependencies_from_content(
+                    path,
+                    &content,
+                    &language,
+                    &query,
+                    configuration.trees.as_deref(),
+                )
+            });
+            match dependencies {
+                Ok(deps) => result.dependencies.extend(deps),
+                Err(e) => result.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    path,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_dependencies(file: &str) -> Vec<Dependency> {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push(file);
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        PackageResolvedProducerBuilder::default()
+            .build()
+            .unwrap()
+            .find_dependencies(&[d], &configuration)
+            .unwrap()
+            .dependencies
+    }
+
+    #[test]
+    fn test_get_namespace_and_name() {
+        assert_eq!(
+            get_namespace_and_name("https://github.com/Alamofire/Alamofire.git"),
+            Some(("github.com/Alamofire".to_string(), "Alamofire".to_string()))
+        );
+        assert_eq!(
+            get_namespace_and_name("git@github.com:apple/swift-nio.git"),
+            Some(("github.com/apple".to_string(), "swift-nio".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_package_resolved_v1() {
+        let dependencies = get_dependencies("resources/swift/v1/Package.resolved");
+        assert_eq!(dependencies.len(), 2);
+        assert_eq!(dependencies[0].name, "Alamofire");
+        assert_eq!(
+            dependencies[0].purl,
+            "pkg:swift/github.com/Alamofire/Alamofire@5.8.1"
+        );
+        assert_eq!(
+            dependencies[0]
+                .location
+                .clone()
+                .unwrap()
+                .name
+                .start
+                .line
+                .get(),
+            5
+        );
+        assert_eq!(
+            dependencies[1].purl,
+            "pkg:swift/github.com/onevcat/Kingfisher@7.10.1"
+        );
+    }
+
+    #[test]
+    fn test_package_resolved_v2() {
+        let dependencies = get_dependencies("resources/swift/v2/Package.resolved");
+        assert_eq!(dependencies.len(), 2);
+        assert_eq!(
+            dependencies[0].purl,
+            "pkg:swift/github.com/apple/swift-log@1.5.4"
+        );
+        // pinned on a branch, no version
+        assert_eq!(
+            dependencies[1].purl,
+            "pkg:swift/github.com/apple/swift-nio@a1b2c3d4e5f60718293a4b5c6d7e8f9012345678"
+        );
+    }
+}
@@ -0,0 +1 @@
+pub mod terraform_lock_producer;
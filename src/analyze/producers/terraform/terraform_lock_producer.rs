@@ -0,0 +1,221 @@
+use std::path::{Path, PathBuf};
+
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::model::dependency::{
+    Dependency, DependencyBuilder, DependencyHash, DependencyLocation, DependencyType,
+    HashAlgorithm,
+};
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::location::Location;
+use crate::model::position::LineIndex;
+use crate::model::purl::get_purl_with_qualifiers;
+use derive_builder::Builder;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+const TERRAFORM_LOCK_FILE: &str = ".terraform.lock.hcl";
+const DEFAULT_REGISTRY: &str = "registry.terraform.io";
+
+lazy_static! {
+    /// A provider block, e.g. `provider "registry.terraform.io/hashicorp/aws" { ... }`.
+    /// The lock file is generated: blocks are never nested and end with `}` at the start
+    /// of a line.
+    static ref REGEX_PROVIDER: Regex =
+        Regex::new(r#"(?ms)^provider\s+"(?P<address>[^"]+)"\s*\{(?P<body>.*?)^\}"#).unwrap();
+    static ref REGEX_VERSION: Regex =
+        Regex::new(r#"(?m)^\s*version\s*=\s*"(?P<version>[^"]+)""#).unwrap();
+    /// The `zh:` hashes are the SHA-256 of the zip archive of the provider for each platform.
+    /// The `h1:` hashes are a hash of the content of the archive and cannot be expressed
+    /// in an SBOM.
+    static ref REGEX_ZIP_HASH: Regex = Regex::new(r#""zh:(?P<hash>[0-9a-fA-F]{64})""#).unwrap();
+}
+
+/// Producer for the providers pinned in the `.terraform.lock.hcl` file of Terraform and
+/// OpenTofu.
+#[derive(Clone, Builder)]
+pub struct TerraformLockProducer {}
+
+fn get_location(
+    file: &str,
+    line_index: &LineIndex,
+    start: usize,
+    end: usize,
+) -> anyhow::Result<Location> {
+    Ok(Location {
+        file: file.to_string(),
+        start: line_index.get_position(start)?,
+        end: line_index.get_position(end)?,
+    })
+}
+
+fn get_dependencies_from_content(path: &Path, content: &str) -> anyhow::Result<Vec<Dependency>> {
+    let mut result = vec![];
+    let path_string = path.display().to_string();
+    let line_index = LineIndex::new(content);
+
+    for captures in REGEX_PROVIDER.captures_iter(content) {
+        let (Some(block), Some(address), Some(body)) = (
+            captures.get(0),
+            captures.name("address"),
+            captures.name("body"),
+        ) else {
+            continue;
+        };
+
+        // the address is `hostname/namespace/type`
+        let [hostname, namespace, name] = address.as_str().split('/').collect::<Vec<&str>>()[..]
+        else {
+            continue;
+        };
+        let Some(version) = REGEX_VERSION
+            .captures(body.as_str())
+            .and_then(|c| c.name("version"))
+        else {
+            continue;
+        };
+
+        let hashes = REGEX_ZIP_HASH
+            .captures_iter(body.as_str())
+            .filter_map(|c| c.name("hash"))
+            .map(|h| DependencyHash {
+                algorithm: HashAlgorithm::Sha256,
+                content: h.as_str().to_lowercase(),
+            })
+            .collect::<Vec<DependencyHash>>();
+
+        let qualifiers: Vec<(&str, &str)> = Some(hostname)
+            .filter(|h| *h != DEFAULT_REGISTRY)
+            .map(|h| ("repository_url", h))
+            .into_iter()
+            .collect();
+
+        let version_start = body.start() + version.start();
+        let location = match (
+            get_location(&path_string, &line_index, block.start(), block.end() - 1),
+            get_location(&path_string, &line_index, address.start(), address.end()),
+        ) {
+            (Ok(block_location), Ok(name_location)) => Some(DependencyLocation {
+                block: block_location,
+                name: name_location,
+                version: get_location(
+                    &path_string,
+                    &line_index,
+                    version_start,
+                    version_start + version.len(),
+                )
+                .ok(),
+            }),
+            _ => None,
+        };
+
+        result.push(
+            DependencyBuilder::default()
+                .name(format!("{}/{}", namespace, name))
+                .version(Some(version.as_str().to_string()))
+                .purl(get_purl_with_qualifiers(
+                    "terraform",
+                    Some(namespace),
+                    name,
+                    Some(version.as_str()),
+                    qualifiers.as_slice(),
+                ))
+                .location(location)
+                .r#type(DependencyType::Application)
+                .hashes(hashes)
+                .build()?,
+        );
+    }
+
+    Ok(result)
+}
+
+impl SbomProducer for TerraformLockProducer {
+    fn name(&self) -> &str {
+        "terraform-lock"
+    }
+
+    fn ecosystem(&self) -> &str {
+        "terraform"
+    }
+
+    fn file_patterns(&self) -> Vec<&str> {
+        vec!["**/.terraform.lock.hcl"]
+    }
+
+    fn use_file(&self, path: &Path, _configuration: &SbomProducerConfiguration) -> bool {
+        match path.file_name() {
+            Some(e) => e == TERRAFORM_LOCK_FILE,
+            None => false,
+        }
+    }
+
+    fn ignored_directories(&self) -> Vec<&str> {
+        vec![".terraform"]
+    }
+
+    fn is_per_file(&self) -> bool {
+        true
+    }
+
+    fn find_dependencies(
+        &self,
+        paths: &[PathBuf],
+        configuration: &SbomProducerConfiguration,
+    ) -> anyhow::Result<SbomProducerResult> {
+        let mut result = SbomProducerResult::default();
+
+        for path in paths {
+            let dependencies = configuration
+                .vfs
+                .read_to_string(path)
+                .and_then(|content| get_dependencies_from_content(path, &content));
+            match dependencies {
+                Ok(deps) => result.dependencies.extend(deps),
+                Err(e) => result.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::ParseError,
+                    path,
+                    format!("cannot parse: {}", e),
+                )),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terraform_lock() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/terraform/.terraform.lock.hcl");
+        let configuration = SbomProducerConfiguration::new(d.clone(), false);
+        let dependencies = TerraformLockProducerBuilder::default()
+            .build()
+            .unwrap()
+            .find_dependencies(&[d], &configuration)
+            .unwrap()
+            .dependencies;
+
+        let purls: Vec<&str> = dependencies.iter().map(|d| d.purl.as_str()).collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:terraform/hashicorp/aws@5.31.0",
+                "pkg:terraform/integrations/github@6.0.0?repository_url=registry.opentofu.org",
+            ]
+        );
+        assert_eq!(dependencies[0].name, "hashicorp/aws");
+        assert_eq!(dependencies[0].hashes.len(), 2);
+        assert!(dependencies[1].hashes.is_empty());
+
+        let location = dependencies[0].location.clone().unwrap();
+        assert_eq!(location.block.start.line.get(), 4);
+        assert_eq!(location.block.end.line.get(), 12);
+        assert_eq!(location.version.unwrap().start.line.get(), 5);
+    }
+}
@@ -0,0 +1,49 @@
+use crate::analyze::producers::producer::SbomProducerResult;
+use crate::analyze::sbom_generate::{get_dependencies, print_diagnostics};
+use crate::model::configuration::Configuration;
+use crate::sbom::compare::{compare_components, render_reports, AccuracyReport};
+use crate::sbom::diff::{get_components, read_sbom};
+use crate::sbom::format::ReportFormat;
+use crate::sbom::generate::get_cyclonedx;
+use std::fs;
+
+/// Scan the directory and compare the components found to each reference SBOM (e.g.
+/// generated by another tool). The report is written to the output of the configuration,
+/// or to stdout when there is no output.
+pub fn compare(
+    configuration: &Configuration,
+    references: &[String],
+    format: ReportFormat,
+) -> anyhow::Result<()> {
+    if configuration.use_debug {
+        configuration.print_configuration();
+    }
+
+    let SbomProducerResult {
+        dependencies,
+        diagnostics,
+    } = get_dependencies(configuration)?;
+    print_diagnostics(configuration, diagnostics.as_slice());
+    let found = get_components(&get_cyclonedx(dependencies, configuration)?);
+
+    let reports = references
+        .iter()
+        .map(|reference| {
+            let expected = get_components(&read_sbom(reference)?);
+            Ok(compare_components(
+                reference,
+                found.as_slice(),
+                expected.as_slice(),
+                diagnostics.as_slice(),
+            ))
+        })
+        .collect::<anyhow::Result<Vec<AccuracyReport>>>()?;
+    let content = render_reports(reports.as_slice(), format)?;
+
+    if configuration.output.is_empty() {
+        println!("{}", content);
+    } else {
+        fs::write(configuration.output.as_str(), content)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use crate::analyze::sbom_generate::{get_dependencies, print_diagnostics};
+use crate::model::configuration::Configuration;
+use crate::sbom::diff::{diff_components, get_components, read_sbom};
+use crate::sbom::format::ReportFormat;
+use crate::sbom::generate::get_cyclonedx;
+use serde_cyclonedx::cyclonedx::v_1_6::CycloneDx;
+use std::fs;
+use std::path::Path;
+
+/// Get the SBOM to compare: the SBOM file if [source] is a file, otherwise the SBOM of
+/// the repository at the revision [source] (a commit, branch or tag).
+fn get_sbom(configuration: &Configuration, source: &str) -> anyhow::Result<CycloneDx> {
+    if Path::new(source).is_file() {
+        return read_sbom(source);
+    }
+
+    let configuration = Configuration {
+        git_ref: Some(source.to_string()),
+        ..configuration.clone()
+    };
+    let result = get_dependencies(&configuration)?;
+    print_diagnostics(&configuration, result.diagnostics.as_slice());
+    get_cyclonedx(result.dependencies, &configuration)
+}
+
+/// Compare two SBOMs and write the added, removed and changed components to the output
+/// of the configuration, or to stdout when there is no output.
+pub fn diff(
+    configuration: &Configuration,
+    before: &str,
+    after: &str,
+    format: ReportFormat,
+) -> anyhow::Result<()> {
+    if configuration.use_debug {
+        configuration.print_configuration();
+    }
+
+    let before_components = get_components(&get_sbom(configuration, before)?);
+    let after_components = get_components(&get_sbom(configuration, after)?);
+    let diff = diff_components(before_components.as_slice(), after_components.as_slice());
+    let content = diff.render(format)?;
+
+    if configuration.output.is_empty() {
+        println!("{}", content);
+    } else {
+        fs::write(configuration.output.as_str(), content)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::analyze::producers::maven::effective_pom::{
+    render_effective_poms, EffectivePom, EffectivePomFormat,
+};
+use crate::analyze::producers::maven::maven_producer::MavenProducer;
+use crate::analyze::producers::producer::SbomProducer;
+use crate::analyze::sbom_generate::{
+    get_producer_configuration, get_thread_pool, print_diagnostics,
+};
+use crate::model::configuration::Configuration;
+use std::fs;
+
+/// Write the effective model of each `pom.xml` of the directory to the output of the
+/// configuration, or to stdout when there is no output.
+pub fn effective_pom(
+    configuration: &Configuration,
+    format: EffectivePomFormat,
+) -> anyhow::Result<()> {
+    let producer_configuration = get_producer_configuration(configuration)?;
+    let producer = MavenProducer {
+        max_parent_depth: configuration.max_parent_depth,
+    };
+    let files = producer.select_files(
+        producer_configuration.vfs.get_files()?.as_slice(),
+        &producer_configuration,
+    );
+
+    let mut diagnostics = vec![];
+    let context = get_thread_pool(configuration)?
+        .install(|| producer.get_context(&files, &producer_configuration, &mut diagnostics));
+    print_diagnostics(configuration, diagnostics.as_slice());
+
+    let poms: Vec<EffectivePom> = context
+        .get_all_files()
+        .iter()
+        .map(|f| f.get_effective_pom(&context))
+        .collect();
+    let content = render_effective_poms(poms.as_slice(), format)?;
+
+    if configuration.output.is_empty() {
+        print!("{}", content);
+    } else {
+        fs::write(configuration.output.as_str(), content)?;
+    }
+    Ok(())
+}
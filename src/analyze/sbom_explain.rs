@@ -0,0 +1,47 @@
+use crate::analyze::producers::maven::maven_producer::MavenProducer;
+use crate::analyze::producers::producer::SbomProducer;
+use crate::analyze::sbom_generate::{
+    get_producer_configuration, get_thread_pool, print_diagnostics,
+};
+use crate::model::configuration::Configuration;
+
+/// Print how the version of a Maven dependency (`groupId:artifactId`) is resolved in
+/// each `pom.xml` that declares it. Return false if no file declares the dependency.
+pub fn explain(configuration: &Configuration, dependency: &str) -> anyhow::Result<bool> {
+    let Some((group_id, artifact_id)) = dependency.split_once(':') else {
+        anyhow::bail!(
+            "invalid dependency {}, expected groupId:artifactId",
+            dependency
+        );
+    };
+
+    let producer_configuration = get_producer_configuration(configuration)?;
+    let producer = MavenProducer {
+        max_parent_depth: configuration.max_parent_depth,
+    };
+    let files = producer.select_files(
+        producer_configuration.vfs.get_files()?.as_slice(),
+        &producer_configuration,
+    );
+
+    let mut diagnostics = vec![];
+    let context = get_thread_pool(configuration)?
+        .install(|| producer.get_context(&files, &producer_configuration, &mut diagnostics));
+    print_diagnostics(configuration, diagnostics.as_slice());
+
+    let mut found = false;
+    for maven_file in context.get_all_files() {
+        for resolution in maven_file.explain_dependency(&context, group_id, artifact_id) {
+            if found {
+                println!();
+            }
+            println!("{}", resolution);
+            found = true;
+        }
+    }
+
+    if !found {
+        eprintln!("{} is not declared in any pom.xml", dependency);
+    }
+    Ok(found)
+}
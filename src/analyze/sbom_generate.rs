@@ -1,45 +1,307 @@
-use crate::analyze::producers::maven::maven_producer::MavenProducerBuilder;
-use crate::analyze::producers::producer::{SbomProducer, SbomProducerConfiguration};
+use crate::analyze::producers::producer::{
+    SbomProducer, SbomProducerConfiguration, SbomProducerResult,
+};
+use crate::analyze::producers::registry::ProducerRegistry;
 use crate::model::configuration::Configuration;
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind, DiagnosticSeverity};
+use crate::sbom::diagnostics::write_diagnostics;
 use crate::sbom::generate::generate_sbom;
-use crate::utils::file_utils::get_files;
-use std::path::PathBuf;
+use crate::utils::cache::Cache;
+use crate::utils::file_utils::{get_files, FileFilter};
+use crate::utils::tree_sitter::tree::TreeCache;
+use crate::utils::vfs::get_vfs;
+use rayon::prelude::*;
+use std::any::Any;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-/// Analyze paths, find dependencies and write the SBOM to disk.
-/// The [configuration] is the configuration of the tool (directory to scan, etc)
-pub fn analyze(configuration: &Configuration) -> anyhow::Result<()> {
-    let mut dependencies = vec![];
-    if configuration.use_debug {
-        configuration.print_configuration();
+/// Get the filter of the files to scan from the options.
+fn get_file_filter(configuration: &Configuration) -> anyhow::Result<FileFilter> {
+    let files = &configuration.files;
+    let mut filter = FileFilter::new(files.includes.as_slice(), files.excludes.as_slice())?;
+    filter.use_ignore_files = files.use_ignore_files;
+    filter.max_depth = files.max_depth;
+    filter.max_file_size = files.max_file_size;
+    Ok(filter)
+}
+
+/// Get the configuration of the producers, with the filesystem of the directories or the
+/// archives to scan.
+pub fn get_producer_configuration(
+    configuration: &Configuration,
+) -> anyhow::Result<SbomProducerConfiguration> {
+    let vfs = get_vfs(
+        configuration.directories.as_slice(),
+        configuration.git_ref.as_deref(),
+        configuration.allowed_roots.as_slice(),
+        get_file_filter(configuration)?,
+    )?;
+    Ok(SbomProducerConfiguration {
+        base_path: vfs.root().to_path_buf(),
+        use_debug: configuration.use_debug,
+        vfs,
+        cache: get_cache(configuration)?.map(Arc::new),
+        trees: None,
+    })
+}
+
+/// Get the cache with `--cache`. Its entries are only used with the same options that change
+/// the results of the producers, and the same custom producers and plugins.
+fn get_cache(configuration: &Configuration) -> anyhow::Result<Option<Cache>> {
+    let Some(directory) = &configuration.cache else {
+        return Ok(None);
+    };
+    let mut parts: Vec<Vec<u8>> = vec![
+        configuration.max_parent_depth.to_string().into_bytes(),
+        configuration.allowed_roots.join("\n").into_bytes(),
+    ];
+    if let Some(config) = &configuration.config {
+        parts.push(fs::read(config)?);
+    }
+    if let Some(plugins) = &configuration.plugins {
+        for plugin in get_files(plugins)? {
+            parts.push(plugin.display().to_string().into_bytes());
+            parts.push(fs::read(&plugin)?);
+        }
+    }
+    let parts: Vec<&[u8]> = parts.iter().map(Vec::as_slice).collect();
+    Ok(Some(Cache::new(Path::new(directory), parts.as_slice())))
+}
+
+/// Get the producers selected by the configuration.
+pub fn get_registry(configuration: &Configuration) -> anyhow::Result<ProducerRegistry> {
+    ProducerRegistry::new(configuration)?.select(
+        configuration.producers.as_slice(),
+        configuration.exclude_producers.as_slice(),
+    )
+}
+
+/// Print the producers with their ecosystem and the files they read, one per line.
+pub fn list_producers(configuration: &Configuration) -> anyhow::Result<()> {
+    for producer in get_registry(configuration)?.producers() {
+        println!(
+            "{:<20} {:<12} {}",
+            producer.name(),
+            producer.ecosystem(),
+            producer.file_patterns().join(" ")
+        );
     }
+    Ok(())
+}
+
+/// Print the ecosystems and the manifest files found in the directory, without parsing
+/// them or generating the SBOM.
+pub fn detect(configuration: &Configuration) -> anyhow::Result<()> {
+    let registry = get_registry(configuration)?;
+    let producer_configuration = get_producer_configuration(configuration)?;
+    let all_files = producer_configuration.vfs.get_files()?;
 
-    let all_producers = vec![MavenProducerBuilder::default()
-        .build()
-        .expect("build producer")];
+    for producer in registry.producers() {
+        let files = producer.select_files(all_files.as_slice(), &producer_configuration);
+        if files.is_empty() {
+            continue;
+        }
+
+        println!(
+            "{} ({}): {} file(s)",
+            producer.ecosystem(),
+            producer.name(),
+            files.len()
+        );
+        for file in &files {
+            println!("  {}", configuration.get_relative_path(file).display());
+        }
+    }
+    Ok(())
+}
+
+fn get_panic_message(payload: &Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown error".to_string()
+    }
+}
+
+/// Get the thread pool of the scan, with `--jobs` threads (all the CPUs by default).
+pub fn get_thread_pool(configuration: &Configuration) -> anyhow::Result<rayon::ThreadPool> {
+    Ok(rayon::ThreadPoolBuilder::new()
+        .num_threads(configuration.jobs.unwrap_or(0))
+        .build()?)
+}
+
+/// Find the dependencies of the files of a producer that reads each file independently,
+/// the files that did not change since the previous scan are read from the [cache].
+fn find_dependencies_with_cache(
+    sbom_producer: &dyn SbomProducer,
+    files: &[PathBuf],
+    producer_configuration: &SbomProducerConfiguration,
+    cache: &Cache,
+) -> anyhow::Result<SbomProducerResult> {
+    let results = files
+        .par_iter()
+        .map(|file| {
+            // a file that cannot be read is reported by the producer
+            let key = producer_configuration.vfs.read(file).ok().map(|content| {
+                cache.get_key(
+                    sbom_producer.name(),
+                    &[file.as_os_str().as_encoded_bytes(), content.as_slice()],
+                )
+            });
+            if let Some(result) = key.as_ref().and_then(|k| cache.get(k)) {
+                return Ok(result);
+            }
+            let result = sbom_producer
+                .find_dependencies(std::slice::from_ref(file), producer_configuration)?;
+            if let Some(k) = key {
+                cache.put(&k, &result);
+            }
+            Ok(result)
+        })
+        .collect::<anyhow::Result<Vec<SbomProducerResult>>>()?;
+
+    let mut result = SbomProducerResult::default();
+    for r in results {
+        result.dependencies.extend(r.dependencies);
+        result.diagnostics.extend(r.diagnostics);
+    }
+    Ok(result)
+}
+
+/// Run a producer on its files, a producer that fails (or panics) is reported as a
+/// diagnostic.
+fn run_producer(
+    sbom_producer: &dyn SbomProducer,
+    all_files: &[PathBuf],
+    producer_configuration: &SbomProducerConfiguration,
+) -> SbomProducerResult {
+    let producer_files = sbom_producer.select_files(all_files, producer_configuration);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| match &producer_configuration.cache {
+        Some(cache) if sbom_producer.is_per_file() => find_dependencies_with_cache(
+            sbom_producer,
+            producer_files.as_slice(),
+            producer_configuration,
+            cache,
+        ),
+        _ => sbom_producer.find_dependencies(producer_files.as_slice(), producer_configuration),
+    }))
+    .unwrap_or_else(|e| {
+        Err(anyhow::anyhow!(
+            "producer panicked: {}",
+            get_panic_message(&e)
+        ))
+    });
+
+    let result = result.unwrap_or_else(|e| SbomProducerResult {
+        dependencies: vec![],
+        diagnostics: vec![Diagnostic::error(
+            DiagnosticKind::ProducerError,
+            producer_configuration.vfs.root(),
+            e.to_string(),
+        )],
+    });
+    SbomProducerResult {
+        dependencies: result.dependencies,
+        diagnostics: result
+            .diagnostics
+            .into_iter()
+            .map(|d| Diagnostic {
+                producer: Some(sbom_producer.name().to_string()),
+                ..d
+            })
+            .collect(),
+    }
+}
+
+/// Run the selected producers on the directory and get the dependencies with the
+/// diagnostics of all the producers. A producer that fails is reported as a diagnostic.
+pub fn get_dependencies(configuration: &Configuration) -> anyhow::Result<SbomProducerResult> {
+    get_dependencies_with_trees(configuration, None)
+}
+
+/// Run the producers as [get_dependencies], the files are parsed from their previous tree
+/// in [trees] when it is set.
+fn get_dependencies_with_trees(
+    configuration: &Configuration,
+    trees: Option<Arc<TreeCache>>,
+) -> anyhow::Result<SbomProducerResult> {
+    let mut dependencies = vec![];
+    let mut diagnostics = vec![];
+
+    let all_producers = get_registry(configuration)?.into_producers();
 
-    let all_files = get_files(configuration.directory.as_str()).expect("cannot read directory");
     let producer_configuration = SbomProducerConfiguration {
-        base_path: PathBuf::from(configuration.directory.clone()),
-        use_debug: configuration.use_debug,
+        trees,
+        ..get_producer_configuration(configuration)?
     };
+    let all_files = producer_configuration.vfs.get_files()?;
 
-    for sbom_producer in all_producers {
-        let producer_files = all_files
-            .clone()
-            .iter()
-            .filter(|f| sbom_producer.use_file(f, &producer_configuration))
-            .map(|v| (*v).clone())
-            .collect::<Vec<PathBuf>>();
+    // the producers run on the thread pool, their results are merged in the order of the
+    // producers so that the SBOM does not depend on the scheduling
+    let results: Vec<SbomProducerResult> = get_thread_pool(configuration)?.install(|| {
+        all_producers
+            .par_iter()
+            .map(|p| run_producer(p.as_ref(), all_files.as_slice(), &producer_configuration))
+            .collect()
+    });
+    for result in results {
+        dependencies.extend(result.dependencies);
+        diagnostics.extend(result.diagnostics);
+    }
+    if let (true, Some(cache)) = (configuration.use_debug, &producer_configuration.cache) {
+        let (hits, misses) = cache.get_statistics();
+        println!("Cache: {} entries found, {} not found", hits, misses);
+    }
 
-        let dependencies_found =
-            sbom_producer.find_dependencies(producer_files.as_slice(), &producer_configuration);
+    Ok(SbomProducerResult {
+        dependencies,
+        diagnostics,
+    })
+}
 
-        if let Ok(deps) = dependencies_found {
-            dependencies.extend(deps);
+/// Print the diagnostics on stderr (warnings and errors only, unless in debug mode).
+pub fn print_diagnostics(configuration: &Configuration, diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics.iter() {
+        if configuration.use_debug || diagnostic.severity >= DiagnosticSeverity::Warning {
+            eprintln!("{}", diagnostic);
         }
     }
+}
+
+/// Analyze paths, find dependencies and write the SBOM to disk.
+/// The [configuration] is the configuration of the tool (directory to scan, etc)
+/// The SBOM is written even if some files cannot be analyzed. Returns false if the
+/// configuration is strict and a producer reports a warning or an error.
+pub fn analyze(configuration: &Configuration) -> anyhow::Result<bool> {
+    analyze_with_trees(configuration, None)
+}
+
+/// Write the SBOM as [analyze], the files are parsed from their previous tree in [trees]
+/// when it is set (`watch`).
+pub fn analyze_with_trees(
+    configuration: &Configuration,
+    trees: Option<Arc<TreeCache>>,
+) -> anyhow::Result<bool> {
+    if configuration.use_debug {
+        configuration.print_configuration();
+    }
+
+    let SbomProducerResult {
+        dependencies,
+        diagnostics,
+    } = get_dependencies_with_trees(configuration, trees)?;
+
+    print_diagnostics(configuration, diagnostics.as_slice());
+    if let Some(path) = &configuration.diagnostics {
+        write_diagnostics(diagnostics.as_slice(), path)?;
+    }
 
-    for dep in dependencies.iter() {
+    for dep in dependencies.iter().filter(|_| configuration.use_debug) {
         let dep_file = dep
             .location
             .as_ref()
@@ -54,6 +316,15 @@ pub fn analyze(configuration: &Configuration) -> anyhow::Result<()> {
             dep_line
         )
     }
-    generate_sbom(dependencies, configuration).expect("cannot generate SBOM");
-    Ok(())
+    generate_sbom(dependencies, configuration)?;
+
+    let failures = diagnostics
+        .iter()
+        .filter(|d| d.severity >= DiagnosticSeverity::Warning)
+        .count();
+    if configuration.strict && failures > 0 {
+        eprintln!("{} warnings or errors reported (strict mode)", failures);
+        return Ok(false);
+    }
+    Ok(true)
 }
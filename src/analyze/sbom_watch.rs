@@ -0,0 +1,440 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::{self, DirBuilder};
+use std::io::ErrorKind;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask};
+
+use crate::analyze::producers::registry::ProducerRegistry;
+use crate::analyze::sbom_generate::{analyze_with_trees, get_producer_configuration, get_registry};
+use crate::model::configuration::Configuration;
+use crate::utils::tree_sitter::tree::TreeCache;
+
+/// Time to wait for the other events of a change: an editor may write a file in several
+/// steps, or several files are changed together (e.g. `git checkout`).
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(100);
+
+/// Time between two reads of the events when the wait has a deadline.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The manifests read by the producers and the directories to watch.
+struct WatchedFiles {
+    /// The files selected by at least one producer
+    manifests: HashSet<PathBuf>,
+    /// The scanned directories and the directories of all the scanned files, so that a
+    /// new manifest is found too
+    directories: BTreeSet<PathBuf>,
+}
+
+fn get_watched_files(
+    configuration: &Configuration,
+    registry: &ProducerRegistry,
+) -> anyhow::Result<WatchedFiles> {
+    let producer_configuration = get_producer_configuration(configuration)?;
+    let all_files = producer_configuration.vfs.get_files()?;
+
+    let manifests = registry
+        .producers()
+        .iter()
+        .flat_map(|p| p.select_files(all_files.as_slice(), &producer_configuration))
+        .collect();
+    let directories = configuration
+        .directories
+        .iter()
+        .map(PathBuf::from)
+        .chain(
+            all_files
+                .iter()
+                .filter_map(|f| f.parent())
+                .map(Path::to_path_buf),
+        )
+        .collect();
+    Ok(WatchedFiles {
+        manifests,
+        directories,
+    })
+}
+
+/// Watch the directories of the scan with inotify.
+struct Watcher {
+    inotify: Inotify,
+    /// The watched directories by watch descriptor
+    directories: HashMap<WatchDescriptor, PathBuf>,
+    /// The manifests of the last scan
+    manifests: HashSet<PathBuf>,
+    /// The trees of the manifests, an edited manifest is parsed from its previous tree
+    trees: Arc<TreeCache>,
+}
+
+impl Watcher {
+    fn new() -> anyhow::Result<Self> {
+        Ok(Watcher {
+            inotify: Inotify::init()?,
+            directories: HashMap::new(),
+            manifests: HashSet::new(),
+            trees: Arc::default(),
+        })
+    }
+
+    /// Watch the new directories of [watched]. The directories that were removed are
+    /// forgotten when their removal is read, the trees of the files that are not manifests
+    /// anymore (deleted, renamed or not selected) are dropped.
+    fn watch(&mut self, watched: WatchedFiles) -> anyhow::Result<()> {
+        let known: HashSet<&PathBuf> = self.directories.values().collect();
+        let new_directories: Vec<PathBuf> = watched
+            .directories
+            .into_iter()
+            .filter(|d| !known.contains(d))
+            .collect();
+        for directory in new_directories {
+            self.add_directory(directory)?;
+        }
+        self.manifests = watched.manifests;
+        self.trees.retain(&self.manifests);
+        Ok(())
+    }
+
+    fn add_directory(&mut self, directory: PathBuf) -> anyhow::Result<()> {
+        let mask = WatchMask::CLOSE_WRITE
+            | WatchMask::CREATE
+            | WatchMask::DELETE
+            | WatchMask::MOVED_FROM
+            | WatchMask::MOVED_TO;
+        match self.inotify.watches().add(&directory, mask) {
+            Ok(descriptor) => {
+                self.directories.insert(descriptor, directory);
+                Ok(())
+            }
+            // removed since the scan, the next scan does not list it anymore
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(anyhow::anyhow!(
+                "cannot watch {}: {}",
+                directory.display(),
+                e
+            )),
+        }
+    }
+
+    /// Watch a created directory and its subdirectories: they have no scanned files yet,
+    /// but a manifest may be written in them later. The subdirectories may have been
+    /// created before the directory is watched (e.g. `mkdir -p`).
+    fn add_created_directory(&mut self, directory: PathBuf) -> anyhow::Result<()> {
+        let subdirectories: Vec<PathBuf> = match fs::read_dir(&directory) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                .map(|e| e.path())
+                .collect(),
+            Err(_) => vec![],
+        };
+        self.add_directory(directory)?;
+        for subdirectory in subdirectories {
+            self.add_created_directory(subdirectory)?;
+        }
+        Ok(())
+    }
+
+    /// Read the available events, get the paths that changed. Return true if some events
+    /// were lost, any file may have changed.
+    fn read_events(
+        &mut self,
+        blocking: bool,
+        changed: &mut BTreeSet<PathBuf>,
+    ) -> anyhow::Result<bool> {
+        let mut buffer = [0u8; 4096];
+        let events = if blocking {
+            self.inotify.read_events_blocking(&mut buffer)
+        } else {
+            self.inotify.read_events(&mut buffer)
+        };
+        let events = match events {
+            Ok(events) => events,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut overflow = false;
+        let mut created_directories = vec![];
+        for event in events {
+            if event.mask.contains(EventMask::Q_OVERFLOW) {
+                overflow = true;
+            } else if event.mask.contains(EventMask::IGNORED) {
+                self.directories.remove(&event.wd);
+            } else if let (Some(directory), Some(name)) =
+                (self.directories.get(&event.wd), event.name)
+            {
+                let path = directory.join(name);
+                if event.mask.contains(EventMask::ISDIR)
+                    && event
+                        .mask
+                        .intersects(EventMask::CREATE | EventMask::MOVED_TO)
+                {
+                    created_directories.push(path.clone());
+                }
+                changed.insert(path);
+            }
+        }
+        for directory in created_directories {
+            self.add_created_directory(directory)?;
+        }
+        Ok(overflow)
+    }
+
+    /// Wait until a file changes, then until no file changed for [DEBOUNCE_DELAY]. Get
+    /// the paths that changed, all the manifests if some events were lost. Without
+    /// [timeout] the first change is awaited forever, otherwise an error is returned when
+    /// no file changed before it expires.
+    fn wait_for_changes(&mut self, timeout: Option<Duration>) -> anyhow::Result<BTreeSet<PathBuf>> {
+        let mut changed = BTreeSet::new();
+        let mut overflow = match timeout {
+            None => self.read_events(true, &mut changed)?,
+            Some(timeout) => self.poll_events(Instant::now() + timeout, &mut changed)?,
+        };
+        loop {
+            thread::sleep(DEBOUNCE_DELAY);
+            let count = changed.len();
+            overflow |= self.read_events(false, &mut changed)?;
+            if changed.len() == count {
+                break;
+            }
+        }
+        if overflow {
+            changed.extend(self.manifests.iter().cloned());
+        }
+        Ok(changed)
+    }
+
+    /// Read the events without blocking until some are found, at most until [deadline].
+    fn poll_events(
+        &mut self,
+        deadline: Instant,
+        changed: &mut BTreeSet<PathBuf>,
+    ) -> anyhow::Result<bool> {
+        loop {
+            let overflow = self.read_events(false, changed)?;
+            if overflow || !changed.is_empty() {
+                return Ok(overflow);
+            }
+            if Instant::now() >= deadline {
+                anyhow::bail!("no file changed before the deadline");
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Get the manifests that changed: the [changed] paths that are manifests of the last scan
+/// ([previous], edited or deleted) or of the new scan ([current], created), and the
+/// manifests that were added or removed by the new scan without an event, e.g. a manifest
+/// created in a directory that was not watched yet.
+fn get_changed_manifests(
+    previous: &HashSet<PathBuf>,
+    changed: &BTreeSet<PathBuf>,
+    current: &HashSet<PathBuf>,
+) -> Vec<PathBuf> {
+    let mut manifests: BTreeSet<&PathBuf> = changed
+        .iter()
+        .filter(|p| previous.contains(*p) || current.contains(*p))
+        .collect();
+    manifests.extend(previous.symmetric_difference(current));
+    manifests.into_iter().cloned().collect()
+}
+
+/// Get the directory of the cache of `watch` when there is no `--cache`: a directory of
+/// the user (`$XDG_CACHE_HOME` or `~/.cache`), readable by the user only so that another
+/// user cannot write entries read by the scan.
+fn get_default_cache_directory() -> anyhow::Result<PathBuf> {
+    let directory = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+        .ok_or_else(|| anyhow::anyhow!("no cache directory for watch, use --cache"))?
+        .join("sbom-generator");
+    DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&directory)
+        .map_err(|e| anyhow::anyhow!("cannot create {}: {}", directory.display(), e))?;
+    Ok(directory)
+}
+
+/// Get the configuration of `watch`: the results of the files that did not change are
+/// read from the cache, the `--cache` directory or a cache directory of the user.
+fn get_watch_configuration(configuration: &Configuration) -> anyhow::Result<Configuration> {
+    let cache = match &configuration.cache {
+        Some(cache) => cache.clone(),
+        None => get_default_cache_directory()?.display().to_string(),
+    };
+    Ok(Configuration {
+        cache: Some(cache),
+        ..configuration.clone()
+    })
+}
+
+/// Generate the SBOM, an error is printed and the next change generates it again.
+fn generate(configuration: &Configuration, trees: &Arc<TreeCache>) {
+    let start = Instant::now();
+    match analyze_with_trees(configuration, Some(trees.clone())) {
+        Ok(_) => eprintln!(
+            "SBOM written to {} in {} ms",
+            configuration.output,
+            start.elapsed().as_millis()
+        ),
+        Err(e) => eprintln!("error when generating SBOM: {:#}", e),
+    }
+}
+
+/// Generate the SBOM, then generate it again each time a manifest of a producer is
+/// edited, created or deleted, until the process is stopped. Only the changed files are
+/// parsed again: the other files are read from the cache, and an edited file is parsed
+/// from its previous tree.
+///
+/// The registry is loaded once, a change of the custom producers or of the plugins needs
+/// a restart.
+pub fn watch(configuration: &Configuration) -> anyhow::Result<()> {
+    if configuration.git_ref.is_some() {
+        anyhow::bail!("cannot watch a git reference, only the files on the disk");
+    }
+    if let Some(path) = configuration
+        .directories
+        .iter()
+        .find(|d| !Path::new(d).is_dir())
+    {
+        anyhow::bail!("cannot watch {}, only directories can be watched", path);
+    }
+
+    let configuration = get_watch_configuration(configuration)?;
+    let registry = get_registry(&configuration)?;
+
+    let mut watcher = Watcher::new()?;
+    let watched = get_watched_files(&configuration, &registry)?;
+    eprintln!(
+        "Watching {} manifests in {} directories",
+        watched.manifests.len(),
+        watched.directories.len()
+    );
+    watcher.watch(watched)?;
+    generate(&configuration, &watcher.trees);
+
+    loop {
+        let changed = watcher.wait_for_changes(None)?;
+        let watched = get_watched_files(&configuration, &registry)?;
+        let manifests = get_changed_manifests(&watcher.manifests, &changed, &watched.manifests);
+        watcher.watch(watched)?;
+
+        if manifests.is_empty() {
+            continue;
+        }
+        for manifest in &manifests {
+            eprintln!(
+                "{} changed",
+                configuration.get_relative_path(manifest).display()
+            );
+        }
+        generate(&configuration, &watcher.trees);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The events of the files written by the test are expected before this delay, the
+    /// test fails instead of waiting forever (e.g. on a filesystem without inotify).
+    const TEST_TIMEOUT: Option<Duration> = Some(Duration::from_secs(10));
+
+    fn get_paths(paths: &[&str]) -> HashSet<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn test_get_changed_manifests() {
+        let previous = get_paths(&["a/pom.xml", "b/pom.xml", "c/pom.xml"]);
+        let current = get_paths(&["a/pom.xml", "c/pom.xml", "d/pom.xml", "e/pom.xml"]);
+        let changed: BTreeSet<PathBuf> = ["a/pom.xml", "a/README.md", "b/pom.xml", "d/pom.xml"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        // edited (a), deleted (b), created with an event (d) or found by the scan only (e)
+        assert_eq!(
+            get_changed_manifests(&previous, &changed, &current),
+            ["a/pom.xml", "b/pom.xml", "d/pom.xml", "e/pom.xml"]
+                .iter()
+                .map(PathBuf::from)
+                .collect::<Vec<PathBuf>>()
+        );
+        assert!(get_changed_manifests(&previous, &BTreeSet::new(), &previous).is_empty());
+    }
+
+    #[test]
+    fn test_watch_changes() {
+        let temp_directory = tempfile::tempdir().unwrap();
+        let directory = temp_directory.path();
+        let module = directory.join("module");
+        fs::create_dir_all(&module).unwrap();
+        fs::write(directory.join("pom.xml"), "<project>\n</project>\n").unwrap();
+        fs::write(directory.join("README.md"), "# Project\n").unwrap();
+
+        let configuration = Configuration {
+            directories: vec![directory.display().to_string()],
+            producers: vec!["maven".to_string()],
+            ..Configuration::default()
+        };
+        let registry = get_registry(&configuration).unwrap();
+        let mut watcher = Watcher::new().unwrap();
+        watcher
+            .watch(get_watched_files(&configuration, &registry).unwrap())
+            .unwrap();
+
+        // an edited manifest, another file and a manifest created in a directory without
+        // scanned files: it is not watched yet, the manifest is found by the next scan
+        fs::write(directory.join("pom.xml"), "<project/>\n").unwrap();
+        fs::write(module.join("pom.xml"), "<project/>\n").unwrap();
+        fs::write(directory.join("README.md"), "# Project 2\n").unwrap();
+
+        let changed = watcher.wait_for_changes(TEST_TIMEOUT).unwrap();
+        assert!(changed.contains(&directory.join("README.md")));
+        let watched = get_watched_files(&configuration, &registry).unwrap();
+        let manifests = get_changed_manifests(&watcher.manifests, &changed, &watched.manifests);
+        assert_eq!(
+            manifests,
+            vec![module.join("pom.xml"), directory.join("pom.xml")]
+        );
+        watcher.watch(watched).unwrap();
+
+        // the new directory is watched too
+        fs::write(module.join("pom.xml"), "<project>\n</project>\n").unwrap();
+        fs::remove_file(directory.join("pom.xml")).unwrap();
+        let changed = watcher.wait_for_changes(TEST_TIMEOUT).unwrap();
+        let watched = get_watched_files(&configuration, &registry).unwrap();
+        assert_eq!(
+            get_changed_manifests(&watcher.manifests, &changed, &watched.manifests),
+            vec![module.join("pom.xml"), directory.join("pom.xml")]
+        );
+        watcher.watch(watched).unwrap();
+
+        // a created directory is watched before a file is written in it
+        let other = directory.join("other");
+        fs::create_dir(&other).unwrap();
+        let changed = watcher.wait_for_changes(TEST_TIMEOUT).unwrap();
+        assert!(changed.contains(&other));
+        fs::write(other.join("pom.xml"), "<project/>\n").unwrap();
+        let changed = watcher.wait_for_changes(TEST_TIMEOUT).unwrap();
+        assert!(changed.contains(&other.join("pom.xml")));
+        let watched = get_watched_files(&configuration, &registry).unwrap();
+        assert_eq!(
+            get_changed_manifests(&watcher.manifests, &changed, &watched.manifests),
+            vec![other.join("pom.xml")]
+        );
+
+        // no file changed
+        assert!(watcher
+            .wait_for_changes(Some(Duration::from_millis(50)))
+            .is_err());
+    }
+}
@@ -1,25 +1,499 @@
 use std::env;
 use std::process::exit;
 
-use getopts::Options;
-use sbom_generator::analyze::sbom_generate::analyze;
-use sbom_generator::model::configuration::Configuration;
+use getopts::{Matches, Options};
+use sbom_generator::analyze::producers::maven::context::DEFAULT_MAX_PARENT_DEPTH;
+use sbom_generator::analyze::producers::maven::effective_pom::EffectivePomFormat;
+use sbom_generator::analyze::sbom_compare::compare;
+use sbom_generator::analyze::sbom_diff::diff;
+use sbom_generator::analyze::sbom_effective_pom::effective_pom;
+use sbom_generator::analyze::sbom_explain::explain;
+use sbom_generator::analyze::sbom_generate::{analyze, detect, list_producers};
+#[cfg(target_os = "linux")]
+use sbom_generator::analyze::sbom_watch::watch;
+use sbom_generator::model::configuration::{Configuration, FileSelection};
+use sbom_generator::sbom::format::ReportFormat;
+
+/// Split a comma-separated list of producers, e.g. `maven,npm`.
+fn get_producer_names(value: Option<String>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
 
 fn print_usage(program: &str, opts: Options) {
     let brief = format!("Usage: {} FILE [options]", program);
     print!("{}", opts.usage(&brief));
 }
 
+/// Options of the files to scan and of the references between the files.
+fn add_scan_options(opts: &mut Options) {
+    opts.optmulti(
+        "",
+        "include",
+        "glob of the files to scan, relative to the directory, can be repeated (default: all)",
+        "**/pom.xml",
+    );
+    opts.optmulti(
+        "",
+        "exclude",
+        "glob of the files or directories to skip, relative to the directory, can be repeated",
+        "**/test/**",
+    );
+    opts.optflag(
+        "",
+        "gitignore",
+        "skip the files ignored by the .gitignore and .ignore files",
+    );
+    opts.optopt(
+        "",
+        "max-depth",
+        "maximum depth of the files, 1 for the files of the directory only",
+        "5",
+    );
+    opts.optopt(
+        "",
+        "max-file-size",
+        "skip the files larger than this size, in bytes",
+        "10000000",
+    );
+    opts.optopt(
+        "",
+        "max-parent-depth",
+        "maximum number of parents followed from a pom.xml (default: 32)",
+        "32",
+    );
+    opts.optmulti(
+        "",
+        "allow-external-root",
+        "directory outside of the scanned directory that the files can reference (e.g. with a relativePath), can be repeated",
+        "/path/to/parent",
+    );
+    opts.optopt(
+        "",
+        "jobs",
+        "number of threads that parse the files (default: number of CPUs)",
+        "4",
+    );
+    opts.optopt(
+        "",
+        "cache",
+        "directory to cache the parsed files between two scans, only the changed files are parsed again",
+        "/path/to/cache",
+    );
+}
+
+/// Options of the subcommands to select and configure the producers.
+fn add_producer_options(opts: &mut Options) {
+    opts.optflag("d", "debug", "use debug mode");
+    opts.optopt(
+        "c",
+        "config",
+        "configuration file with custom producers",
+        "/path/to/sbom-generator.json",
+    );
+    opts.optopt(
+        "",
+        "plugins",
+        "directory with WebAssembly plugins",
+        "/path/to/plugins",
+    );
+    opts.optopt(
+        "",
+        "producers",
+        "producers or ecosystems to use (default: all)",
+        "maven,npm",
+    );
+    opts.optopt(
+        "",
+        "exclude-producers",
+        "producers or ecosystems to skip",
+        "binary",
+    );
+    add_scan_options(opts);
+}
+
+/// Parse the arguments of a subcommand, print the usage with `--help`.
+fn parse_subcommand(opts: &Options, brief: &str, args: &[String]) -> Matches {
+    let matches = match opts.parse(args) {
+        Ok(m) => m,
+        Err(f) => {
+            panic!("error when parsing arguments: {}", f)
+        }
+    };
+
+    if matches.opt_present("h") {
+        print!("{}", opts.usage(brief));
+        exit(0);
+    }
+    matches
+}
+
+/// Get the format of the report with `--format` (default: markdown).
+fn get_report_format(matches: &Matches) -> ReportFormat {
+    match matches
+        .opt_str("format")
+        .unwrap_or("markdown".to_string())
+        .parse::<ReportFormat>()
+    {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1);
+        }
+    }
+}
+
+/// Get the maximum number of parents of a `pom.xml` with `--max-parent-depth`.
+fn get_max_parent_depth(matches: &Matches) -> usize {
+    get_number_option(matches, "max-parent-depth").unwrap_or(DEFAULT_MAX_PARENT_DEPTH)
+}
+
+/// Parse a numeric option, exit if it is not a number.
+fn get_number_option<T: std::str::FromStr>(matches: &Matches, name: &str) -> Option<T> {
+    let value = matches.opt_str(name)?;
+    match value.parse::<T>() {
+        Ok(v) => Some(v),
+        Err(_) => {
+            eprintln!("invalid value {} for --{}", value, name);
+            exit(1);
+        }
+    }
+}
+
+/// Get the files to scan with `--include`, `--exclude`, `--gitignore`, `--max-depth` and
+/// `--max-file-size`.
+fn get_file_selection(matches: &Matches) -> FileSelection {
+    FileSelection {
+        includes: matches.opt_strs("include"),
+        excludes: matches.opt_strs("exclude"),
+        use_ignore_files: matches.opt_present("gitignore"),
+        max_depth: get_number_option(matches, "max-depth"),
+        max_file_size: get_number_option(matches, "max-file-size"),
+    }
+}
+
+/// Get the configuration of a scan of the [directories] from the options of the
+/// subcommand, the options that the subcommand does not define keep their default value.
+fn get_configuration(matches: &Matches, directories: Vec<String>) -> Configuration {
+    let get_option = |name: &str| {
+        if matches.opt_defined(name) {
+            matches.opt_str(name)
+        } else {
+            None
+        }
+    };
+    Configuration {
+        directories,
+        output: get_option("o").unwrap_or_default(),
+        use_debug: matches.opt_present("d"),
+        config: get_option("c"),
+        plugins: get_option("plugins"),
+        producers: get_producer_names(get_option("producers")),
+        exclude_producers: get_producer_names(get_option("exclude-producers")),
+        diagnostics: get_option("diagnostics"),
+        git_ref: get_option("git-ref"),
+        strict: matches.opt_defined("strict") && matches.opt_present("strict"),
+        max_parent_depth: get_max_parent_depth(matches),
+        allowed_roots: matches.opt_strs("allow-external-root"),
+        files: get_file_selection(matches),
+        jobs: get_number_option(matches, "jobs"),
+        cache: matches.opt_str("cache"),
+    }
+}
+
+/// `sbom-generator diff A B`: compare two SBOM files or two revisions of the repository.
+fn diff_main(program: &str, args: &[String]) {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help");
+    opts.optopt(
+        "i",
+        "directory",
+        "repository to scan for the revisions (default: current directory)",
+        "/path/to/repository",
+    );
+    opts.optopt(
+        "o",
+        "output",
+        "file to write the diff (default: stdout)",
+        "/path/to/diff.md",
+    );
+    opts.optopt(
+        "",
+        "format",
+        "format of the diff: markdown or json (default: markdown)",
+        "json",
+    );
+    add_producer_options(&mut opts);
+
+    let brief = format!(
+        "Usage: {} diff BEFORE AFTER [options]\n\nBEFORE and AFTER are SBOM files or git revisions of the repository.",
+        program
+    );
+    let matches = parse_subcommand(&opts, &brief, args);
+
+    let [before, after] = matches.free.as_slice() else {
+        eprintln!("expected two SBOM files or git revisions to compare");
+        print!("{}", opts.usage(&brief));
+        exit(1);
+    };
+
+    let format = get_report_format(&matches);
+
+    let configuration = get_configuration(
+        &matches,
+        vec![matches.opt_str("i").unwrap_or(".".to_string())],
+    );
+
+    diff(&configuration, before, after, format).expect("error when comparing SBOMs");
+}
+
+/// `sbom-generator compare -i DIRECTORY REFERENCE...`: scan the directory and measure the
+/// accuracy against SBOMs generated by other tools.
+fn compare_main(program: &str, args: &[String]) {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help");
+    opts.optmulti(
+        "i",
+        "directory",
+        "directory to scan (valid existing directory), can be repeated to scan several directories together",
+        "/path/to/code/to/analyze",
+    );
+    opts.optopt(
+        "",
+        "git-ref",
+        "scan a commit, branch or tag of the repository without checking it out",
+        "v1.2.0",
+    );
+    opts.optopt(
+        "o",
+        "output",
+        "file to write the report (default: stdout)",
+        "/path/to/report.md",
+    );
+    opts.optopt(
+        "",
+        "format",
+        "format of the report: markdown or json (default: markdown)",
+        "json",
+    );
+    add_producer_options(&mut opts);
+
+    let brief = format!(
+        "Usage: {} compare -i DIRECTORY REFERENCE... [options]\n\nREFERENCE is a CycloneDX SBOM of the directory generated by another tool.",
+        program
+    );
+    let matches = parse_subcommand(&opts, &brief, args);
+
+    let directories = matches.opt_strs("i");
+    if directories.is_empty() {
+        eprintln!("missing directory to analyze");
+        print!("{}", opts.usage(&brief));
+        exit(1);
+    }
+    if matches.free.is_empty() {
+        eprintln!("missing reference SBOM");
+        print!("{}", opts.usage(&brief));
+        exit(1);
+    }
+
+    let format = get_report_format(&matches);
+
+    let configuration = get_configuration(&matches, directories);
+
+    compare(&configuration, matches.free.as_slice(), format)
+        .expect("error when comparing with the reference SBOMs");
+}
+
+/// `sbom-generator explain groupId:artifactId`: print how the version of a Maven
+/// dependency is resolved.
+fn explain_main(program: &str, args: &[String]) {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help");
+    opts.optmulti(
+        "i",
+        "directory",
+        "directory to scan (valid existing directory), can be repeated to scan several directories together",
+        "/path/to/code/to/analyze",
+    );
+    opts.optopt(
+        "",
+        "git-ref",
+        "scan a commit, branch or tag of the repository without checking it out",
+        "v1.2.0",
+    );
+    add_scan_options(&mut opts);
+    opts.optflag("d", "debug", "use debug mode");
+
+    let brief = format!(
+        "Usage: {} explain groupId:artifactId -i DIRECTORY [options]",
+        program
+    );
+    let matches = parse_subcommand(&opts, &brief, args);
+
+    let [dependency] = matches.free.as_slice() else {
+        eprintln!("expected a dependency groupId:artifactId");
+        print!("{}", opts.usage(&brief));
+        exit(1);
+    };
+    let directories = matches.opt_strs("i");
+    if directories.is_empty() {
+        eprintln!("missing directory to analyze");
+        print!("{}", opts.usage(&brief));
+        exit(1);
+    }
+
+    let configuration = get_configuration(&matches, directories);
+
+    if !explain(&configuration, dependency).expect("error when explaining the dependency") {
+        exit(1);
+    }
+}
+
+/// `sbom-generator effective-pom`: write the effective model of each `pom.xml`.
+fn effective_pom_main(program: &str, args: &[String]) {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help");
+    opts.optmulti(
+        "i",
+        "directory",
+        "directory to scan (valid existing directory), can be repeated to scan several directories together",
+        "/path/to/code/to/analyze",
+    );
+    opts.optopt(
+        "",
+        "git-ref",
+        "scan a commit, branch or tag of the repository without checking it out",
+        "v1.2.0",
+    );
+    opts.optopt(
+        "o",
+        "output",
+        "file to write the effective POMs (default: stdout)",
+        "/path/to/effective-pom.xml",
+    );
+    opts.optopt(
+        "",
+        "format",
+        "format of the effective POMs: xml or json (default: xml)",
+        "json",
+    );
+    add_scan_options(&mut opts);
+    opts.optflag("d", "debug", "use debug mode");
+
+    let brief = format!("Usage: {} effective-pom -i DIRECTORY [options]", program);
+    let matches = parse_subcommand(&opts, &brief, args);
+
+    let directories = matches.opt_strs("i");
+    if directories.is_empty() {
+        eprintln!("missing directory to analyze");
+        print!("{}", opts.usage(&brief));
+        exit(1);
+    }
+    let format = match matches
+        .opt_str("format")
+        .unwrap_or("xml".to_string())
+        .parse::<EffectivePomFormat>()
+    {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1);
+        }
+    };
+
+    let configuration = get_configuration(&matches, directories);
+
+    effective_pom(&configuration, format).expect("error when writing the effective POMs");
+}
+
+/// `sbom-generator watch -i DIRECTORY -o OUTPUT`: write the SBOM again each time a
+/// manifest changes.
+#[cfg(target_os = "linux")]
+fn watch_main(program: &str, args: &[String]) {
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help");
+    opts.optmulti(
+        "i",
+        "directory",
+        "directory to scan (valid existing directory), can be repeated to scan several directories together",
+        "/path/to/code/to/analyze",
+    );
+    opts.optopt(
+        "o",
+        "output",
+        "file to write the results",
+        "/path/to/file.sbom",
+    );
+    opts.optopt(
+        "",
+        "diagnostics",
+        "file to write the diagnostics of the producers (JSON)",
+        "/path/to/diagnostics.json",
+    );
+    add_producer_options(&mut opts);
+
+    let brief = format!(
+        "Usage: {} watch -i DIRECTORY -o OUTPUT [options]\n\nThe SBOM is written again each time a manifest is edited, created or deleted.",
+        program
+    );
+    let matches = parse_subcommand(&opts, &brief, args);
+
+    let directories = matches.opt_strs("i");
+    if directories.is_empty() {
+        eprintln!("missing directory to analyze");
+        print!("{}", opts.usage(&brief));
+        exit(1);
+    }
+    if !matches.opt_present("o") {
+        eprintln!("missing output file");
+        print!("{}", opts.usage(&brief));
+        exit(1);
+    }
+
+    let configuration = get_configuration(&matches, directories);
+
+    watch(&configuration).expect("error when watching the directories");
+}
+
 pub fn main() {
     let args: Vec<String> = env::args().collect();
     let program = args[0].clone();
 
+    match args.get(1).map(String::as_str) {
+        Some("diff") => {
+            diff_main(&program, &args[2..]);
+            exit(0);
+        }
+        Some("compare") => {
+            compare_main(&program, &args[2..]);
+            exit(0);
+        }
+        Some("explain") => {
+            explain_main(&program, &args[2..]);
+            exit(0);
+        }
+        Some("effective-pom") => {
+            effective_pom_main(&program, &args[2..]);
+            exit(0);
+        }
+        #[cfg(target_os = "linux")]
+        Some("watch") => {
+            watch_main(&program, &args[2..]);
+            exit(0);
+        }
+        _ => {}
+    }
+
     let mut opts = Options::new();
     opts.optflag("h", "help", "print this help");
-    opts.optopt(
+    opts.optmulti(
         "i",
         "directory",
-        "directory to scan (valid existing directory)",
+        "directory to scan (valid existing directory), can be repeated to scan several directories together",
         "/path/to/code/to/analyze",
     );
 
@@ -32,6 +506,64 @@ pub fn main() {
         "/path/to/file.sbom",
     );
 
+    opts.optopt(
+        "c",
+        "config",
+        "configuration file with custom producers",
+        "/path/to/sbom-generator.json",
+    );
+
+    opts.optopt(
+        "",
+        "plugins",
+        "directory with WebAssembly plugins",
+        "/path/to/plugins",
+    );
+
+    opts.optflag("", "list-producers", "list the producers and exit");
+
+    opts.optopt(
+        "",
+        "producers",
+        "producers or ecosystems to use (default: all)",
+        "maven,npm",
+    );
+
+    opts.optopt(
+        "",
+        "exclude-producers",
+        "producers or ecosystems to skip",
+        "binary",
+    );
+
+    opts.optopt(
+        "",
+        "diagnostics",
+        "file to write the diagnostics of the producers (JSON)",
+        "/path/to/diagnostics.json",
+    );
+
+    opts.optopt(
+        "",
+        "git-ref",
+        "scan a commit, branch or tag of the repository without checking it out",
+        "v1.2.0",
+    );
+
+    add_scan_options(&mut opts);
+
+    opts.optflag(
+        "",
+        "strict",
+        "exit with an error when a producer reports a warning or an error",
+    );
+
+    opts.optflag(
+        "",
+        "detect",
+        "print the ecosystems and manifest files found and exit",
+    );
+
     let matches = match opts.parse(&args[1..]) {
         Ok(m) => m,
         Err(f) => {
@@ -44,26 +576,32 @@ pub fn main() {
         exit(0);
     }
 
-    let directory_to_analyze_option = matches.opt_str("i");
     let output = matches.opt_str("o");
+    let configuration = get_configuration(&matches, matches.opt_strs("i"));
 
-    if directory_to_analyze_option.is_none() {
+    if matches.opt_present("list-producers") {
+        list_producers(&configuration).expect("error when loading producers");
+        exit(0);
+    }
+
+    if configuration.directories.is_empty() {
         eprintln!("missing directory to analyze");
         print_usage(&program, opts);
         exit(1);
     }
 
+    if matches.opt_present("detect") {
+        detect(&configuration).expect("error when detecting ecosystems");
+        exit(0);
+    }
+
     if output.is_none() {
         eprintln!("missing output file");
         print_usage(&program, opts);
         exit(1);
     }
 
-    let configuration = Configuration {
-        directory: directory_to_analyze_option.unwrap(),
-        output: output.unwrap(),
-        use_debug: matches.opt_present("d"),
-    };
-
-    analyze(&configuration).expect("error when generating SBOM");
+    if !analyze(&configuration).expect("error when generating SBOM") {
+        exit(1);
+    }
 }
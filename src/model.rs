@@ -1,4 +1,6 @@
 pub mod configuration;
 pub mod dependency;
+pub mod diagnostic;
 pub mod location;
 pub mod position;
+pub mod purl;
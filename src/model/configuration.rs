@@ -1,18 +1,114 @@
 use std::fmt::{Display, Formatter};
+use std::path::Path;
 
+use crate::analyze::producers::maven::context::DEFAULT_MAX_PARENT_DEPTH;
+
+/// Which files of the directory are scanned
+#[derive(Clone, Debug, Default)]
+pub struct FileSelection {
+    /// Globs of the files to scan, all the files when empty
+    pub includes: Vec<String>,
+    /// Globs of the files and directories to skip
+    pub excludes: Vec<String>,
+    /// Skip the files ignored by the `.gitignore` and `.ignore` files
+    pub use_ignore_files: bool,
+    /// Maximum depth of the files, 1 for the files of the directory only
+    pub max_depth: Option<usize>,
+    /// Maximum size of the files, in bytes
+    pub max_file_size: Option<u64>,
+}
+
+#[derive(Clone)]
 pub struct Configuration {
-    pub directory: String,
+    /// Directories (or archives) to scan, the locations are relative to their directory
+    pub directories: Vec<String>,
     pub output: String,
     pub use_debug: bool,
+    /// Configuration file that defines custom producers
+    pub config: Option<String>,
+    /// Directory with the WebAssembly plugins
+    pub plugins: Option<String>,
+    /// Producers or ecosystems to use, all when empty
+    pub producers: Vec<String>,
+    /// Producers or ecosystems to skip
+    pub exclude_producers: Vec<String>,
+    /// JSON file to write the diagnostics of the producers
+    pub diagnostics: Option<String>,
+    /// Scan this revision of the repository instead of the files on disk
+    pub git_ref: Option<String>,
+    /// Fail when the producers report warnings or errors
+    pub strict: bool,
+    /// Maximum number of parents followed from a `pom.xml`
+    pub max_parent_depth: usize,
+    /// Directories outside of the scanned directory that the scanned files can reference
+    pub allowed_roots: Vec<String>,
+    /// The files of the directory to scan
+    pub files: FileSelection,
+    /// Number of threads that parse the files, all the CPUs when not set
+    pub jobs: Option<usize>,
+    /// Directory of the cache of the parsed files, no cache when not set
+    pub cache: Option<String>,
+}
+
+impl Default for Configuration {
+    /// The configuration of a scan of the current directory without any option.
+    fn default() -> Self {
+        Configuration {
+            directories: vec![".".to_string()],
+            output: String::new(),
+            use_debug: false,
+            config: None,
+            plugins: None,
+            producers: vec![],
+            exclude_producers: vec![],
+            diagnostics: None,
+            git_ref: None,
+            strict: false,
+            max_parent_depth: DEFAULT_MAX_PARENT_DEPTH,
+            allowed_roots: vec![],
+            files: FileSelection::default(),
+            jobs: None,
+            cache: None,
+        }
+    }
 }
 
 impl Configuration {
+    /// Get the path of a scanned file relative to its directory, the deepest one when the
+    /// directories are nested.
+    pub fn get_relative_path<'a>(&self, path: &'a Path) -> &'a Path {
+        self.directories
+            .iter()
+            .filter_map(|d| path.strip_prefix(d).ok())
+            .min_by_key(|p| p.components().count())
+            .unwrap_or(path)
+    }
+
     pub fn print_configuration(&self) {
         println!("Configuration");
         println!("=============");
-        println!("Directory: {}", self.directory);
+        println!("Directory: {}", self.directories.join(","));
         println!("Output:    {}", self.output);
         println!("Debug:     {}", self.use_debug);
+        println!("Config:    {}", self.config.as_deref().unwrap_or("(none)"));
+        println!("Plugins:   {}", self.plugins.as_deref().unwrap_or("(none)"));
+        println!("Producers: {}", self.producers.join(","));
+        println!("Excluded:  {}", self.exclude_producers.join(","));
+        println!(
+            "Diagnostics: {}",
+            self.diagnostics.as_deref().unwrap_or("(none)")
+        );
+        println!("Git ref:   {}", self.git_ref.as_deref().unwrap_or("(none)"));
+        println!("Strict:    {}", self.strict);
+        println!("Max parent depth: {}", self.max_parent_depth);
+        println!("Allowed roots: {}", self.allowed_roots.join(","));
+        println!("Files:     {:?}", self.files);
+        println!(
+            "Jobs:      {}",
+            self.jobs
+                .map_or("(all CPUs)".to_string(), |j| j.to_string())
+        );
+        println!("Cache:     {}", self.cache.as_deref().unwrap_or("(none)"));
     }
 }
 
@@ -20,8 +116,22 @@ impl Display for Configuration {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(
             f,
-            "(directory={}, output={}, debug={})",
-            self.directory, self.output, self.use_debug
+            "(directories={}, output={}, debug={}, config={}, plugins={}, producers={}, exclude_producers={}, diagnostics={}, git_ref={}, strict={}, max_parent_depth={}, allowed_roots={}, files={:?}, jobs={:?}, cache={})",
+            self.directories.join(","),
+            self.output,
+            self.use_debug,
+            self.config.as_deref().unwrap_or("none"),
+            self.plugins.as_deref().unwrap_or("none"),
+            self.producers.join(","),
+            self.exclude_producers.join(","),
+            self.diagnostics.as_deref().unwrap_or("none"),
+            self.git_ref.as_deref().unwrap_or("none"),
+            self.strict,
+            self.max_parent_depth,
+            self.allowed_roots.join(","),
+            self.files,
+            self.jobs,
+            self.cache.as_deref().unwrap_or("none")
         )
     }
 }
@@ -1,14 +1,80 @@
+use std::str::FromStr;
+
 use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
 
 use crate::model::location::Location;
 
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DependencyType {
     #[default]
     Library,
+    /// A container image (e.g. the base image of a Dockerfile)
+    Container,
+    /// Something deployed as a whole (e.g. a Helm chart or a Terraform provider)
+    Application,
+}
+
+impl DependencyType {
+    /// The type of the component in the CycloneDX specification.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DependencyType::Library => "library",
+            DependencyType::Container => "container",
+            DependencyType::Application => "application",
+        }
+    }
+}
+
+impl FromStr for DependencyType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "library" => Ok(DependencyType::Library),
+            "container" => Ok(DependencyType::Container),
+            "application" => Ok(DependencyType::Application),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+/// A hash of the dependency content, the content is hex-encoded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyHash {
+    pub algorithm: HashAlgorithm,
+    pub content: String,
+}
+
+/// How a dependency was found. A declared dependency comes from a manifest or a lockfile
+/// (e.g. `pom.xml`), an installed dependency comes from the metadata of a package
+/// that is present on disk (e.g. `node_modules` or `site-packages`) and a compiled
+/// dependency comes from the metadata embedded in a binary.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DependencyEvidence {
+    #[default]
+    Declared,
+    Installed,
+    Compiled,
+}
+
+impl DependencyEvidence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DependencyEvidence::Declared => "declared",
+            DependencyEvidence::Installed => "installed",
+            DependencyEvidence::Compiled => "compiled",
+        }
+    }
 }
 
-#[derive(Builder, Clone, Default, Debug)]
+#[derive(Builder, Clone, Default, Debug, Serialize, Deserialize)]
 pub struct DependencyLocation {
     #[allow(dead_code)]
     pub block: Location,
@@ -18,7 +84,7 @@ pub struct DependencyLocation {
     pub version: Option<Location>,
 }
 
-#[derive(Builder, Clone, Default, Debug)]
+#[derive(Builder, Clone, Default, Debug, Serialize, Deserialize)]
 pub struct Dependency {
     #[allow(dead_code)]
     pub r#type: DependencyType,
@@ -30,4 +96,11 @@ pub struct Dependency {
     pub purl: String,
     #[allow(dead_code)]
     pub location: Option<DependencyLocation>,
+    #[builder(default)]
+    pub evidence: DependencyEvidence,
+    #[builder(default)]
+    pub hashes: Vec<DependencyHash>,
+    /// Additional (name, value) properties added to the component in the SBOM
+    #[builder(default)]
+    pub properties: Vec<(String, String)>,
 }
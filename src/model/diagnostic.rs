@@ -0,0 +1,139 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::location::Location;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl DiagnosticSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticSeverity::Info => "info",
+            DiagnosticSeverity::Warning => "warning",
+            DiagnosticSeverity::Error => "error",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticKind {
+    /// The file cannot be read or parsed
+    ParseError,
+    /// A `${property}` is not defined
+    UnresolvedProperty,
+    /// The parent of a file is declared but not found
+    MissingParent,
+    /// The parents of a file form a cycle
+    ParentCycle,
+    /// The parents of a file are followed up to the maximum depth only
+    ParentTooDeep,
+    /// A file references a path outside of the scanned directory
+    PathOutsideRoot,
+    /// A dependency is declared but not reported in the SBOM
+    DroppedDependency,
+    /// A declaration that is valid but worth reviewing (e.g. a mutable reference)
+    Notice,
+    /// The producer failed for all its files
+    ProducerError,
+}
+
+impl DiagnosticKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticKind::ParseError => "parse-error",
+            DiagnosticKind::UnresolvedProperty => "unresolved-property",
+            DiagnosticKind::MissingParent => "missing-parent",
+            DiagnosticKind::ParentCycle => "parent-cycle",
+            DiagnosticKind::ParentTooDeep => "parent-too-deep",
+            DiagnosticKind::PathOutsideRoot => "path-outside-root",
+            DiagnosticKind::DroppedDependency => "dropped-dependency",
+            DiagnosticKind::Notice => "notice",
+            DiagnosticKind::ProducerError => "producer-error",
+        }
+    }
+}
+
+/// A problem found by a producer. Diagnostics are reported with the SBOM so that a
+/// dependency missing from the SBOM can be explained.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: DiagnosticSeverity,
+    pub kind: DiagnosticKind,
+    pub message: String,
+    /// The file the diagnostic is about
+    pub file: String,
+    /// The declaration the diagnostic is about, when known
+    pub location: Option<Location>,
+    /// Name of the producer, set when the diagnostics of all producers are collected
+    pub producer: Option<String>,
+    /// The package URL of the dependency the diagnostic is about, when known (its version,
+    /// if any, is not used). Used to explain why a dependency is missing from the SBOM.
+    pub package: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: DiagnosticSeverity,
+        kind: DiagnosticKind,
+        file: &Path,
+        message: impl Into<String>,
+    ) -> Self {
+        Diagnostic {
+            severity,
+            kind,
+            message: message.into(),
+            file: file.display().to_string(),
+            location: None,
+            producer: None,
+            package: None,
+        }
+    }
+
+    pub fn error(kind: DiagnosticKind, file: &Path, message: impl Into<String>) -> Self {
+        Self::new(DiagnosticSeverity::Error, kind, file, message)
+    }
+
+    pub fn warning(kind: DiagnosticKind, file: &Path, message: impl Into<String>) -> Self {
+        Self::new(DiagnosticSeverity::Warning, kind, file, message)
+    }
+
+    pub fn with_location(self, location: Option<Location>) -> Self {
+        Diagnostic { location, ..self }
+    }
+
+    pub fn with_package(self, package: impl Into<String>) -> Self {
+        Diagnostic {
+            package: Some(package.into()),
+            ..self
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(location) = &self.location {
+            write!(f, ":{}:{}", location.start.line, location.start.col)?;
+        }
+        write!(
+            f,
+            ": {}: {} [{}",
+            self.severity.as_str(),
+            self.message,
+            self.kind.as_str()
+        )?;
+        if let Some(producer) = &self.producer {
+            write!(f, ", {}", producer)?;
+        }
+        write!(f, "]")
+    }
+}
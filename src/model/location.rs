@@ -1,8 +1,10 @@
 use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 
 use crate::model::position::Position;
 
-#[derive(Builder, Clone, Default, Debug)]
+#[derive(Builder, Clone, Default, Debug, Serialize, Deserialize)]
 pub struct Location {
     #[allow(dead_code)]
     pub file: String,
@@ -11,3 +13,9 @@ pub struct Location {
     #[allow(dead_code)]
     pub end: Position,
 }
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.start.line, self.start.col)
+    }
+}
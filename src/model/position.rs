@@ -1,15 +1,23 @@
 use std::num::NonZeroU32;
 
-use bstr::BStr;
 use bstr::ByteSlice;
 use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
 
-#[derive(Builder, Clone, Copy, Debug)]
+#[derive(Builder, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Position {
     #[allow(dead_code)]
     pub line: NonZeroU32,
+    /// Column in graphemes, what a user sees as a character
     #[allow(dead_code)]
     pub col: NonZeroU32,
+    /// Offset in bytes from the start of the file
+    #[allow(dead_code)]
+    pub offset: usize,
+    /// Column in UTF-16 code units, as used by the editors (e.g. the language server
+    /// protocol)
+    #[allow(dead_code)]
+    pub utf16_col: NonZeroU32,
 }
 
 impl Default for Position {
@@ -17,50 +25,109 @@ impl Default for Position {
         Self {
             line: NonZeroU32::new(1).unwrap(),
             col: NonZeroU32::new(1).unwrap(),
+            offset: 0,
+            utf16_col: NonZeroU32::new(1).unwrap(),
         }
     }
 }
 
-/// Get position of an offset in a code and return a [Position].
-pub fn get_position_in_string(content: &str, offset: usize) -> anyhow::Result<Position> {
-    if offset >= content.len() {
-        anyhow::bail!("offset is larger than content length");
+/// The start of the lines of a file, computed once to get the [Position] of many offsets
+/// without reading the file again.
+pub struct LineIndex<'a> {
+    content: &'a str,
+    /// Offset of the first byte of each line, the first line starts at 0
+    line_starts: Vec<usize>,
+}
+
+fn to_one_based(index: usize) -> anyhow::Result<NonZeroU32> {
+    u32::try_from(index + 1)
+        .ok()
+        .and_then(NonZeroU32::new)
+        .ok_or(anyhow::anyhow!("position is too large"))
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(content: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(content.bytes().enumerate().filter_map(|(i, b)| match b {
+                b'\n' => Some(i + 1),
+                _ => None,
+            }))
+            .collect();
+        LineIndex {
+            content,
+            line_starts,
+        }
     }
 
-    let bstr = BStr::new(&content);
+    /// Get the [Position] of an offset. An offset within a grapheme is in the next column,
+    /// the offset of the end of the content is valid (e.g. the end of the last node).
+    pub fn get_position(&self, offset: usize) -> anyhow::Result<Position> {
+        if offset > self.content.len() {
+            anyhow::bail!("offset is larger than content length");
+        }
+
+        // the last line that starts at or before the offset
+        let line_index = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let line_start = self.line_starts[line_index];
+        let line_end = self
+            .line_starts
+            .get(line_index + 1)
+            .copied()
+            .unwrap_or(self.content.len());
+        let line = &self.content.as_bytes()[line_start..line_end];
+        let line_offset = offset - line_start;
 
-    let mut line_number: u32 = 1;
-    let lines = bstr.lines_with_terminator();
-    for line in lines {
-        let start_index = line.as_ptr() as usize - content.as_ptr() as usize;
-        let end_index = start_index + line.len();
+        // an offset within a grapheme (or a character) is in the next column
+        let graphemes = line
+            .grapheme_indices()
+            .take_while(|(start, _, _)| *start < line_offset)
+            .count();
+        let utf16_units: usize = line
+            .char_indices()
+            .take_while(|(start, _, _)| *start < line_offset)
+            .map(|(_, _, c)| c.len_utf16())
+            .sum();
 
-        if (start_index..end_index).contains(&offset) {
-            let mut col_number: u32 = 1;
-            for (grapheme_start, grapheme_end, _) in line.grapheme_indices() {
-                let grapheme_absolute_start = start_index + grapheme_start;
-                let grapheme_absolute_end = start_index + grapheme_end;
+        Ok(Position {
+            line: to_one_based(line_index)?,
+            col: to_one_based(graphemes)?,
+            offset,
+            utf16_col: to_one_based(utf16_units)?,
+        })
+    }
+}
 
-                // It's exactly the index we are looking for.
-                if offset == grapheme_absolute_start {
-                    return Ok(Position {
-                        line: NonZeroU32::new(line_number).unwrap(),
-                        col: NonZeroU32::new(col_number).unwrap(),
-                    });
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                // The offset is within the grapheme we are looking for, it's the next col.
-                if (grapheme_absolute_start..grapheme_absolute_end).contains(&offset) {
-                    return Ok(Position {
-                        line: NonZeroU32::new(line_number).unwrap(),
-                        col: NonZeroU32::new(col_number + 1).unwrap(),
-                    });
-                }
-                col_number += 1;
-            }
-        }
-        line_number += 1;
+    fn get_positions(content: &str, offset: usize) -> (u32, u32, u32) {
+        let position = LineIndex::new(content).get_position(offset).unwrap();
+        assert_eq!(position.offset, offset);
+        (
+            position.line.get(),
+            position.col.get(),
+            position.utf16_col.get(),
+        )
     }
 
-    Err(anyhow::anyhow!("cannot find position"))
+    #[test]
+    fn test_get_position() {
+        let content = "<a>\r\n  <b>\u{1F600}é</b>\n";
+        assert_eq!(get_positions(content, 0), (1, 1, 1));
+        assert_eq!(get_positions(content, 3), (1, 4, 4));
+        // the line starts after the \r\n
+        assert_eq!(get_positions(content, 5), (2, 1, 1));
+        assert_eq!(get_positions(content, 8), (2, 4, 4));
+        // the emoji is one grapheme and two UTF-16 code units
+        let after_emoji = content.find('é').unwrap();
+        assert_eq!(get_positions(content, after_emoji), (2, 7, 8));
+        // an offset within a grapheme is in the next column
+        assert_eq!(get_positions(content, after_emoji - 1), (2, 7, 8));
+        // the end of the content is after the last line
+        assert_eq!(get_positions(content, content.len()), (3, 1, 1));
+        assert_eq!(get_positions("<a/>", 4), (1, 5, 5));
+        assert!(LineIndex::new("<a/>").get_position(5).is_err());
+    }
 }
@@ -0,0 +1,242 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref REGEX_PYPI_NAME_NORMALIZATION: Regex = Regex::new(r"[-_.]+").unwrap();
+}
+
+/// Characters that can be kept as-is in a purl component. Everything else is percent-encoded.
+fn is_purl_safe(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '~' | '+')
+}
+
+/// Percent-encode a single component (namespace segment, name, version) of a purl.
+pub fn encode_purl_component(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        if is_purl_safe(c) {
+            res.push(c);
+        } else {
+            let mut buffer = [0; 4];
+            for b in c.encode_utf8(&mut buffer).bytes() {
+                res.push_str(format!("%{:02X}", b).as_str());
+            }
+        }
+    }
+    res
+}
+
+/// Build a package URL (see https://github.com/package-url/purl-spec).
+/// The namespace can contain several segments separated by `/`, each segment is encoded
+/// separately.
+///
+/// `get_purl("npm", Some("@angular"), "core", Some("1.0.0"))` returns `pkg:npm/%40angular/core@1.0.0`
+pub fn get_purl(
+    r#type: &str,
+    namespace: Option<&str>,
+    name: &str,
+    version: Option<&str>,
+) -> String {
+    get_purl_with_qualifiers(r#type, namespace, name, version, &[])
+}
+
+/// Same as [get_purl] but also add qualifiers (e.g. `?arch=amd64`). Qualifiers are sorted
+/// by key as required by the specification.
+pub fn get_purl_with_qualifiers(
+    r#type: &str,
+    namespace: Option<&str>,
+    name: &str,
+    version: Option<&str>,
+    qualifiers: &[(&str, &str)],
+) -> String {
+    let mut res = format!("pkg:{}/", r#type.to_lowercase());
+
+    if let Some(ns) = namespace.filter(|n| !n.is_empty()) {
+        let segments: Vec<String> = ns
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(encode_purl_component)
+            .collect();
+        res.push_str(segments.join("/").as_str());
+        res.push('/');
+    }
+
+    res.push_str(encode_purl_component(name).as_str());
+
+    if let Some(v) = version {
+        res.push('@');
+        res.push_str(encode_purl_component(v).as_str());
+    }
+
+    let mut sorted_qualifiers: Vec<&(&str, &str)> =
+        qualifiers.iter().filter(|(_, v)| !v.is_empty()).collect();
+    sorted_qualifiers.sort_by_key(|(k, _)| k.to_lowercase());
+    if !sorted_qualifiers.is_empty() {
+        let q: Vec<String> = sorted_qualifiers
+            .iter()
+            .map(|(k, v)| format!("{}={}", k.to_lowercase(), encode_purl_component(v)))
+            .collect();
+        res.push('?');
+        res.push_str(q.join("&").as_str());
+    }
+
+    res
+}
+
+/// Add a subpath to a purl (e.g. the subspec of a CocoaPods pod: `pkg:cocoapods/Firebase@10.0.0#Core`).
+pub fn add_purl_subpath(purl: &str, subpath: &str) -> String {
+    let segments: Vec<String> = subpath
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != "." && *s != "..")
+        .map(encode_purl_component)
+        .collect();
+    if segments.is_empty() {
+        return purl.to_string();
+    }
+    format!("{}#{}", purl, segments.join("/"))
+}
+
+/// Decode the percent-encoded characters of a purl component.
+pub fn decode_purl_component(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let decoded = match bytes.get(i + 1..i + 3) {
+            Some(hex) if bytes[i] == b'%' => std::str::from_utf8(hex)
+                .ok()
+                .and_then(|h| u8::from_str_radix(h, 16).ok()),
+            _ => None,
+        };
+        match decoded {
+            Some(b) => {
+                res.push(b);
+                i += 3;
+            }
+            None => {
+                res.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&res).to_string()
+}
+
+/// The parts of a package URL that identify a package, decoded. Qualifiers and subpath
+/// are ignored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Purl {
+    pub r#type: String,
+    pub namespace: Option<String>,
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/// Parse a package URL. The `@` of an npm scope may or may not be encoded
+/// (`pkg:npm/%40angular/core@16.2.0` or `pkg:npm/@angular/core@16.2.0`).
+pub fn parse_purl(purl: &str) -> Option<Purl> {
+    let rest = purl.strip_prefix("pkg:")?;
+    let end = rest.find(['?', '#']).unwrap_or(rest.len());
+    let (r#type, path) = rest[..end].trim_matches('/').split_once('/')?;
+
+    // the version is after the first `@` of the name
+    let name_start = path.rfind('/').map_or(0, |i| i + 1);
+    let (path, version) = match path[name_start..].find('@') {
+        Some(i) if i > 0 => (
+            &path[..name_start + i],
+            Some(decode_purl_component(&path[name_start + i + 1..])),
+        ),
+        _ => (path, None),
+    };
+
+    let mut segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(decode_purl_component)
+        .collect();
+    let name = segments.pop()?;
+    Some(Purl {
+        r#type: r#type.to_lowercase(),
+        namespace: Some(segments.join("/")).filter(|n| !n.is_empty()),
+        name,
+        version,
+    })
+}
+
+/// Normalize a Python package name as described in PEP 503, the name of a `pkg:pypi`
+/// purl (e.g. `Flask_SQLAlchemy` becomes `flask-sqlalchemy`).
+pub fn normalize_package_name(name: &str) -> String {
+    REGEX_PYPI_NAME_NORMALIZATION
+        .replace_all(name, "-")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_purl() {
+        assert_eq!(
+            get_purl("maven", Some("org.json"), "json", Some("20090211")),
+            "pkg:maven/org.json/json@20090211"
+        );
+        assert_eq!(
+            get_purl("npm", Some("@angular"), "core", Some("16.2.0")),
+            "pkg:npm/%40angular/core@16.2.0"
+        );
+        assert_eq!(
+            get_purl("pypi", None, "requests", None),
+            "pkg:pypi/requests"
+        );
+        assert_eq!(
+            get_purl_with_qualifiers(
+                "docker",
+                None,
+                "ubuntu",
+                Some("sha256:abcd"),
+                &[("tag", "22.04"), ("repository_url", "docker.io/library")]
+            ),
+            "pkg:docker/ubuntu@sha256%3Aabcd?repository_url=docker.io%2Flibrary&tag=22.04"
+        );
+        assert_eq!(
+            add_purl_subpath("pkg:cocoapods/Firebase@10.0.0", "Core"),
+            "pkg:cocoapods/Firebase@10.0.0#Core"
+        );
+    }
+
+    #[test]
+    fn test_parse_purl() {
+        assert_eq!(
+            parse_purl("pkg:npm/%40angular/core@16.2.0"),
+            parse_purl("pkg:npm/@angular/core@16.2.0")
+        );
+        let purl = parse_purl("pkg:npm/%40angular/core@16.2.0").unwrap();
+        assert_eq!(purl.r#type, "npm");
+        assert_eq!(purl.namespace, Some("@angular".to_string()));
+        assert_eq!(purl.name, "core");
+        assert_eq!(purl.version, Some("16.2.0".to_string()));
+
+        let purl = parse_purl("pkg:docker/ubuntu@sha256%3Aabcd?repository_url=docker.io%2Flibrary")
+            .unwrap();
+        assert_eq!(purl.namespace, None);
+        assert_eq!(purl.name, "ubuntu");
+        assert_eq!(purl.version, Some("sha256:abcd".to_string()));
+
+        let purl = parse_purl("pkg:cocoapods/Firebase#Core").unwrap();
+        assert_eq!(purl.name, "Firebase");
+        assert_eq!(purl.version, None);
+
+        assert_eq!(parse_purl("maven/org.json/json"), None);
+    }
+
+    #[test]
+    fn test_normalize_package_name() {
+        assert_eq!(
+            normalize_package_name("Flask_SQLAlchemy"),
+            "flask-sqlalchemy"
+        );
+        assert_eq!(normalize_package_name("zope.interface"), "zope-interface");
+        assert_eq!(normalize_package_name("my__-package"), "my-package");
+    }
+}
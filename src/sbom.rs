@@ -1 +1,5 @@
+pub mod compare;
+pub mod diagnostics;
+pub mod diff;
+pub mod format;
 pub mod generate;
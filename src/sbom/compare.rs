@@ -0,0 +1,433 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::model::diagnostic::{Diagnostic, DiagnosticKind};
+use crate::model::purl::{normalize_package_name, parse_purl};
+use crate::sbom::diff::SbomComponent;
+use crate::sbom::format::ReportFormat;
+
+/// The identity of a component, normalized so that the same package reported by
+/// different tools is equal (e.g. `Django` and `django` for PyPI, `v1.2.0` and `1.2.0`
+/// for Go).
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct PackageId {
+    ecosystem: String,
+    /// The namespace and the name, e.g. `org.slf4j/slf4j-api`
+    package: String,
+}
+
+/// Get the normalized package and version of a package URL.
+fn normalize_purl(purl: &str) -> Option<(PackageId, Option<String>)> {
+    let purl = parse_purl(purl)?;
+    let mut namespace = purl.namespace;
+    let mut name = purl.name;
+    let mut version = purl.version;
+
+    match purl.r#type.as_str() {
+        "pypi" => {
+            name = normalize_package_name(&name);
+        }
+        "npm" | "github" => {
+            namespace = namespace.map(|n| n.to_lowercase());
+            name = name.to_lowercase();
+        }
+        "golang" => {
+            version = version.map(|v| v.strip_prefix('v').unwrap_or(&v).to_string());
+        }
+        // official images are in the `library` namespace
+        "docker" => {
+            namespace = namespace.filter(|n| n != "library");
+        }
+        _ => {}
+    }
+
+    let package = match namespace {
+        Some(n) => format!("{}/{}", n, name),
+        None => name,
+    };
+    Some((
+        PackageId {
+            ecosystem: purl.r#type,
+            package,
+        },
+        version,
+    ))
+}
+
+/// The components of an SBOM by package and version.
+type Packages<'a> = BTreeMap<PackageId, BTreeMap<Option<String>, &'a SbomComponent>>;
+
+/// Group the components by package, components without a valid package URL are ignored.
+fn get_packages(components: &[SbomComponent]) -> Packages<'_> {
+    let mut packages: Packages = BTreeMap::new();
+    for component in components {
+        if let Some((id, version)) = component.purl.as_deref().and_then(normalize_purl) {
+            packages
+                .entry(id)
+                .or_default()
+                .entry(version)
+                .or_insert(component);
+        }
+    }
+    packages
+}
+
+/// The accuracy of the SBOM for an ecosystem, compared to the reference
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EcosystemAccuracy {
+    pub ecosystem: String,
+    /// Number of components (package and version) in the SBOM
+    pub found: usize,
+    /// Number of components in the reference
+    pub expected: usize,
+    /// Number of components in both
+    pub matched: usize,
+    /// `matched / found`, none when nothing is found
+    pub precision: Option<f64>,
+    /// `matched / expected`, none when nothing is expected
+    pub recall: Option<f64>,
+}
+
+impl EcosystemAccuracy {
+    fn new(ecosystem: &str, found: usize, expected: usize, matched: usize) -> Self {
+        let ratio = |total: usize| (total > 0).then(|| matched as f64 / total as f64);
+        EcosystemAccuracy {
+            ecosystem: ecosystem.to_string(),
+            found,
+            expected,
+            matched,
+            precision: ratio(found),
+            recall: ratio(expected),
+        }
+    }
+}
+
+/// A component in only one of the SBOM and the reference, with the reason why
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ComponentMismatch {
+    pub ecosystem: String,
+    pub package: String,
+    pub version: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<i64>,
+    pub explanation: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AccuracyReport {
+    /// The SBOM used as reference, e.g. the output of trivy
+    pub reference: String,
+    pub ecosystems: Vec<EcosystemAccuracy>,
+    /// Components of the reference missing from the SBOM
+    pub missing: Vec<ComponentMismatch>,
+    /// Components of the SBOM missing from the reference
+    pub unexpected: Vec<ComponentMismatch>,
+}
+
+fn join_versions<'a>(versions: impl Iterator<Item = &'a Option<String>>) -> String {
+    versions
+        .map(|v| v.as_deref().unwrap_or("no version"))
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+/// Explain a dependency missing from the SBOM with the diagnostic of the producer.
+fn explain_with_diagnostic(diagnostic: &Diagnostic) -> String {
+    match diagnostic.kind {
+        DiagnosticKind::UnresolvedProperty => {
+            format!("dropped because version unresolved: {}", diagnostic.message)
+        }
+        DiagnosticKind::DroppedDependency => format!("dropped: {}", diagnostic.message),
+        _ => diagnostic.message.clone(),
+    }
+}
+
+/// Compare the components found ([found]) to the components of a reference SBOM
+/// ([expected]). Only the ecosystems of the reference are compared, other tools do not
+/// support all the ecosystems. The [diagnostics] of the producers explain the
+/// components that are missing.
+pub fn compare_components(
+    reference: &str,
+    found: &[SbomComponent],
+    expected: &[SbomComponent],
+    diagnostics: &[Diagnostic],
+) -> AccuracyReport {
+    let found_packages = get_packages(found);
+    let expected_packages = get_packages(expected);
+    let ecosystems: BTreeSet<&str> = expected_packages
+        .keys()
+        .map(|id| id.ecosystem.as_str())
+        .collect();
+
+    let mut diagnostic_packages: BTreeMap<PackageId, &Diagnostic> = BTreeMap::new();
+    for diagnostic in diagnostics {
+        if let Some((id, _)) = diagnostic.package.as_deref().and_then(normalize_purl) {
+            diagnostic_packages.entry(id).or_insert(diagnostic);
+        }
+    }
+
+    let mut counts: BTreeMap<&str, (usize, usize, usize)> =
+        ecosystems.iter().map(|e| (*e, (0, 0, 0))).collect();
+    let mut missing = vec![];
+    let mut unexpected = vec![];
+
+    for (id, versions) in &expected_packages {
+        let found_versions = found_packages.get(id);
+        for (version, component) in versions {
+            let count = counts.get_mut(id.ecosystem.as_str()).unwrap();
+            count.1 += 1;
+            if found_versions.is_some_and(|v| v.contains_key(version)) {
+                count.2 += 1;
+                continue;
+            }
+
+            let diagnostic = diagnostic_packages.get(id);
+            let (explanation, file, line) = match (found_versions, diagnostic) {
+                (Some(v), _) => {
+                    let other = v.values().next().unwrap();
+                    (
+                        format!("found with version {}", join_versions(v.keys())),
+                        other.file.clone(),
+                        other.line,
+                    )
+                }
+                (None, Some(d)) => (
+                    explain_with_diagnostic(d),
+                    Some(d.file.clone()),
+                    d.location.as_ref().map(|l| l.start.line.get() as i64),
+                ),
+                (None, None) => (
+                    "not found by any producer".to_string(),
+                    component.file.clone(),
+                    component.line,
+                ),
+            };
+            missing.push(ComponentMismatch {
+                ecosystem: id.ecosystem.clone(),
+                package: id.package.clone(),
+                version: version.clone(),
+                file,
+                line,
+                explanation,
+            });
+        }
+    }
+
+    for (id, versions) in &found_packages {
+        let Some(count) = counts.get_mut(id.ecosystem.as_str()) else {
+            continue;
+        };
+        let expected_versions = expected_packages.get(id);
+        for (version, component) in versions {
+            count.0 += 1;
+            if expected_versions.is_some_and(|v| v.contains_key(version)) {
+                continue;
+            }
+            let explanation = match expected_versions {
+                Some(v) => format!("reference has version {}", join_versions(v.keys())),
+                None => "not in the reference".to_string(),
+            };
+            unexpected.push(ComponentMismatch {
+                ecosystem: id.ecosystem.clone(),
+                package: id.package.clone(),
+                version: version.clone(),
+                file: component.file.clone(),
+                line: component.line,
+                explanation,
+            });
+        }
+    }
+
+    AccuracyReport {
+        reference: reference.to_string(),
+        ecosystems: counts
+            .into_iter()
+            .map(|(e, (f, x, m))| EcosystemAccuracy::new(e, f, x, m))
+            .collect(),
+        missing,
+        unexpected,
+    }
+}
+
+fn get_percentage(value: Option<f64>) -> String {
+    value
+        .map(|v| format!("{:.2}%", v * 100.0))
+        .unwrap_or("n/a".to_string())
+}
+
+fn get_location(mismatch: &ComponentMismatch) -> String {
+    match (&mismatch.file, mismatch.line) {
+        (Some(f), Some(l)) => format!("`{}:{}`", f, l),
+        (Some(f), None) => format!("`{}`", f),
+        _ => String::new(),
+    }
+}
+
+fn push_mismatches(result: &mut String, title: &str, mismatches: &[ComponentMismatch]) {
+    if mismatches.is_empty() {
+        return;
+    }
+    result.push_str(&format!("\n#### {} ({})\n\n", title, mismatches.len()));
+    result.push_str("| Component | Version | Location | Explanation |\n");
+    result.push_str("|-----------|---------|----------|-------------|\n");
+    for m in mismatches {
+        result.push_str(&format!(
+            "| `pkg:{}/{}` | {} | {} | {} |\n",
+            m.ecosystem,
+            m.package,
+            m.version.as_deref().unwrap_or(""),
+            get_location(m),
+            m.explanation.replace('|', "\\|")
+        ));
+    }
+}
+
+impl AccuracyReport {
+    /// Render the report as markdown: the precision and recall of each ecosystem, then
+    /// the components missing and unexpected.
+    pub fn to_markdown(&self) -> String {
+        let mut result = format!("### Accuracy against {}\n\n", self.reference);
+        result.push_str("| Ecosystem | Found | Reference | Matched | Precision | Recall |\n");
+        result.push_str("|-----------|-------|-----------|---------|-----------|--------|\n");
+        for e in &self.ecosystems {
+            result.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} |\n",
+                e.ecosystem,
+                e.found,
+                e.expected,
+                e.matched,
+                get_percentage(e.precision),
+                get_percentage(e.recall)
+            ));
+        }
+        push_mismatches(&mut result, "Missing", self.missing.as_slice());
+        push_mismatches(
+            &mut result,
+            "Not in the reference",
+            self.unexpected.as_slice(),
+        );
+        result
+    }
+}
+
+/// Render the reports of all the reference SBOMs.
+pub fn render_reports(reports: &[AccuracyReport], format: ReportFormat) -> anyhow::Result<String> {
+    match format {
+        ReportFormat::Json => Ok(serde_json::to_string_pretty(reports)?),
+        ReportFormat::Markdown => Ok(reports
+            .iter()
+            .map(|r| r.to_markdown())
+            .collect::<Vec<String>>()
+            .join("\n")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn get_component(purl: &str, file: &str, line: i64) -> SbomComponent {
+        SbomComponent {
+            name: purl.to_string(),
+            version: None,
+            purl: Some(purl.to_string()),
+            file: Some(file.to_string()),
+            line: Some(line),
+        }
+    }
+
+    #[test]
+    fn test_normalize_purl() {
+        assert_eq!(
+            normalize_purl("pkg:pypi/Django_Rest.framework@3.15.0"),
+            normalize_purl("pkg:pypi/django-rest-framework@3.15.0")
+        );
+        assert_eq!(
+            normalize_purl("pkg:pypi/zope__interface@6.0"),
+            normalize_purl("pkg:pypi/zope.-interface@6.0")
+        );
+        assert_eq!(
+            normalize_purl("pkg:golang/github.com/spf13/cobra@v1.8.0"),
+            normalize_purl("pkg:golang/github.com/spf13/cobra@1.8.0")
+        );
+        assert_eq!(
+            normalize_purl("pkg:docker/library/alpine@3.19"),
+            normalize_purl("pkg:docker/alpine@3.19?arch=amd64")
+        );
+        assert_eq!(
+            normalize_purl("pkg:npm/@Types/node@20.0.0"),
+            normalize_purl("pkg:npm/%40types/node@20.0.0")
+        );
+        assert_ne!(
+            normalize_purl("pkg:maven/org.slf4j/SLF4J-api@2.0.13"),
+            normalize_purl("pkg:maven/org.slf4j/slf4j-api@2.0.13")
+        );
+    }
+
+    #[test]
+    fn test_compare_components() {
+        let found = vec![
+            get_component("pkg:maven/org.slf4j/slf4j-api@2.0.13", "pom.xml", 24),
+            get_component("pkg:maven/junit/junit@4.13.2", "pom.xml", 30),
+            get_component("pkg:maven/com.google.guava/guava@32.0.0-jre", "pom.xml", 36),
+            get_component("pkg:github/actions/checkout@v4", "ci.yml", 12),
+        ];
+        let expected = vec![
+            get_component("pkg:maven/org.slf4j/slf4j-api@2.0.13", "", 0),
+            get_component("pkg:maven/com.google.guava/guava@33.0.0-jre", "", 0),
+            get_component("pkg:maven/commons-io/commons-io@2.11.0", "", 0),
+            get_component("pkg:maven/org.yaml/snakeyaml@2.2", "", 0),
+        ];
+        let diagnostics = vec![Diagnostic::warning(
+            DiagnosticKind::UnresolvedProperty,
+            Path::new("pom.xml"),
+            "property commons.version is not defined, commons-io:commons-io is dropped",
+        )
+        .with_package("pkg:maven/commons-io/commons-io")];
+
+        let report = compare_components("trivy.json", &found, &expected, &diagnostics);
+
+        // github is not in the reference
+        assert_eq!(report.ecosystems.len(), 1);
+        let maven = &report.ecosystems[0];
+        assert_eq!(maven.ecosystem, "maven");
+        assert_eq!((maven.found, maven.expected, maven.matched), (3, 4, 1));
+        assert_eq!(maven.precision, Some(1.0 / 3.0));
+        assert_eq!(maven.recall, Some(0.25));
+
+        assert_eq!(report.missing.len(), 3);
+        let commons_io = report
+            .missing
+            .iter()
+            .find(|m| m.package == "commons-io/commons-io")
+            .unwrap();
+        assert!(commons_io
+            .explanation
+            .starts_with("dropped because version unresolved"));
+        let guava = report
+            .missing
+            .iter()
+            .find(|m| m.package == "com.google.guava/guava")
+            .unwrap();
+        assert_eq!(guava.explanation, "found with version 32.0.0-jre");
+        assert_eq!(guava.line, Some(36));
+        let snakeyaml = report
+            .missing
+            .iter()
+            .find(|m| m.package == "org.yaml/snakeyaml")
+            .unwrap();
+        assert_eq!(snakeyaml.explanation, "not found by any producer");
+
+        assert_eq!(report.unexpected.len(), 2);
+        assert!(report
+            .unexpected
+            .iter()
+            .any(|m| m.package == "junit/junit" && m.explanation == "not in the reference"));
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| maven | 3 | 4 | 1 | 33.33% | 25.00% |"));
+        assert!(markdown.contains("#### Missing (3)"));
+    }
+}
@@ -0,0 +1,11 @@
+use std::fs;
+use std::io::Write;
+
+use crate::model::diagnostic::Diagnostic;
+
+/// Write the diagnostics of all the producers to a JSON file.
+pub fn write_diagnostics(diagnostics: &[Diagnostic], path: &str) -> anyhow::Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(serde_json::to_string_pretty(diagnostics)?.as_bytes())?;
+    Ok(())
+}
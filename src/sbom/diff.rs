@@ -0,0 +1,275 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use serde::Serialize;
+use serde_cyclonedx::cyclonedx::v_1_6::CycloneDx;
+
+use crate::sbom::format::ReportFormat;
+
+/// A component of an SBOM with the file and the line where it is declared, when the
+/// SBOM has this information.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SbomComponent {
+    pub name: String,
+    pub version: Option<String>,
+    pub purl: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<i64>,
+}
+
+/// A component found in both SBOMs with different versions
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SbomVersionChange {
+    pub name: String,
+    /// The package URL without version, that identifies the component in both SBOMs
+    pub package: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+    /// Where the new version is declared (or where the old one was, for a version removed)
+    pub file: Option<String>,
+    pub line: Option<i64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SbomDiff {
+    pub added: Vec<SbomComponent>,
+    pub removed: Vec<SbomComponent>,
+    pub changed: Vec<SbomVersionChange>,
+}
+
+/// Read a CycloneDX SBOM in JSON.
+pub fn read_sbom(path: &str) -> anyhow::Result<CycloneDx> {
+    let content = fs::read(path)?;
+    serde_json::from_slice(content.as_slice())
+        .map_err(|e| anyhow::anyhow!("invalid CycloneDX file {}: {}", path, e))
+}
+
+/// Get the components of an SBOM, with the location of their first occurrence.
+pub fn get_components(sbom: &CycloneDx) -> Vec<SbomComponent> {
+    sbom.components
+        .iter()
+        .flatten()
+        .map(|c| {
+            let occurrence = c
+                .evidence
+                .as_ref()
+                .and_then(|e| e.occurrences.as_ref())
+                .and_then(|o| o.first());
+            SbomComponent {
+                name: c.name.clone(),
+                version: c.version.clone(),
+                purl: c.purl.clone(),
+                file: occurrence.map(|o| o.location.clone()),
+                line: occurrence.and_then(|o| o.line),
+            }
+        })
+        .collect()
+}
+
+/// Get the identity of a component in both SBOMs: its package URL without the version,
+/// the qualifiers or the subpath, e.g. `pkg:maven/org.slf4j/slf4j-api`. Components
+/// without package URL are identified by their name.
+fn get_package(component: &SbomComponent) -> String {
+    match &component.purl {
+        Some(purl) => {
+            let end = purl.find(['@', '?', '#']).unwrap_or(purl.len());
+            purl[..end].to_string()
+        }
+        None => component.name.clone(),
+    }
+}
+
+fn group_by_package(components: &[SbomComponent]) -> BTreeMap<String, Vec<&SbomComponent>> {
+    let mut packages: BTreeMap<String, Vec<&SbomComponent>> = BTreeMap::new();
+    for component in components {
+        packages
+            .entry(get_package(component))
+            .or_default()
+            .push(component);
+    }
+    packages
+}
+
+/// Get one component per version, the first one found.
+fn get_distinct_versions(components: &[&SbomComponent]) -> Vec<SbomComponent> {
+    let mut versions = BTreeSet::new();
+    components
+        .iter()
+        .filter(|c| versions.insert(c.version.clone()))
+        .map(|c| (*c).clone())
+        .collect()
+}
+
+fn get_versions(components: &[&SbomComponent]) -> BTreeSet<String> {
+    components
+        .iter()
+        .filter_map(|c| c.version.clone())
+        .collect()
+}
+
+/// Compare the components of two SBOMs, [before] being the reference.
+pub fn diff_components(before: &[SbomComponent], after: &[SbomComponent]) -> SbomDiff {
+    let before_packages = group_by_package(before);
+    let after_packages = group_by_package(after);
+    let mut diff = SbomDiff::default();
+
+    for (package, components) in &after_packages {
+        if !before_packages.contains_key(package) {
+            diff.added.extend(get_distinct_versions(components));
+        }
+    }
+
+    for (package, components) in &before_packages {
+        let Some(after_components) = after_packages.get(package) else {
+            diff.removed.extend(get_distinct_versions(components));
+            continue;
+        };
+
+        let before_versions = get_versions(components);
+        let after_versions = get_versions(after_components);
+        if before_versions == after_versions {
+            continue;
+        }
+
+        let location = after_components
+            .iter()
+            .find(|c| {
+                c.version
+                    .as_ref()
+                    .is_some_and(|v| !before_versions.contains(v))
+            })
+            .or_else(|| {
+                components.iter().find(|c| {
+                    c.version
+                        .as_ref()
+                        .is_some_and(|v| !after_versions.contains(v))
+                })
+            });
+
+        diff.changed.push(SbomVersionChange {
+            name: after_components[0].name.clone(),
+            package: package.clone(),
+            before: before_versions.into_iter().collect(),
+            after: after_versions.into_iter().collect(),
+            file: location.and_then(|c| c.file.clone()),
+            line: location.and_then(|c| c.line),
+        });
+    }
+    diff
+}
+
+fn get_location(file: &Option<String>, line: Option<i64>) -> String {
+    match (file, line) {
+        (Some(f), Some(l)) => format!("`{}:{}`", f, l),
+        (Some(f), None) => format!("`{}`", f),
+        _ => String::new(),
+    }
+}
+
+impl SbomDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Render the diff as a markdown table, to be posted as a comment of a pull request.
+    pub fn to_markdown(&self) -> String {
+        let mut result = String::from("### SBOM changes\n\n");
+        if self.is_empty() {
+            result.push_str("No component added, removed or changed.\n");
+            return result;
+        }
+
+        result.push_str(&format!(
+            "{} added, {} removed, {} changed\n\n",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        ));
+        result.push_str("| Change | Component | Before | After | Location |\n");
+        result.push_str("|--------|-----------|--------|-------|----------|\n");
+        for c in &self.added {
+            result.push_str(&format!(
+                "| added | `{}` | | {} | {} |\n",
+                get_package(c),
+                c.version.as_deref().unwrap_or(""),
+                get_location(&c.file, c.line)
+            ));
+        }
+        for c in &self.removed {
+            result.push_str(&format!(
+                "| removed | `{}` | {} | | {} |\n",
+                get_package(c),
+                c.version.as_deref().unwrap_or(""),
+                get_location(&c.file, c.line)
+            ));
+        }
+        for c in &self.changed {
+            result.push_str(&format!(
+                "| changed | `{}` | {} | {} | {} |\n",
+                c.package,
+                c.before.join(", "),
+                c.after.join(", "),
+                get_location(&c.file, c.line)
+            ));
+        }
+        result
+    }
+
+    pub fn render(&self, format: ReportFormat) -> anyhow::Result<String> {
+        match format {
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ReportFormat::Markdown => Ok(self.to_markdown()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn get_fixture(name: &str) -> String {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push(format!("resources/sbom/{}", name));
+        d.display().to_string()
+    }
+
+    #[test]
+    fn test_diff_components() {
+        let before = get_components(&read_sbom(&get_fixture("before.json")).unwrap());
+        let after = get_components(&read_sbom(&get_fixture("after.json")).unwrap());
+        let diff = diff_components(before.as_slice(), after.as_slice());
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].name, "jackson-databind");
+        assert_eq!(diff.added[0].file, Some("api/pom.xml".to_string()));
+        assert_eq!(diff.added[0].line, Some(31));
+
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].name, "commons-io");
+        assert_eq!(diff.removed[0].line, Some(18));
+
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].package, "pkg:maven/org.slf4j/slf4j-api");
+        assert_eq!(diff.changed[0].before, vec!["1.7.36".to_string()]);
+        assert_eq!(diff.changed[0].after, vec!["2.0.13".to_string()]);
+        assert_eq!(diff.changed[0].file, Some("pom.xml".to_string()));
+        assert_eq!(diff.changed[0].line, Some(24));
+
+        let markdown = diff.to_markdown();
+        assert!(markdown.contains("1 added, 1 removed, 1 changed"));
+        assert!(markdown.contains(
+            "| changed | `pkg:maven/org.slf4j/slf4j-api` | 1.7.36 | 2.0.13 | `pom.xml:24` |"
+        ));
+    }
+
+    #[test]
+    fn test_diff_same_sbom() {
+        let before = get_components(&read_sbom(&get_fixture("before.json")).unwrap());
+        let diff = diff_components(before.as_slice(), before.as_slice());
+        assert!(diff.is_empty());
+        assert!(diff
+            .to_markdown()
+            .contains("No component added, removed or changed."));
+    }
+}
@@ -0,0 +1,20 @@
+use std::str::FromStr;
+
+/// Output format of the reports (diff, comparison with a reference SBOM)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Markdown,
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(ReportFormat::Json),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            _ => anyhow::bail!("unknown format {} (json or markdown)", value),
+        }
+    }
+}
@@ -1,25 +1,92 @@
+use anyhow::Context;
 use std::fs;
 use std::io::Write;
+use std::path::Path;
 
-use serde_cyclonedx::cyclonedx::v_1_6::{ComponentBuilder, CycloneDxBuilder};
+use serde_cyclonedx::cyclonedx::v_1_6::{
+    ComponentBuilder, ComponentEvidenceBuilder, ComponentEvidenceItemOccurrencesBuilder, CycloneDx,
+    CycloneDxBuilder, Hash, HashAlg, PropertyBuilder,
+};
 
 use crate::model::configuration::Configuration;
-use crate::model::dependency::Dependency;
+use crate::model::dependency::{Dependency, HashAlgorithm};
 
-pub fn generate_sbom(
+/// Name of the CycloneDX property that indicates if a component was declared in a manifest
+/// or found installed on disk.
+pub const PROPERTY_EVIDENCE: &str = "sbom-generator:evidence";
+
+/// Name of the CycloneDX property that indicates if a component is pinned to an immutable
+/// reference (a commit SHA or an image digest) or to a tag that can be moved.
+pub const PROPERTY_PINNED: &str = "sbom-generator:pinned";
+
+/// Name of the CycloneDX property that holds the local directory a Go module was replaced
+/// with when the binary was built, the compiled code can differ from the declared version.
+pub const PROPERTY_LOCAL_REPLACEMENT: &str = "sbom-generator:local-replacement";
+
+/// Get the CycloneDX document of the dependencies. The file and the line where each
+/// dependency is declared are written as an occurrence, relative to the scanned directory.
+pub fn get_cyclonedx(
     dependencies: Vec<Dependency>,
     configuration: &Configuration,
-) -> anyhow::Result<()> {
-    let mut file = fs::File::create(configuration.output.as_str()).expect("cannot create file");
+) -> anyhow::Result<CycloneDx> {
     let components: Vec<serde_cyclonedx::cyclonedx::v_1_6::Component> = dependencies
         .into_iter()
         .map(|d| {
+            let mut properties = vec![PropertyBuilder::default()
+                .name(PROPERTY_EVIDENCE)
+                .value(d.evidence.as_str())
+                .build()
+                .unwrap()];
+            for (name, value) in &d.properties {
+                properties.push(
+                    PropertyBuilder::default()
+                        .name(name.as_str())
+                        .value(value.as_str())
+                        .build()
+                        .unwrap(),
+                );
+            }
+
             let mut binding = ComponentBuilder::default();
-            let mut component_builder = binding.name(d.name.to_string()).type_("library");
+            let mut component_builder = binding
+                .name(d.name.to_string())
+                .type_(d.r#type.as_str())
+                .purl(d.purl.clone())
+                .properties(properties);
 
             if let Some(v) = d.version {
                 component_builder = component_builder.version(&v);
-                component_builder.purl(format!("pkg:maven/{}@{}", d.name, &v).to_string());
+            }
+
+            if let Some(location) = &d.location {
+                let file = Path::new(&location.block.file);
+                let relative_file = configuration.get_relative_path(file);
+                let occurrence = ComponentEvidenceItemOccurrencesBuilder::default()
+                    .location(relative_file.display().to_string())
+                    .line(location.block.start.line.get() as i64)
+                    .build()
+                    .unwrap();
+                component_builder = component_builder.evidence(
+                    ComponentEvidenceBuilder::default()
+                        .occurrences(vec![occurrence])
+                        .build()
+                        .unwrap(),
+                );
+            }
+
+            if !d.hashes.is_empty() {
+                component_builder = component_builder.hashes(
+                    d.hashes
+                        .iter()
+                        .map(|h| Hash {
+                            alg: match h.algorithm {
+                                HashAlgorithm::Sha256 => HashAlg::Sha256,
+                                HashAlgorithm::Sha512 => HashAlg::Sha512,
+                            },
+                            content: h.content.clone(),
+                        })
+                        .collect::<Vec<Hash>>(),
+                );
             }
 
             component_builder.build().unwrap()
@@ -30,10 +97,18 @@ pub fn generate_sbom(
         .spec_version("1.6")
         .version(1)
         .components(components)
-        .build();
+        .build()?;
+    Ok(cyclonedx)
+}
 
-    let value_to_write =
-        serde_json::to_string(&cyclonedx.unwrap()).expect("cannot get CycloneDX file");
+pub fn generate_sbom(
+    dependencies: Vec<Dependency>,
+    configuration: &Configuration,
+) -> anyhow::Result<()> {
+    let mut file = fs::File::create(configuration.output.as_str())
+        .with_context(|| format!("cannot create file {}", configuration.output))?;
+    let cyclonedx = get_cyclonedx(dependencies, configuration)?;
+    let value_to_write = serde_json::to_string(&cyclonedx)?;
     file.write_all(value_to_write.as_bytes())?;
     Ok(())
 }
@@ -1,2 +1,4 @@
+pub mod cache;
 pub mod file_utils;
 pub mod tree_sitter;
+pub mod vfs;
@@ -0,0 +1,141 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Get the hex-encoded SHA-256 of the parts, each part is prefixed by its length so that
+/// `["ab", "c"]` and `["a", "bc"]` have different hashes.
+pub fn get_hash(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Version of the format of the entries, to increase when a cached type or the way a
+/// result is computed changes, so that a build of the same version never reads the
+/// entries of another build.
+const CACHE_SCHEMA_VERSION: u32 = 2;
+
+/// Results of the producers stored on disk between two scans, e.g. the parsed `pom.xml`
+/// files. An entry is found with a key computed from the content of the files it depends
+/// on, the version of the tool, the schema of the entries and the configuration: a changed file gets a new key and
+/// the old entry is never read again.
+///
+/// The cache is an optimization only: an entry that cannot be read or written is ignored.
+pub struct Cache {
+    directory: PathBuf,
+    /// Hash of the version of the tool, of the schema and of the configuration
+    version: String,
+    /// Number of entries read, for the debug mode
+    hits: AtomicUsize,
+    /// Number of entries not found
+    misses: AtomicUsize,
+}
+
+impl Cache {
+    /// Use the cache in [directory], only with the entries written with the same
+    /// [configuration] (the options that change the results of the producers).
+    pub fn new(directory: &Path, configuration: &[&[u8]]) -> Self {
+        let schema = CACHE_SCHEMA_VERSION.to_le_bytes();
+        let mut parts: Vec<&[u8]> = vec![env!("CARGO_PKG_VERSION").as_bytes(), &schema];
+        parts.extend(configuration);
+        Cache {
+            directory: directory.to_path_buf(),
+            version: get_hash(parts.as_slice()),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get the key of an entry of a [namespace] (e.g. the name of a producer) from the
+    /// paths and the contents it depends on.
+    pub fn get_key(&self, namespace: &str, parts: &[&[u8]]) -> String {
+        let mut all_parts: Vec<&[u8]> = vec![self.version.as_bytes(), namespace.as_bytes()];
+        all_parts.extend(parts);
+        get_hash(all_parts.as_slice())
+    }
+
+    fn get_path(&self, key: &str) -> PathBuf {
+        self.directory.join(&key[..2]).join(format!("{}.json", key))
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = fs::read(self.get_path(key))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok());
+        match value {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        value
+    }
+
+    /// Store an entry. It is written to a temporary file first so that a scan running at
+    /// the same time never reads a partial entry.
+    pub fn put<T: Serialize>(&self, key: &str, value: &T) {
+        let path = self.get_path(key);
+        let temporary_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| {
+                let content = serde_json::to_vec(value)?;
+                fs::write(&temporary_path, content)
+            })
+            .and_then(|_| fs::rename(&temporary_path, &path));
+        if written.is_err() {
+            let _ = fs::remove_file(&temporary_path);
+        }
+    }
+
+    /// Get the number of entries found and not found.
+    pub fn get_statistics(&self) -> (usize, usize) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache() {
+        let temp_directory = tempfile::tempdir().unwrap();
+        let directory = temp_directory.path();
+        let cache = Cache::new(directory, &[b"max-parent-depth=32"]);
+
+        let key = cache.get_key("maven", &[b"pom.xml", b"<project/>"]);
+        assert_ne!(
+            key,
+            cache.get_key("maven", &[b"pom.xml", b"<project></project>"])
+        );
+        assert_ne!(key, cache.get_key("maven", &[b"pom.x", b"ml<project/>"]));
+        assert_eq!(cache.get::<Vec<String>>(&key), None);
+
+        cache.put(&key, &vec!["junit".to_string()]);
+        assert_eq!(
+            cache.get::<Vec<String>>(&key),
+            Some(vec!["junit".to_string()])
+        );
+        assert_eq!(cache.get_statistics(), (1, 1));
+
+        // another configuration does not read the entries
+        let other = Cache::new(directory, &[b"max-parent-depth=1"]);
+        let other_key = other.get_key("maven", &[b"pom.xml", b"<project/>"]);
+        assert_ne!(key, other_key);
+        assert_eq!(other.get::<Vec<String>>(&other_key), None);
+    }
+}
@@ -1,36 +1,216 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 use anyhow::Result;
-use walkdir::WalkDir;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
 
-pub fn get_files(directory: &str) -> Result<Vec<PathBuf>> {
-    let mut files_to_return: Vec<PathBuf> = vec![];
+/// Directories never scanned, whatever the ecosystem: the metadata of the version control
+/// systems, including the `.git` of the submodules.
+pub const DEFAULT_IGNORED_DIRECTORIES: &[&str] = &[".git", ".hg", ".svn"];
 
-    // This is the directory that contains the .git files, we do not need to keep them.
-    let git_directory = format!("{}/.git", &directory);
+/// Which files of the scanned directory are listed.
+#[derive(Clone, Debug, Default)]
+pub struct FileFilter {
+    /// Only the files that match one of these globs are listed, all when none
+    includes: Option<GlobSet>,
+    /// The files and the directories that match one of these globs are skipped
+    excludes: Option<GlobSet>,
+    /// Skip the files ignored by the `.gitignore` and `.ignore` files
+    pub use_ignore_files: bool,
+    /// Maximum depth of the files, 1 for the files of the scanned directory only
+    pub max_depth: Option<usize>,
+    /// Files larger than this size (in bytes) are skipped
+    pub max_file_size: Option<u64>,
+}
 
-    let directories_to_walk: Vec<String> = vec![directory.to_string()];
+fn get_glob_set(globs: &[String]) -> Result<Option<GlobSet>> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).map_err(|e| anyhow::anyhow!("invalid glob {}: {}", glob, e))?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+/// Check if one of the directories of [path] (relative to [root]) is in [directories].
+pub fn is_in_directories(path: &Path, root: &Path, directories: &[&str]) -> bool {
+    let relative_path = path.strip_prefix(root).unwrap_or(path);
+    let mut components: Vec<Component> = relative_path.components().collect();
+    // the last component is the file
+    components.pop();
+    components.iter().any(|c| match c {
+        Component::Normal(name) => directories.iter().any(|d| name == d),
+        _ => false,
+    })
+}
 
-    for directory_to_walk in directories_to_walk {
-        for entry in WalkDir::new(directory_to_walk.as_str()) {
-            let dir_entry = entry?;
-            let entry = dir_entry.path();
+impl FileFilter {
+    /// The globs match the path relative to the scanned directory, e.g. `**/test/**`.
+    pub fn new(includes: &[String], excludes: &[String]) -> Result<Self> {
+        Ok(FileFilter {
+            includes: get_glob_set(includes)?,
+            excludes: get_glob_set(excludes)?,
+            ..FileFilter::default()
+        })
+    }
 
-            // we only include if this is a file and not a symlink
-            // we should NEVER follow symlink for security reason (an attacker could then
-            // attempt to add a symlink outside the repo and read content outside of the
-            // repo with a custom rule.
-            let mut should_include = entry.is_file() && !entry.is_symlink();
+    fn is_excluded(&self, relative_path: &Path) -> bool {
+        self.excludes
+            .as_ref()
+            .is_some_and(|e| e.is_match(relative_path))
+    }
 
-            // do not include the git directory.
-            if entry.starts_with(git_directory.as_str()) {
-                should_include = false;
+    /// Check a file with its path relative to the scanned directory. The ignore files are
+    /// only used when walking a directory.
+    pub fn is_included(&self, relative_path: &Path, size: Option<u64>) -> bool {
+        if is_in_directories(relative_path, Path::new(""), DEFAULT_IGNORED_DIRECTORIES)
+            || self.is_excluded(relative_path)
+        {
+            return false;
+        }
+        if let Some(includes) = &self.includes {
+            if !includes.is_match(relative_path) {
+                return false;
             }
+        }
+        if let Some(max_depth) = self.max_depth {
+            if relative_path.components().count() > max_depth {
+                return false;
+            }
+        }
+        match (self.max_file_size, size) {
+            (Some(max_file_size), Some(size)) => size <= max_file_size,
+            _ => true,
+        }
+    }
+}
 
-            if should_include {
-                files_to_return.push(entry.to_path_buf());
+pub fn get_files(directory: &str) -> Result<Vec<PathBuf>> {
+    get_filtered_files(directory, &FileFilter::default())
+}
+
+/// Get the files of the directory selected by the [filter].
+pub fn get_filtered_files(directory: &str, filter: &FileFilter) -> Result<Vec<PathBuf>> {
+    let mut files_to_return: Vec<PathBuf> = vec![];
+    let root = PathBuf::from(directory);
+
+    let directory_filter = filter.clone();
+    let directory_root = root.clone();
+    let walker = WalkBuilder::new(directory)
+        .standard_filters(false)
+        // we should NEVER follow symlink for security reason (an attacker could then
+        // attempt to add a symlink outside the repo and read content outside of the
+        // repo with a custom rule.
+        .follow_links(false)
+        .git_ignore(filter.use_ignore_files)
+        .git_exclude(filter.use_ignore_files)
+        .ignore(filter.use_ignore_files)
+        .parents(filter.use_ignore_files)
+        .require_git(false)
+        .max_depth(filter.max_depth)
+        .max_filesize(filter.max_file_size)
+        .sort_by_file_path(|a, b| a.cmp(b))
+        .filter_entry(move |entry| {
+            if !entry.file_type().is_some_and(|t| t.is_dir()) {
+                return true;
             }
+            let is_ignored = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| DEFAULT_IGNORED_DIRECTORIES.contains(&name));
+            let relative_path = entry
+                .path()
+                .strip_prefix(&directory_root)
+                .unwrap_or(entry.path());
+            !is_ignored && !directory_filter.is_excluded(relative_path)
+        })
+        .build();
+
+    for entry in walker {
+        let dir_entry = entry?;
+        // we only include if this is a file and not a symlink
+        if !dir_entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let entry = dir_entry.path();
+        let relative_path = entry.strip_prefix(&root).unwrap_or(entry);
+        // the size is checked by the walker
+        if filter.is_included(relative_path, None) {
+            files_to_return.push(entry.to_path_buf());
         }
     }
     Ok(files_to_return)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_get_filtered_files() {
+        let temp_directory = tempfile::tempdir().unwrap();
+        let root = temp_directory.path();
+        for directory in ["app/target", "app/src/test", "module/.git", "generated"] {
+            fs::create_dir_all(root.join(directory)).unwrap();
+        }
+        for file in [
+            "pom.xml",
+            "app/pom.xml",
+            "app/target/pom.xml",
+            "app/src/test/pom.xml",
+            "module/.git/config",
+            "generated/pom.xml",
+        ] {
+            fs::write(root.join(file), "<project/>").unwrap();
+        }
+        fs::write(root.join("large.json"), "x".repeat(1000)).unwrap();
+        fs::write(root.join(".gitignore"), "generated/\n").unwrap();
+
+        let relative_files = |filter: &FileFilter| -> Vec<String> {
+            get_filtered_files(root.display().to_string().as_str(), filter)
+                .unwrap()
+                .iter()
+                .map(|f| f.strip_prefix(root).unwrap().display().to_string())
+                .collect()
+        };
+
+        // the .git of a submodule is never listed
+        assert_eq!(
+            relative_files(&FileFilter::default()),
+            vec![
+                ".gitignore",
+                "app/pom.xml",
+                "app/src/test/pom.xml",
+                "app/target/pom.xml",
+                "generated/pom.xml",
+                "large.json",
+                "pom.xml",
+            ]
+        );
+
+        let mut filter = FileFilter::new(
+            &["**/*.xml".to_string()],
+            &["**/test".to_string(), "**/target/**".to_string()],
+        )
+        .unwrap();
+        filter.use_ignore_files = true;
+        assert_eq!(relative_files(&filter), vec!["app/pom.xml", "pom.xml"]);
+
+        let filter = FileFilter {
+            max_depth: Some(1),
+            max_file_size: Some(100),
+            ..FileFilter::default()
+        };
+        assert_eq!(relative_files(&filter), vec![".gitignore", "pom.xml"]);
+
+        assert!(is_in_directories(
+            &root.join("app/target/pom.xml"),
+            root,
+            &["target"]
+        ));
+        assert!(!is_in_directories(&root.join("target"), root, &["target"]));
+    }
+}
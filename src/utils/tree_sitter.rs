@@ -1,2 +1,5 @@
+pub mod json;
 pub mod language;
 pub mod tree;
+pub mod xml;
+pub mod yaml;
@@ -0,0 +1,46 @@
+use crate::utils::tree_sitter::tree::get_node_text;
+
+/// Get the value node of the attribute `key` of a JSON object node.
+pub fn get_object_attribute<'tree>(
+    code: &str,
+    object: &tree_sitter::Node<'tree>,
+    key: &str,
+) -> Option<tree_sitter::Node<'tree>> {
+    let mut cursor = object.walk();
+    let res = object
+        .named_children(&mut cursor)
+        .filter(|c| c.kind() == "pair")
+        .find(|pair| {
+            pair.child_by_field_name("key")
+                .and_then(|k| get_string_content(code, &k))
+                .map(|(k, _)| k == key)
+                .unwrap_or(false)
+        })
+        .and_then(|pair| pair.child_by_field_name("value"));
+    res
+}
+
+/// Get the content of a JSON string node, without the quotes. Also returns the node of
+/// the content to get the location of the value.
+pub fn get_string_content<'a, 'tree>(
+    code: &'a str,
+    node: &tree_sitter::Node<'tree>,
+) -> Option<(&'a str, tree_sitter::Node<'tree>)> {
+    if node.kind() != "string" {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let content = node
+        .named_children(&mut cursor)
+        .find(|c| c.kind() == "string_content")?;
+    Some((get_node_text(code, &content), content))
+}
+
+/// Shortcut to get the content of a string attribute of an object.
+pub fn get_object_string<'a, 'tree>(
+    code: &'a str,
+    object: &tree_sitter::Node<'tree>,
+    key: &str,
+) -> Option<(&'a str, tree_sitter::Node<'tree>)> {
+    get_object_attribute(code, object, key).and_then(|v| get_string_content(code, &v))
+}
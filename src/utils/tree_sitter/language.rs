@@ -1,17 +1,27 @@
 extern "C" {
     fn tree_sitter_xml() -> tree_sitter::Language;
-    // fn tree_sitter_json() -> tree_sitter::Language;
-    // fn tree_sitter_yaml() -> tree_sitter::Language;
+    fn tree_sitter_json() -> tree_sitter::Language;
+    fn tree_sitter_yaml() -> tree_sitter::Language;
 }
 
 pub fn get_tree_sitter_xml() -> tree_sitter::Language {
     unsafe { tree_sitter_xml() }
 }
 
-// pub fn get_tree_sitter_json() -> tree_sitter::Language {
-//     unsafe { tree_sitter_json() }
-// }
-//
-// pub fn get_tree_sitter_yaml() -> tree_sitter::Language {
-//     unsafe { tree_sitter_yaml() }
-// }
+pub fn get_tree_sitter_json() -> tree_sitter::Language {
+    unsafe { tree_sitter_json() }
+}
+
+pub fn get_tree_sitter_yaml() -> tree_sitter::Language {
+    unsafe { tree_sitter_yaml() }
+}
+
+/// Get a grammar by its name, used when the grammar comes from a configuration file.
+pub fn get_tree_sitter_language(name: &str) -> Option<tree_sitter::Language> {
+    match name.to_lowercase().as_str() {
+        "xml" => Some(get_tree_sitter_xml()),
+        "json" => Some(get_tree_sitter_json()),
+        "yaml" | "yml" => Some(get_tree_sitter_yaml()),
+        _ => None,
+    }
+}
@@ -1,8 +1,229 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::model::location::Location;
+use crate::model::position::LineIndex;
+
+thread_local! {
+    /// A parser cannot be shared between threads, each thread of the pool keeps its own.
+    static PARSER: RefCell<tree_sitter::Parser> = RefCell::new(tree_sitter::Parser::new());
+}
+
+/// The last tree of each file with its code, when the files are parsed again and again
+/// (`watch`): an edited file is parsed from its previous tree, only the edited part is
+/// parsed again. The scans that parse the files once do not use it.
+#[derive(Default)]
+pub struct TreeCache {
+    trees: Mutex<HashMap<(PathBuf, tree_sitter::Language), (String, tree_sitter::Tree)>>,
+}
+
+impl TreeCache {
+    /// Parse the [code] of the file at [path] from its previous tree when it was already
+    /// parsed, and keep the new tree for the next time.
+    fn get_tree(
+        &self,
+        path: &Path,
+        code: &str,
+        tree_sitter_language: &tree_sitter::Language,
+    ) -> Option<tree_sitter::Tree> {
+        let key = (path.to_path_buf(), tree_sitter_language.clone());
+        let previous = self
+            .trees
+            .lock()
+            .ok()
+            .and_then(|mut trees| trees.remove(&key));
+        let tree = match previous {
+            Some((previous_code, tree)) if previous_code == code => Some(tree),
+            Some((previous_code, mut tree)) => {
+                tree.edit(&get_edit(previous_code.as_str(), code));
+                parse(code, tree_sitter_language, Some(&tree))
+            }
+            None => parse(code, tree_sitter_language, None),
+        }?;
+        if let Ok(mut trees) = self.trees.lock() {
+            trees.insert(key, (code.to_string(), tree.clone()));
+        }
+        Some(tree)
+    }
+
+    /// Forget the trees of the files that are not in [paths], e.g. the deleted files.
+    pub fn retain(&self, paths: &HashSet<PathBuf>) {
+        if let Ok(mut trees) = self.trees.lock() {
+            trees.retain(|(path, _), _| paths.contains(path));
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.trees.lock().map_or(0, |trees| trees.len())
+    }
+}
+
+/// Get the position of an offset for tree-sitter: the row and the column in bytes.
+fn get_point(code: &str, offset: usize) -> tree_sitter::Point {
+    let before = &code.as_bytes()[..offset];
+    let line_start = before
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    tree_sitter::Point {
+        row: before.iter().filter(|b| **b == b'\n').count(),
+        column: offset - line_start,
+    }
+}
+
+/// Get the edit from [old_code] to [new_code]: the part between their common prefix and
+/// their common suffix is replaced.
+fn get_edit(old_code: &str, new_code: &str) -> tree_sitter::InputEdit {
+    let mut prefix = old_code
+        .bytes()
+        .zip(new_code.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old_code.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let max_suffix = old_code.len().min(new_code.len()) - prefix;
+    let mut suffix = old_code
+        .bytes()
+        .rev()
+        .zip(new_code.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old_code.is_char_boundary(old_code.len() - suffix) {
+        suffix -= 1;
+    }
+
+    let old_end_byte = old_code.len() - suffix;
+    let new_end_byte = new_code.len() - suffix;
+    tree_sitter::InputEdit {
+        start_byte: prefix,
+        old_end_byte,
+        new_end_byte,
+        start_position: get_point(old_code, prefix),
+        old_end_position: get_point(old_code, old_end_byte),
+        new_end_position: get_point(new_code, new_end_byte),
+    }
+}
+
+/// Parse the [code] of the file at [path], from its previous tree in [trees] when it is
+/// set (`watch`).
 pub fn get_tree(
+    path: &Path,
     code: &str,
     tree_sitter_language: &tree_sitter::Language,
+    trees: Option<&TreeCache>,
 ) -> Option<tree_sitter::Tree> {
-    let mut tree_sitter_parser = tree_sitter::Parser::new();
-    tree_sitter_parser.set_language(tree_sitter_language).ok()?;
-    tree_sitter_parser.parse(code, None)
+    match trees {
+        Some(trees) => trees.get_tree(path, code, tree_sitter_language),
+        None => parse(code, tree_sitter_language, None),
+    }
+}
+
+fn parse(
+    code: &str,
+    tree_sitter_language: &tree_sitter::Language,
+    previous_tree: Option<&tree_sitter::Tree>,
+) -> Option<tree_sitter::Tree> {
+    PARSER.with_borrow_mut(|tree_sitter_parser| {
+        tree_sitter_parser.set_language(tree_sitter_language).ok()?;
+        tree_sitter_parser.parse(code, previous_tree)
+    })
+}
+
+/// Get the text of a node from the code that was used to build the tree.
+pub fn get_node_text<'a>(code: &'a str, node: &tree_sitter::Node) -> &'a str {
+    &code[node.start_byte()..node.end_byte()]
+}
+
+/// Get the [Location] of a node in the file `file`, with the [LineIndex] of the code that
+/// was used to build the tree.
+pub fn get_node_location(
+    file: &str,
+    line_index: &LineIndex,
+    node: &tree_sitter::Node,
+) -> anyhow::Result<Location> {
+    Ok(Location {
+        file: file.to_string(),
+        start: line_index.get_position(node.start_byte())?,
+        end: line_index.get_position(node.end_byte())?,
+    })
+}
+
+/// Get the node captured as `@name` in a query match.
+pub fn get_captured_node<'tree>(
+    query: &tree_sitter::Query,
+    query_match: &tree_sitter::QueryMatch<'_, 'tree>,
+    name: &str,
+) -> Option<tree_sitter::Node<'tree>> {
+    let index = query.capture_index_for_name(name)?;
+    query_match
+        .captures
+        .iter()
+        .find(|c| c.index == index)
+        .map(|c| c.node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tree_sitter::language::get_tree_sitter_xml;
+
+    #[test]
+    fn test_incremental_parsing() {
+        let trees = TreeCache::default();
+        let language = get_tree_sitter_xml();
+        let path = Path::new("incremental/pom.xml");
+        let versions = [
+            "<project>\n  <version>1.0</version>\n</project>\n",
+            "<project>\n  <version>1.0.1</version>\n</project>\n",
+            "<project>\n  <groupId>é</groupId>\n  <version>2</version>\n</project>\n",
+            "<project>\n  <groupId>è</groupId>\n</project>\n",
+        ];
+        for code in versions {
+            let tree = get_tree(path, code, &language, Some(&trees)).unwrap();
+            let expected = parse(code, &language, None).unwrap();
+            assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+            assert_eq!(tree.root_node().end_byte(), code.len());
+        }
+        assert_eq!(trees.len(), 1);
+
+        // the tree of a file that is not scanned anymore is dropped
+        trees.retain(&HashSet::from([PathBuf::from("other/pom.xml")]));
+        assert_eq!(trees.len(), 0);
+    }
+
+    #[test]
+    fn test_get_edit() {
+        let edit = get_edit("a\nbc\nd", "a\nbxyc\nd");
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (3, 3, 5)
+        );
+        assert_eq!(
+            edit.start_position,
+            tree_sitter::Point { row: 1, column: 1 }
+        );
+        assert_eq!(
+            edit.new_end_position,
+            tree_sitter::Point { row: 1, column: 3 }
+        );
+
+        // the common prefix and suffix do not overlap
+        let edit = get_edit("aa", "aaa");
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (2, 2, 3)
+        );
+
+        // the edit does not split a character
+        let edit = get_edit("é", "è");
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (0, 2, 2)
+        );
+    }
 }
@@ -0,0 +1,123 @@
+use crate::utils::tree_sitter::language::get_tree_sitter_xml;
+use crate::utils::tree_sitter::tree::get_node_text;
+
+/// Compile a query of the XML grammar. The queries are constants of the producers,
+/// compiled once in their context.
+pub fn get_xml_query(source: &str) -> tree_sitter::Query {
+    tree_sitter::Query::new(&get_tree_sitter_xml(), source).expect("got XML query")
+}
+
+/// Get a query of the elements named one of [names], wherever they are in the document:
+/// the tag (`STag` or `EmptyElemTag`, with the attributes) is captured as `@tag` and the
+/// whole element as `@element`.
+pub fn get_element_query(names: &[&str]) -> tree_sitter::Query {
+    let names: Vec<String> = names.iter().map(|n| format!("\"{}\"", n)).collect();
+    get_xml_query(&format!(
+        r#"
+(element
+  [
+    (EmptyElemTag (Name) @name)
+    (STag (Name) @name)
+  ] @tag
+  (#any-of? @name {})
+) @element
+"#,
+        names.join(" ")
+    ))
+}
+
+/// Decode the text of an XML document: the predefined entities (e.g. `&amp;`) and the
+/// character references (e.g. `&#38;` or `&#x26;`) are replaced, an unknown entity is
+/// kept as is.
+pub fn decode_xml_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').and_then(|end| {
+            let character = match &rest[1..end] {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "amp" => Some('&'),
+                reference => reference
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| reference.strip_prefix('#').map(|d| d.parse::<u32>()))
+                    .and_then(|code| code.ok())
+                    .and_then(char::from_u32),
+            };
+            character.map(|c| (c, end))
+        });
+        match decoded {
+            Some((character, end)) => {
+                result.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Get the value of an XML attribute without the quotes, the entities are replaced.
+fn get_attribute_value(code: &str, node: &tree_sitter::Node) -> String {
+    decode_xml_text(get_node_text(code, node).trim_matches(|c| c == '"' || c == '\''))
+}
+
+/// Get the attributes of a tag (`STag` or `EmptyElemTag`) as (name, value, value node).
+pub fn get_tag_attributes<'tree>(
+    code: &str,
+    tag: &tree_sitter::Node<'tree>,
+) -> Vec<(String, String, tree_sitter::Node<'tree>)> {
+    let mut cursor = tag.walk();
+    let res = tag
+        .named_children(&mut cursor)
+        .filter(|c| c.kind() == "Attribute")
+        .filter_map(|attribute| {
+            let mut attribute_cursor = attribute.walk();
+            let children: Vec<tree_sitter::Node> =
+                attribute.named_children(&mut attribute_cursor).collect();
+            let name = children.iter().find(|c| c.kind() == "Name")?;
+            let value = children.iter().find(|c| c.kind() == "AttValue")?;
+            Some((
+                get_node_text(code, name).to_string(),
+                get_attribute_value(code, value),
+                *value,
+            ))
+        })
+        .collect();
+    res
+}
+
+/// Get the value of the attribute `name` of a tag, with the node of the value.
+pub fn get_tag_attribute<'tree>(
+    code: &str,
+    tag: &tree_sitter::Node<'tree>,
+    name: &str,
+) -> Option<(String, tree_sitter::Node<'tree>)> {
+    get_tag_attributes(code, tag)
+        .into_iter()
+        .find(|(n, _, _)| n == name)
+        .map(|(_, v, node)| (v, node))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_xml_text() {
+        assert_eq!(decode_xml_text("a &amp; b"), "a & b");
+        assert_eq!(decode_xml_text("&amp;lt;"), "&lt;");
+        assert_eq!(decode_xml_text("&lt;&gt;&quot;&apos;"), "<>\"'");
+        assert_eq!(decode_xml_text("&#38;&#x26;&#X26;"), "&&&#X26;");
+        assert_eq!(decode_xml_text("&unknown; & &#xzz;"), "&unknown; & &#xzz;");
+    }
+}
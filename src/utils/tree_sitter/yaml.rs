@@ -0,0 +1,119 @@
+use crate::utils::tree_sitter::tree::get_node_text;
+
+/// Get the first named child of a node that has the kind `kind`.
+fn get_child_of_kind<'tree>(
+    node: &tree_sitter::Node<'tree>,
+    kind: &str,
+) -> Option<tree_sitter::Node<'tree>> {
+    let mut cursor = node.walk();
+    let res = node.named_children(&mut cursor).find(|c| c.kind() == kind);
+    res
+}
+
+/// Get the value of a scalar: quotes are removed for quoted scalars. The node can be a
+/// `flow_node` or the scalar itself. Returns `None` if the node is not a scalar.
+pub fn get_scalar_value(code: &str, node: &tree_sitter::Node) -> Option<String> {
+    let scalar = if node.kind() == "flow_node" {
+        let mut cursor = node.walk();
+        let res = node.named_children(&mut cursor).last();
+        res?
+    } else {
+        *node
+    };
+
+    let text = get_node_text(code, &scalar);
+    match scalar.kind() {
+        "plain_scalar" => Some(text.trim().to_string()),
+        "single_quote_scalar" => Some(text.trim_matches('\'').replace("''", "'").to_string()),
+        "double_quote_scalar" => Some(
+            text.trim_matches('"')
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\"),
+        ),
+        _ => None,
+    }
+}
+
+/// Get the mapping held by a node (`block_node` or `flow_node`), if any.
+fn get_mapping<'tree>(node: &tree_sitter::Node<'tree>) -> Option<tree_sitter::Node<'tree>> {
+    match node.kind() {
+        "block_mapping" | "flow_mapping" => Some(*node),
+        "block_node" => get_child_of_kind(node, "block_mapping"),
+        "flow_node" => get_child_of_kind(node, "flow_mapping"),
+        _ => None,
+    }
+}
+
+/// Get all the (key, value) pairs of a mapping. The value is `None` when the key has no
+/// value (e.g. `key:`).
+pub fn get_mapping_pairs<'tree>(
+    node: &tree_sitter::Node<'tree>,
+) -> Vec<(tree_sitter::Node<'tree>, Option<tree_sitter::Node<'tree>>)> {
+    let Some(mapping) = get_mapping(node) else {
+        return vec![];
+    };
+    let mut cursor = mapping.walk();
+    let res = mapping
+        .named_children(&mut cursor)
+        .filter(|c| c.kind() == "block_mapping_pair" || c.kind() == "flow_pair")
+        .filter_map(|p| {
+            p.child_by_field_name("key")
+                .map(|k| (k, p.child_by_field_name("value")))
+        })
+        .collect();
+    res
+}
+
+/// Get the value node of `key` in a mapping.
+pub fn get_mapping_value<'tree>(
+    code: &str,
+    node: &tree_sitter::Node<'tree>,
+    key: &str,
+) -> Option<tree_sitter::Node<'tree>> {
+    get_mapping_pairs(node)
+        .into_iter()
+        .find(|(k, _)| get_scalar_value(code, k).as_deref() == Some(key))
+        .and_then(|(_, v)| v)
+}
+
+/// Shortcut to get the scalar value of `key` in a mapping, with the node of the value.
+pub fn get_mapping_scalar<'tree>(
+    code: &str,
+    node: &tree_sitter::Node<'tree>,
+    key: &str,
+) -> Option<(String, tree_sitter::Node<'tree>)> {
+    let value = get_mapping_value(code, node, key)?;
+    get_scalar_value(code, &value).map(|v| (v, value))
+}
+
+/// Get the items of a sequence held by a node (`block_node` or `flow_node`).
+pub fn get_sequence_items<'tree>(node: &tree_sitter::Node<'tree>) -> Vec<tree_sitter::Node<'tree>> {
+    let (sequence, item_kind) = match node.kind() {
+        "block_node" => (
+            get_child_of_kind(node, "block_sequence"),
+            "block_sequence_item",
+        ),
+        "flow_node" => (get_child_of_kind(node, "flow_sequence"), "flow_node"),
+        _ => (None, ""),
+    };
+    let Some(s) = sequence else {
+        return vec![];
+    };
+    let mut cursor = s.walk();
+    let res = s
+        .named_children(&mut cursor)
+        .filter(|c| c.kind() == item_kind)
+        .filter_map(|c| {
+            if item_kind == "block_sequence_item" {
+                let mut item_cursor = c.walk();
+                let res = c
+                    .named_children(&mut item_cursor)
+                    .find(|n| n.kind() != "comment");
+                res
+            } else {
+                Some(c)
+            }
+        })
+        .collect();
+    res
+}
@@ -0,0 +1,137 @@
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::utils::file_utils::FileFilter;
+use crate::utils::vfs::archive::ArchiveVfs;
+use crate::utils::vfs::disk::DiskVfs;
+use crate::utils::vfs::git::GitVfs;
+use crate::utils::vfs::multi::MultiVfs;
+
+pub mod archive;
+pub mod disk;
+pub mod git;
+pub mod multi;
+
+/// The files to scan. Producers read through this trait rather than `std::fs` so that a
+/// directory, an archive or a git commit can be scanned the same way.
+///
+/// All the paths start with [Vfs::root], as if the files were extracted there: the
+/// locations in the SBOM are the same for a directory and for an archive of it.
+pub trait Vfs: Send + Sync {
+    /// The path all the files start with (the directory, the archive or the repository)
+    fn root(&self) -> &Path;
+    /// The root of a file, the locations are relative to it. Only differs from
+    /// [Vfs::root] when several directories are scanned.
+    fn root_of(&self, _path: &Path) -> &Path {
+        self.root()
+    }
+    /// Get all the regular files, never the symbolic links.
+    fn get_files(&self) -> anyhow::Result<Vec<PathBuf>>;
+    fn read(&self, path: &Path) -> anyhow::Result<Vec<u8>>;
+    fn is_file(&self, path: &Path) -> bool;
+    /// Resolve `.` and `..` components and fail if the file does not exist.
+    fn canonicalize(&self, path: &Path) -> anyhow::Result<PathBuf>;
+
+    /// Resolve a path referenced by a scanned file (e.g. the `relativePath` of a
+    /// `pom.xml`) and fail if it is outside of [Vfs::root]. The files read from the
+    /// repository are not trusted, a reference must never be used to read another file
+    /// of the disk.
+    fn resolve_path(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        let resolved = self.canonicalize(path)?;
+        if !resolved.starts_with(self.root()) {
+            anyhow::bail!("{} is outside of {}", path.display(), self.root().display());
+        }
+        Ok(resolved)
+    }
+
+    fn read_to_string(&self, path: &Path) -> anyhow::Result<String> {
+        Ok(String::from_utf8(self.read(path)?)?)
+    }
+
+    /// Read the first bytes of a file, to detect its format.
+    fn read_prefix(&self, path: &Path, length: usize) -> anyhow::Result<Vec<u8>> {
+        let mut content = self.read(path)?;
+        content.truncate(length);
+        Ok(content)
+    }
+}
+
+/// Resolve `.` and `..` without accessing the filesystem.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match result.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    result.pop();
+                }
+                // `/..` is `/`
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => result.push(component),
+            },
+            _ => result.push(component),
+        }
+    }
+    result
+}
+
+/// Get the filesystem for a path given with `--directory`: a directory or an archive,
+/// or a git revision of the repository when `--git-ref` is given. The files of a
+/// directory can also reference the files of the [allowed_roots]. Only the files
+/// selected by the [filter] are listed.
+fn get_root_vfs(
+    path: &str,
+    git_ref: Option<&str>,
+    allowed_roots: &[String],
+    filter: FileFilter,
+) -> anyhow::Result<Arc<dyn Vfs>> {
+    let path = Path::new(path);
+    if let Some(reference) = git_ref {
+        Ok(Arc::new(GitVfs::new(path, reference)?.with_filter(filter)))
+    } else if path.is_file() {
+        Ok(Arc::new(ArchiveVfs::open(path)?.with_filter(filter)))
+    } else {
+        Ok(Arc::new(
+            DiskVfs::new(path)
+                .with_allowed_roots(allowed_roots.iter().map(PathBuf::from).collect())
+                .with_filter(filter),
+        ))
+    }
+}
+
+/// Get the filesystem for the paths given with `--directory`, the files of all the paths
+/// are scanned together.
+pub fn get_vfs(
+    paths: &[String],
+    git_ref: Option<&str>,
+    allowed_roots: &[String],
+    filter: FileFilter,
+) -> anyhow::Result<Arc<dyn Vfs>> {
+    let mut roots = paths
+        .iter()
+        .map(|p| get_root_vfs(p, git_ref, allowed_roots, filter.clone()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    match roots.len() {
+        0 => anyhow::bail!("no directory to scan"),
+        1 => Ok(roots.remove(0)),
+        _ => Ok(Arc::new(MultiVfs::new(roots))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path(Path::new("repo/module/../parent/./pom.xml")),
+            PathBuf::from("repo/parent/pom.xml")
+        );
+        assert_eq!(
+            normalize_path(Path::new("/repo/../../pom.xml")),
+            PathBuf::from("/pom.xml")
+        );
+    }
+}
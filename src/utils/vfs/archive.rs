@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use flate2::read::GzDecoder;
+
+use crate::utils::file_utils::FileFilter;
+use crate::utils::vfs::{normalize_path, Vfs};
+
+/// The files of a tarball (`.tar`, `.tar.gz`, `.tgz`) or a zip archive (`.zip`, `.jar`,
+/// `.war`, ...), read in memory without extracting anything to disk.
+#[derive(Clone, Debug)]
+pub struct ArchiveVfs {
+    root: PathBuf,
+    files: BTreeMap<PathBuf, Vec<u8>>,
+    filter: FileFilter,
+}
+
+/// Get the path of an entry under the root, rejecting absolute paths and paths that
+/// go up out of the archive.
+fn get_entry_path(root: &Path, entry: &Path) -> Option<PathBuf> {
+    let normalized = normalize_path(entry);
+    if normalized.is_absolute() || normalized.starts_with("..") {
+        return None;
+    }
+    Some(root.join(normalized))
+}
+
+impl ArchiveVfs {
+    /// Open an archive, its format is guessed from its content.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let content =
+            fs::read(path).with_context(|| format!("cannot read archive {}", path.display()))?;
+        Self::from_bytes(path, content)
+    }
+
+    pub fn from_bytes(root: &Path, content: Vec<u8>) -> anyhow::Result<Self> {
+        let files = if content.starts_with(b"PK\x03\x04") {
+            Self::read_zip(root, content)?
+        } else if content.starts_with(&[0x1f, 0x8b]) {
+            let mut decompressed = vec![];
+            GzDecoder::new(content.as_slice()).read_to_end(&mut decompressed)?;
+            Self::read_tar(root, decompressed.as_slice())?
+        } else {
+            Self::read_tar(root, content.as_slice())?
+        };
+
+        Ok(ArchiveVfs {
+            root: root.to_path_buf(),
+            files,
+            filter: FileFilter::default(),
+        })
+    }
+
+    /// List only the files selected by the [filter], the ignore files are not used.
+    pub fn with_filter(self, filter: FileFilter) -> Self {
+        ArchiveVfs { filter, ..self }
+    }
+
+    fn read_tar(root: &Path, content: &[u8]) -> anyhow::Result<BTreeMap<PathBuf, Vec<u8>>> {
+        let mut files = BTreeMap::new();
+        let mut archive = tar::Archive::new(content);
+        for entry in archive
+            .entries()
+            .map_err(|e| anyhow!("invalid archive: {}", e))?
+        {
+            let mut entry = entry?;
+            // like for directories, links are not followed
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let Some(path) = get_entry_path(root, &entry.path()?) else {
+                continue;
+            };
+            let mut data = vec![];
+            entry.read_to_end(&mut data)?;
+            files.insert(path, data);
+        }
+        Ok(files)
+    }
+
+    fn read_zip(root: &Path, content: Vec<u8>) -> anyhow::Result<BTreeMap<PathBuf, Vec<u8>>> {
+        let mut files = BTreeMap::new();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(content))?;
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+            if !entry.is_file() {
+                continue;
+            }
+            let Some(path) = entry.enclosed_name().and_then(|p| get_entry_path(root, &p)) else {
+                continue;
+            };
+            let mut data = vec![];
+            entry.read_to_end(&mut data)?;
+            files.insert(path, data);
+        }
+        Ok(files)
+    }
+}
+
+impl Vfs for ArchiveVfs {
+    fn root(&self) -> &Path {
+        self.root.as_path()
+    }
+
+    fn get_files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        Ok(self
+            .files
+            .iter()
+            .filter(|(p, content)| {
+                let relative_path = p.strip_prefix(&self.root).unwrap_or(p);
+                self.filter
+                    .is_included(relative_path, Some(content.len() as u64))
+            })
+            .map(|(p, _)| p.clone())
+            .collect())
+    }
+
+    fn read(&self, path: &Path) -> anyhow::Result<Vec<u8>> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or(anyhow!("{} is not in the archive", path.display()))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        let normalized = normalize_path(path);
+        if self.files.contains_key(&normalized)
+            || self.files.keys().any(|f| f.starts_with(&normalized))
+        {
+            Ok(normalized)
+        } else {
+            Err(anyhow!("{} is not in the archive", path.display()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    #[test]
+    fn test_tar_gz() {
+        let mut builder = tar::Builder::new(vec![]);
+        let content = b"{\"name\": \"app\"}";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, "app/package.json", content.as_slice())
+            .unwrap();
+        let mut link = tar::Header::new_gnu();
+        link.set_entry_type(tar::EntryType::Symlink);
+        link.set_size(0);
+        builder
+            .append_link(&mut link, "app/link.json", "/etc/passwd")
+            .unwrap();
+        let tar = builder.into_inner().unwrap();
+        let mut encoder = GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(&tar).unwrap();
+
+        let root = Path::new("release.tar.gz");
+        let vfs = ArchiveVfs::from_bytes(root, encoder.finish().unwrap()).unwrap();
+        let path = root.join("app/package.json");
+        assert_eq!(vfs.get_files().unwrap(), vec![path.clone()]);
+        assert_eq!(vfs.read(&path).unwrap(), content);
+        assert_eq!(
+            vfs.canonicalize(&root.join("app/lib/../package.json"))
+                .unwrap(),
+            path
+        );
+        assert!(vfs.canonicalize(&root.join("app/missing.json")).is_err());
+    }
+
+    #[test]
+    fn test_zip() {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+        writer
+            .start_file("lib/pom.xml", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"<project/>").unwrap();
+        let content = writer.finish().unwrap().into_inner();
+
+        let root = Path::new("app.jar");
+        let vfs = ArchiveVfs::from_bytes(root, content).unwrap();
+        assert!(vfs.is_file(&root.join("lib/pom.xml")));
+        assert_eq!(
+            vfs.read_to_string(&root.join("lib/pom.xml")).unwrap(),
+            "<project/>"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::utils::file_utils::{get_filtered_files, FileFilter};
+use crate::utils::vfs::Vfs;
+
+/// The files of a directory on the local disk.
+#[derive(Clone, Debug)]
+pub struct DiskVfs {
+    root: PathBuf,
+    /// Directories outside of the root that the scanned files can reference
+    allowed_roots: Vec<PathBuf>,
+    filter: FileFilter,
+}
+
+impl DiskVfs {
+    pub fn new(root: &Path) -> Self {
+        DiskVfs {
+            root: root.to_path_buf(),
+            allowed_roots: vec![],
+            filter: FileFilter::default(),
+        }
+    }
+
+    /// List only the files selected by the [filter].
+    pub fn with_filter(self, filter: FileFilter) -> Self {
+        DiskVfs { filter, ..self }
+    }
+
+    /// Let the scanned files reference the files of [allowed_roots], e.g. a parent
+    /// `pom.xml` in another checkout.
+    pub fn with_allowed_roots(self, allowed_roots: Vec<PathBuf>) -> Self {
+        DiskVfs {
+            allowed_roots,
+            ..self
+        }
+    }
+}
+
+impl Vfs for DiskVfs {
+    fn root(&self) -> &Path {
+        self.root.as_path()
+    }
+
+    fn get_files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        get_filtered_files(&self.root.display().to_string(), &self.filter)
+    }
+
+    fn read(&self, path: &Path) -> anyhow::Result<Vec<u8>> {
+        Ok(fs::read(path)?)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn canonicalize(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        Ok(fs::canonicalize(path)?)
+    }
+
+    /// The symbolic links are followed: a link to a file outside of the root is rejected.
+    /// A path in the root is returned under [Vfs::root], as the files of
+    /// [Vfs::get_files], a path in an allowed root is returned canonicalized.
+    fn resolve_path(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        let resolved = fs::canonicalize(path)?;
+        if let Ok(root) = fs::canonicalize(&self.root) {
+            if let Ok(relative_path) = resolved.strip_prefix(&root) {
+                return Ok(self.root.join(relative_path));
+            }
+        }
+        for allowed_root in &self.allowed_roots {
+            if fs::canonicalize(allowed_root).is_ok_and(|r| resolved.starts_with(r)) {
+                return Ok(resolved);
+            }
+        }
+        anyhow::bail!(
+            "{} is outside of {} and of the allowed roots",
+            path.display(),
+            self.root.display()
+        )
+    }
+
+    fn read_to_string(&self, path: &Path) -> anyhow::Result<String> {
+        Ok(fs::read_to_string(path)?)
+    }
+
+    fn read_prefix(&self, path: &Path, length: usize) -> anyhow::Result<Vec<u8>> {
+        let mut content = vec![];
+        fs::File::open(path)?
+            .take(length as u64)
+            .read_to_end(&mut content)?;
+        Ok(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_path() {
+        let temp_directory = tempfile::tempdir().unwrap();
+        let directory = temp_directory.path();
+        let root = directory.join("repository");
+        let outside = directory.join("outside");
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(root.join("app/pom.xml"), "<project/>").unwrap();
+        fs::write(outside.join("pom.xml"), "<project/>").unwrap();
+        std::os::unix::fs::symlink(outside.join("pom.xml"), root.join("link.xml")).unwrap();
+
+        let vfs = DiskVfs::new(&root);
+        assert_eq!(
+            vfs.resolve_path(&root.join("app/../app/pom.xml")).unwrap(),
+            root.join("app/pom.xml")
+        );
+        assert!(vfs.resolve_path(&root.join("../outside/pom.xml")).is_err());
+        assert!(vfs.resolve_path(&root.join("link.xml")).is_err());
+        assert!(vfs.resolve_path(&root.join("missing.xml")).is_err());
+        assert!(!vfs.get_files().unwrap().contains(&root.join("link.xml")));
+
+        let vfs = DiskVfs::new(&root).with_allowed_roots(vec![outside.clone()]);
+        assert_eq!(
+            vfs.resolve_path(&root.join("link.xml")).unwrap(),
+            fs::canonicalize(outside.join("pom.xml")).unwrap()
+        );
+    }
+}
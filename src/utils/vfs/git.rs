@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context};
+
+use crate::utils::file_utils::FileFilter;
+use crate::utils::vfs::{normalize_path, Vfs};
+
+/// Mode of the symbolic links in a git tree, they are not followed.
+const MODE_SYMLINK: &str = "120000";
+
+/// The files of a commit of a git repository, read from the git objects without checking
+/// out anything. The `git` command must be installed. When the root is a subdirectory of
+/// the repository, only the files of this subdirectory are scanned.
+#[derive(Clone, Debug)]
+pub struct GitVfs {
+    root: PathBuf,
+    commit: String,
+    /// The id of the blob of each file
+    blobs: BTreeMap<PathBuf, String>,
+    /// The size of each file
+    sizes: BTreeMap<PathBuf, u64>,
+    filter: FileFilter,
+    /// The process that reads the blobs, started by the first read and shared by the
+    /// threads
+    cat_file: Arc<Mutex<Option<CatFile>>>,
+}
+
+/// A `git cat-file --batch` process: one process reads all the blobs of a scan. Each
+/// request is an object id on a line, the answer is `<id> <type> <size>` on a line, then
+/// the content and a new line.
+#[derive(Debug)]
+struct CatFile {
+    process: Child,
+    input: Option<ChildStdin>,
+    output: BufReader<ChildStdout>,
+}
+
+impl CatFile {
+    fn start(repository: &Path) -> anyhow::Result<Self> {
+        let mut process = Command::new("git")
+            .arg("-C")
+            .arg(repository)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("cannot run git")?;
+        let (Some(input), Some(output)) = (process.stdin.take(), process.stdout.take()) else {
+            anyhow::bail!("cannot read the output of git cat-file");
+        };
+        Ok(CatFile {
+            process,
+            input: Some(input),
+            output: BufReader::new(output),
+        })
+    }
+
+    /// Read the first [length] bytes of a blob (all of it when None). The rest of the
+    /// blob is skipped without being kept in memory.
+    fn read(&mut self, id: &str, length: Option<usize>) -> anyhow::Result<Vec<u8>> {
+        let input = self
+            .input
+            .as_mut()
+            .ok_or(anyhow!("git cat-file is closed"))?;
+        writeln!(input, "{}", id)?;
+        input.flush()?;
+
+        let mut header = String::new();
+        self.output.read_line(&mut header)?;
+        let size: u64 = match header.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [_, "blob", size] => size.parse()?,
+            _ => anyhow::bail!("cannot read blob {}: {}", id, header.trim()),
+        };
+
+        let kept = length.map_or(size, |l| size.min(l as u64));
+        let mut content = Vec::with_capacity(kept as usize);
+        (&mut self.output).take(kept).read_to_end(&mut content)?;
+        // the rest of the blob and the new line after it
+        io::copy(
+            &mut (&mut self.output).take(size - kept + 1),
+            &mut io::sink(),
+        )?;
+        Ok(content)
+    }
+}
+
+impl Drop for CatFile {
+    fn drop(&mut self) {
+        // git exits at the end of its input
+        self.input.take();
+        let _ = self.process.wait();
+    }
+}
+
+fn run_git(repository: &Path, arguments: &[&str]) -> anyhow::Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(arguments)
+        .output()
+        .context("cannot run git")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            arguments.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+impl GitVfs {
+    /// Open the commit a reference (branch, tag or commit) points to.
+    pub fn new(repository: &Path, reference: &str) -> anyhow::Result<Self> {
+        // a reference is never an option of the git command
+        if reference.starts_with('-') {
+            anyhow::bail!("invalid git reference {}", reference);
+        }
+        let commit = String::from_utf8(run_git(
+            repository,
+            &[
+                "rev-parse",
+                "--verify",
+                "--end-of-options",
+                &format!("{}^{{commit}}", reference),
+            ],
+        )?)?
+        .trim()
+        .to_string();
+
+        // the path of the root in the repository, e.g. `services/api/`
+        let prefix = String::from_utf8(run_git(repository, &["rev-parse", "--show-prefix"])?)?
+            .trim()
+            .to_string();
+
+        // each entry is `<mode> <type> <id> <size>\t<path>`
+        let mut blobs = BTreeMap::new();
+        let mut sizes = BTreeMap::new();
+        let tree = run_git(
+            repository,
+            &["ls-tree", "-r", "-l", "-z", "--full-tree", &commit],
+        )?;
+        for entry in tree.split(|b| *b == 0).filter(|e| !e.is_empty()) {
+            let entry = String::from_utf8_lossy(entry);
+            let Some((metadata, path)) = entry.split_once('\t') else {
+                continue;
+            };
+            let Some(path) = path.strip_prefix(prefix.as_str()) else {
+                continue;
+            };
+            let fields: Vec<&str> = metadata.split_whitespace().collect();
+            if let [mode, "blob", id, size] = fields.as_slice() {
+                if *mode != MODE_SYMLINK {
+                    blobs.insert(repository.join(path), id.to_string());
+                    if let Ok(size) = size.parse() {
+                        sizes.insert(repository.join(path), size);
+                    }
+                }
+            }
+        }
+
+        Ok(GitVfs {
+            root: repository.to_path_buf(),
+            commit,
+            blobs,
+            sizes,
+            filter: FileFilter::default(),
+            cat_file: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// List only the files selected by the [filter], the ignore files are not used.
+    pub fn with_filter(self, filter: FileFilter) -> Self {
+        GitVfs { filter, ..self }
+    }
+
+    /// The commit the reference points to
+    pub fn get_commit(&self) -> &str {
+        self.commit.as_str()
+    }
+
+    /// Read the first [length] bytes of a file (all of it when None) with the
+    /// `git cat-file` process of the VFS.
+    fn read_blob(&self, path: &Path, length: Option<usize>) -> anyhow::Result<Vec<u8>> {
+        let id = self.blobs.get(path).ok_or(anyhow!(
+            "{} is not in commit {}",
+            path.display(),
+            self.commit
+        ))?;
+        let mut cat_file = self.cat_file.lock().unwrap_or_else(|e| e.into_inner());
+        let process = match cat_file.as_mut() {
+            Some(process) => process,
+            None => cat_file.insert(CatFile::start(&self.root)?),
+        };
+        let content = process.read(id, length);
+        if content.is_err() {
+            // the output may be out of sync, the next read starts another process
+            *cat_file = None;
+        }
+        content
+    }
+}
+
+impl Vfs for GitVfs {
+    fn root(&self) -> &Path {
+        self.root.as_path()
+    }
+
+    fn get_files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        Ok(self
+            .blobs
+            .keys()
+            .filter(|p| {
+                let relative_path = p.strip_prefix(&self.root).unwrap_or(p);
+                self.filter
+                    .is_included(relative_path, self.sizes.get(*p).copied())
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn read(&self, path: &Path) -> anyhow::Result<Vec<u8>> {
+        self.read_blob(path, None)
+    }
+
+    fn read_prefix(&self, path: &Path, length: usize) -> anyhow::Result<Vec<u8>> {
+        self.read_blob(path, Some(length))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.blobs.contains_key(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        let normalized = normalize_path(path);
+        if self.blobs.contains_key(&normalized)
+            || self.blobs.keys().any(|f| f.starts_with(&normalized))
+        {
+            Ok(normalized)
+        } else {
+            Err(anyhow!(
+                "{} is not in commit {}",
+                path.display(),
+                self.commit
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_git_vfs() {
+        let directory = tempfile::tempdir().unwrap();
+        let repository = directory.path();
+        fs::create_dir_all(repository.join("app")).unwrap();
+        let git = |arguments: &[&str]| {
+            run_git(
+                repository,
+                &[
+                    &["-c", "user.name=test", "-c", "user.email=test@example.com"],
+                    arguments,
+                ]
+                .concat(),
+            )
+            .unwrap()
+        };
+        git(&["init", "-q"]);
+        fs::write(repository.join("app/pom.xml"), "<project>1</project>").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "first"]);
+        git(&["tag", "v1"]);
+        fs::write(repository.join("app/pom.xml"), "<project>2</project>").unwrap();
+        fs::write(repository.join("README.md"), "readme").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "second"]);
+
+        let vfs = GitVfs::new(&repository.join("app"), "HEAD~1").unwrap();
+        assert_eq!(
+            vfs.get_files().unwrap(),
+            vec![repository.join("app").join("pom.xml")]
+        );
+
+        let vfs = GitVfs::new(repository, "v1").unwrap();
+        let pom = repository.join("app/pom.xml");
+        assert_eq!(vfs.get_files().unwrap(), vec![pom.clone()]);
+        assert_eq!(vfs.read_to_string(&pom).unwrap(), "<project>1</project>");
+        assert_eq!(vfs.read_prefix(&pom, 4).unwrap(), b"<pro");
+        assert_eq!(vfs.read_prefix(&pom, 100).unwrap(), b"<project>1</project>");
+        // the same process reads the next blobs
+        assert_eq!(vfs.read_to_string(&pom).unwrap(), "<project>1</project>");
+        assert!(vfs.read(&repository.join("README.md")).is_err());
+        assert_eq!(
+            vfs.canonicalize(&repository.join("app/../app")).unwrap(),
+            repository.join("app")
+        );
+        assert!(GitVfs::new(repository, "--help").is_err());
+        assert!(GitVfs::new(repository, "missing").is_err());
+    }
+}
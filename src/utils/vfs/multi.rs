@@ -0,0 +1,97 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::anyhow;
+
+use crate::utils::vfs::Vfs;
+
+/// The files of several roots (directories, archives or git revisions) scanned together,
+/// e.g. a service and the repository of its parent `pom.xml` checked out next to it.
+/// Each file keeps the path of its root.
+pub struct MultiVfs {
+    roots: Vec<Arc<dyn Vfs>>,
+}
+
+impl MultiVfs {
+    pub fn new(roots: Vec<Arc<dyn Vfs>>) -> Self {
+        MultiVfs { roots }
+    }
+
+    /// Get the filesystem of the root of a file, the deepest one for nested roots.
+    fn get_vfs(&self, path: &Path) -> Option<&Arc<dyn Vfs>> {
+        self.roots
+            .iter()
+            .filter(|r| path.starts_with(r.root()))
+            .max_by_key(|r| r.root().components().count())
+    }
+}
+
+impl Vfs for MultiVfs {
+    /// The first root
+    fn root(&self) -> &Path {
+        self.roots[0].root()
+    }
+
+    fn root_of(&self, path: &Path) -> &Path {
+        self.get_vfs(path).map(|r| r.root()).unwrap_or(self.root())
+    }
+
+    fn get_files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        let mut files = vec![];
+        for root in &self.roots {
+            for file in root.get_files()? {
+                // a file of nested roots is listed once
+                if self.root_of(&file) == root.root() {
+                    files.push(file);
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn read(&self, path: &Path) -> anyhow::Result<Vec<u8>> {
+        self.get_vfs(path)
+            .ok_or(anyhow!("{} is not in a scanned directory", path.display()))?
+            .read(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.get_vfs(path).is_some_and(|r| r.is_file(path))
+    }
+
+    fn canonicalize(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        self.get_vfs(path)
+            .ok_or(anyhow!("{} is not in a scanned directory", path.display()))?
+            .canonicalize(path)
+    }
+
+    /// A path can go from a root to another one, it is resolved in the root it ends in.
+    fn resolve_path(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        let mut error = None;
+        let mut external = None;
+        for root in &self.roots {
+            match root.resolve_path(path) {
+                Ok(p) if p.starts_with(root.root()) => return Ok(p),
+                Ok(p) => external = external.or(Some(p)),
+                Err(e) => error = error.or(Some(e)),
+            }
+        }
+        match (external, error) {
+            (Some(p), _) => Ok(p),
+            (None, Some(e)) => Err(e),
+            (None, None) => Err(anyhow!("{} is not in a scanned directory", path.display())),
+        }
+    }
+
+    fn read_to_string(&self, path: &Path) -> anyhow::Result<String> {
+        self.get_vfs(path)
+            .ok_or(anyhow!("{} is not in a scanned directory", path.display()))?
+            .read_to_string(path)
+    }
+
+    fn read_prefix(&self, path: &Path, length: usize) -> anyhow::Result<Vec<u8>> {
+        self.get_vfs(path)
+            .ok_or(anyhow!("{} is not in a scanned directory", path.display()))?
+            .read_prefix(path, length)
+    }
+}